@@ -18,6 +18,7 @@ async fn main() -> anyhow::Result<()> {
             track,
             artists,
             lyric,
+            ..
         } => {
             println!("{track} by {artists}'s lyric:\n{lyric}");
         }
@@ -3,6 +3,11 @@
 //! This crate provides a [`Client`] struct for retrieving a song's lyric.
 //!
 //! It ultilizes the [Genius](https://genius.com) website and its APIs to get lyric data.
+//! Synced (time-stamped) lyrics can also be retrieved from [LRCLIB](https://lrclib.net)
+//! or from local [LRC](https://en.wikipedia.org/wiki/LRC_(file_format)) files.
+//!
+//! Each lyric source implements the [`LyricProvider`] trait, and providers can be combined
+//! into a [`ProviderChain`], which queries them in a priority order.
 //!
 //! ## Example
 //!
@@ -17,6 +22,7 @@
 //!         track,
 //!         artists,
 //!         lyric,
+//!         ..
 //!     } => {
 //!         println!("{} by {}'s lyric:\n{}", track, artists, lyric);
 //!     }
@@ -28,22 +34,44 @@
 //! # }
 //! ```
 
+mod local;
+mod lrc;
+mod lrclib;
+mod provider;
+
+pub use local::LocalProvider;
+pub use lrc::{SyncedLine, SyncedLyric};
+pub use lrclib::LrclibProvider;
+pub use provider::{LyricProvider, ProviderChain};
+
 const SEARCH_BASE_URL: &str = "https://genius.com/api/search";
 
+/// A client retrieving lyrics from [Genius](https://genius.com)
 pub struct Client {
     http: reqwest::Client,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum LyricResult {
     Some {
         track: String,
         artists: String,
         lyric: String,
+        /// the lyric's time-stamped lines, if the lyric is synced
+        synced_lyric: Option<SyncedLyric>,
     },
     None,
 }
 
+/// A song whose lyric is queried from a [`LyricProvider`]
+#[derive(Debug, Clone)]
+pub struct LyricQuery {
+    pub track: String,
+    /// the song's artists, separated by commas
+    pub artists: String,
+    pub duration: Option<std::time::Duration>,
+}
+
 impl Client {
     pub fn new() -> Self {
         Self {
@@ -67,9 +95,16 @@ impl Client {
             .get(format!("{SEARCH_BASE_URL}?q={query}"))
             .send()
             .await?
-            .json::<search::Body>()
+            .text()
             .await?;
 
+        Self::parse_search_response(&body)
+    }
+
+    /// Parse the JSON body of a "genius.com" search response into song results.
+    pub fn parse_search_response(body: &str) -> anyhow::Result<Vec<search::Result>> {
+        let body = serde_json::from_str::<search::Body>(body)?;
+
         if body.meta.status != 200 {
             let message = match body.meta.message {
                 Some(m) => m,
@@ -94,6 +129,11 @@ impl Client {
     pub async fn retrieve_lyric(&self, url: &str) -> anyhow::Result<String> {
         let html = self.http.get(url).send().await?.text().await?;
         log::debug!("retrieve lyric from url={url}: html={html}");
+        Self::parse_lyric_page(html)
+    }
+
+    /// Parse the HTML content of a "genius.com" lyric page into the song's lyric.
+    pub fn parse_lyric_page(html: String) -> anyhow::Result<String> {
         let lyric = parse::parse(html)?;
         Ok(lyric.trim().to_string())
     }
@@ -128,6 +168,7 @@ impl Client {
             track: result.title,
             artists: result.artist_names,
             lyric: Self::process_lyric(lyric),
+            synced_lyric: None,
         })
     }
 }
@@ -138,6 +179,17 @@ impl Default for Client {
     }
 }
 
+#[async_trait::async_trait]
+impl LyricProvider for Client {
+    fn name(&self) -> &'static str {
+        "genius"
+    }
+
+    async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        Client::get_lyric(self, &format!("{} {}", query.track, query.artists)).await
+    }
+}
+
 /// Returns `query` without `remaster` & `remix` information from track/artist query.
 /// Returned value is lowercase.
 /// These caused wildly invalid lyrics to be found.
@@ -265,7 +317,9 @@ mod parse {
     }
 }
 
-mod search {
+pub mod search {
+    //! Data types of a "genius.com" search response
+
     use serde::Deserialize;
 
     #[derive(Debug, Deserialize)]
@@ -299,3 +353,61 @@ mod search {
         pub artist_names: String,
     }
 }
+
+#[cfg(test)]
+/// Get the path of a test fixture file
+fn fixture_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        std::fs::read_to_string(fixture_path(name)).unwrap()
+    }
+
+    #[test]
+    fn parse_genius_search_response() {
+        let results = Client::parse_search_response(&fixture("genius_search.json")).unwrap();
+        // non-song hits are ignored
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "Lantern Song");
+        assert_eq!(
+            results[0].artist_names,
+            "The Placeholders (Ft. Jane Example)"
+        );
+        assert_eq!(
+            results[0].url,
+            "https://genius.com/The-placeholders-lantern-song-lyrics"
+        );
+        assert_eq!(results[1].title, "Lantern Song (Live)");
+    }
+
+    #[test]
+    fn parse_genius_search_error() {
+        let err = Client::parse_search_response(&fixture("genius_search_error.json")).unwrap_err();
+        assert_eq!(err.to_string(), "Action forbidden for current scope");
+    }
+
+    #[test]
+    fn parse_genius_lyric_page() {
+        let lyric = Client::parse_lyric_page(fixture("genius_lyric.html")).unwrap();
+        assert_eq!(
+            lyric,
+            "[Verse 1]\nCarry the lantern down to the shore\nNobody's waiting there anymore\n\n\
+             [Chorus]\nLight it, light it\nLet it burn till the morning\n\
+             [Verse 2]\nFold up the map & follow the light"
+        );
+        // sections are separated by a single empty line
+        assert_eq!(
+            Client::process_lyric(lyric),
+            "[Verse 1]\nCarry the lantern down to the shore\nNobody's waiting there anymore\n\n\
+             [Chorus]\nLight it, light it\nLet it burn till the morning\n\n\
+             [Verse 2]\nFold up the map & follow the light"
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Retrieve lyrics from local `.lrc` files
+
+use std::path::{Path, PathBuf};
+
+use crate::{LyricProvider, LyricQuery, LyricResult, SyncedLyric};
+
+/// A lyric provider retrieving lyrics from `.lrc` files inside a local folder.
+///
+/// A lyric file should be named either `{artist} - {track}.lrc` or `{track}.lrc` (case-insensitive),
+/// in which `{artist}` can be any of the song's artists.
+/// Files without time tags are returned as plain lyrics.
+pub struct LocalProvider {
+    folder: PathBuf,
+}
+
+impl LocalProvider {
+    pub fn new(folder: impl Into<PathBuf>) -> Self {
+        Self {
+            folder: folder.into(),
+        }
+    }
+
+    /// Find a song's lyric inside the provider's folder
+    pub fn find_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        let Some(path) = find_lyric_file(&self.folder, &query.track, &query.artists)? else {
+            return Ok(LyricResult::None);
+        };
+        log::debug!("found local lyric file: {}", path.display());
+
+        let content = std::fs::read_to_string(&path)?;
+        let synced_lyric = SyncedLyric::parse(&content);
+        let lyric = match synced_lyric {
+            Some(ref synced_lyric) => synced_lyric.text(),
+            None => content.trim().to_string(),
+        };
+
+        Ok(LyricResult::Some {
+            track: query.track.clone(),
+            artists: query.artists.clone(),
+            lyric,
+            synced_lyric,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LyricProvider for LocalProvider {
+    fn name(&self) -> &'static str {
+        "local"
+    }
+
+    async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        self.find_lyric(query)
+    }
+}
+
+fn find_lyric_file(folder: &Path, track: &str, artists: &str) -> anyhow::Result<Option<PathBuf>> {
+    let track = normalize(track);
+    let mut candidates = artists
+        .split(',')
+        .map(|artist| format!("{} {track}", normalize(artist)))
+        .collect::<Vec<_>>();
+    candidates.push(track);
+
+    let mut best: Option<(usize, PathBuf)> = None;
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        if !path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("lrc"))
+        {
+            continue;
+        }
+        let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let stem = normalize(stem);
+        // files matching earlier candidates (i.e. with artist names) are preferred
+        if let Some(rank) = candidates.iter().position(|c| *c == stem) {
+            if !matches!(best, Some((r, _)) if r <= rank) {
+                best = Some((rank, path));
+            }
+        }
+    }
+
+    Ok(best.map(|(_, path)| path))
+}
+
+/// Normalize a name by lowercasing it and replacing non-alphanumeric characters with single spaces
+fn normalize(s: &str) -> String {
+    s.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(track: &str, artists: &str) -> LyricResult {
+        LocalProvider::new(crate::fixture_path("lyrics"))
+            .find_lyric(&LyricQuery {
+                track: track.to_string(),
+                artists: artists.to_string(),
+                duration: None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn find_synced_lyric_with_artist() {
+        // `{artist} - {track}.lrc` is preferred to `{track}.lrc`
+        let LyricResult::Some {
+            lyric,
+            synced_lyric,
+            ..
+        } = find("Lantern Song", "Jane Example, The Placeholders")
+        else {
+            panic!("a lyric should be found");
+        };
+        assert_eq!(
+            lyric,
+            "Carry the lantern down to the shore\nLight it, light it\nLight it, light it"
+        );
+        // the `offset` tag is applied to every line
+        assert_eq!(
+            synced_lyric
+                .unwrap()
+                .lines
+                .iter()
+                .map(|l| l.time_ms)
+                .collect::<Vec<_>>(),
+            [12_000, 15_500, 61_500]
+        );
+    }
+
+    #[test]
+    fn find_plain_lyric_without_artist() {
+        let LyricResult::Some {
+            lyric,
+            synced_lyric,
+            ..
+        } = find("Lantern Song!", "Someone Else")
+        else {
+            panic!("a lyric should be found");
+        };
+        assert_eq!(
+            lyric,
+            "Carry the lantern down to the shore\nNobody's waiting there anymore"
+        );
+        assert!(synced_lyric.is_none());
+    }
+
+    #[test]
+    fn find_lyric_file_extension() {
+        // the extension is case-insensitive and non-`.lrc` files are ignored
+        let LyricResult::Some { lyric, .. } = find("Quiet Harbor", "") else {
+            panic!("a lyric should be found");
+        };
+        assert_eq!(lyric, "Quiet harbor, quiet night");
+        assert!(matches!(find("Unknown", "Nobody"), LyricResult::None));
+    }
+}
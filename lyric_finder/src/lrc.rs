@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+/// A time-stamped lyric line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedLine {
+    /// the line's start time in milliseconds
+    pub time_ms: u64,
+    pub text: String,
+}
+
+/// A synced lyric, whose lines are sorted by their start time
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncedLyric {
+    pub lines: Vec<SyncedLine>,
+}
+
+impl SyncedLyric {
+    /// Parse a lyric in the [LRC](https://en.wikipedia.org/wiki/LRC_(file_format)) format.
+    ///
+    /// A line can have multiple time tags (e.g. `[00:12.00][01:30.50]chorus`),
+    /// and the `[offset:+/-ms]` tag is applied to every line's time.
+    /// Return `None` if the lyric doesn't have any time-stamped line.
+    pub fn parse(content: &str) -> Option<Self> {
+        let mut offset_ms = 0;
+        let mut lines = Vec::new();
+
+        for line in content.lines() {
+            let mut rest = line.trim();
+            let mut times = Vec::new();
+            while let Some(tag) = rest.strip_prefix('[') {
+                let Some((tag, remaining)) = tag.split_once(']') else {
+                    break;
+                };
+                rest = remaining;
+                match parse_time_tag(tag) {
+                    Some(time) => times.push(time),
+                    None => {
+                        if let Some(offset) = tag.strip_prefix("offset:") {
+                            offset_ms = offset.trim().parse::<i64>().unwrap_or_default();
+                        }
+                    }
+                }
+            }
+            let text = rest.trim();
+            lines.extend(times.into_iter().map(|time_ms| SyncedLine {
+                time_ms,
+                text: text.to_string(),
+            }));
+        }
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        // a positive offset shifts the lyric's lines up (i.e. makes them appear sooner)
+        for line in &mut lines {
+            line.time_ms = line.time_ms.saturating_add_signed(-offset_ms);
+        }
+        lines.sort_by_key(|l| l.time_ms);
+        Some(Self { lines })
+    }
+
+    /// Get the index of the line being sung at a given playback position (in milliseconds)
+    pub fn line_index_at(&self, position_ms: i64) -> Option<usize> {
+        let position_ms = u64::try_from(position_ms).ok()?;
+        self.lines
+            .partition_point(|l| l.time_ms <= position_ms)
+            .checked_sub(1)
+    }
+
+    /// Get the lyric's text without time tags
+    pub fn text(&self) -> String {
+        self.lines
+            .iter()
+            .map(|l| l.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Parse a time tag in the `mm:ss`, `mm:ss.xx` or `mm:ss.xxx` format into milliseconds
+fn parse_time_tag(tag: &str) -> Option<u64> {
+    let (mins, secs) = tag.split_once(':')?;
+    let mins = mins.trim().parse::<u64>().ok()?;
+    let (secs, frac) = secs.split_once(['.', ':']).unwrap_or((secs, ""));
+    let secs = secs.trim().parse::<u64>().ok()?;
+    let frac_ms = match frac.len() {
+        0 => 0,
+        1 => frac.parse::<u64>().ok()? * 100,
+        2 => frac.parse::<u64>().ok()? * 10,
+        _ => frac.get(..3)?.parse::<u64>().ok()?,
+    };
+    Some((mins * 60 + secs) * 1000 + frac_ms)
+}
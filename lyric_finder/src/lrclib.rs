@@ -0,0 +1,189 @@
+//! Retrieve synced lyrics from [LRCLIB](https://lrclib.net), an open lyric database
+
+use serde::Deserialize;
+
+use crate::{LyricProvider, LyricQuery, LyricResult, SyncedLyric};
+
+const SEARCH_BASE_URL: &str = "https://lrclib.net/api/search";
+
+/// the maximum difference (in seconds) between a result's duration and the queried song's duration
+const MAX_DURATION_DIFF_IN_SECS: f64 = 10.0;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchResult {
+    track_name: String,
+    artist_name: String,
+    duration: Option<f64>,
+    plain_lyrics: Option<String>,
+    synced_lyrics: Option<String>,
+}
+
+/// A lyric provider retrieving synced lyrics from [LRCLIB](https://lrclib.net).
+///
+/// A plain lyric is returned if no synced lyric is found.
+pub struct LrclibProvider {
+    http: reqwest::Client,
+}
+
+impl LrclibProvider {
+    pub fn new() -> Self {
+        Self {
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Construct a provider reusing an existing http client
+    pub fn from_http_client(http: &reqwest::Client) -> Self {
+        Self { http: http.clone() }
+    }
+
+    /// Get the lyric of a song from [LRCLIB](https://lrclib.net)
+    pub async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        log::debug!("search LRCLIB: query={query:?}");
+
+        let body = self
+            .http
+            .get(SEARCH_BASE_URL)
+            .query(&[
+                ("track_name", query.track.as_str()),
+                ("artist_name", query.artists.as_str()),
+            ])
+            .header(
+                reqwest::header::USER_AGENT,
+                concat!("lyric_finder/", env!("CARGO_PKG_VERSION")),
+            )
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+
+        Self::parse_search_response(&body, query)
+    }
+
+    /// Parse the JSON body of a LRCLIB search response into the lyric best matching a `query`.
+    ///
+    /// If the query's duration is specified, results whose durations don't match it are ignored.
+    pub fn parse_search_response(body: &str, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        let mut results = serde_json::from_str::<Vec<SearchResult>>(body)?
+            .into_iter()
+            .filter(|r| match (query.duration, r.duration) {
+                (Some(expected), Some(actual)) => {
+                    (expected.as_secs_f64() - actual).abs() <= MAX_DURATION_DIFF_IN_SECS
+                }
+                _ => true,
+            })
+            .collect::<Vec<_>>();
+
+        // prefer results with synced lyrics
+        let Some(index) = results
+            .iter()
+            .position(|r| r.synced_lyrics.is_some())
+            .or_else(|| {
+                results
+                    .iter()
+                    .position(|r| r.plain_lyrics.as_deref().is_some_and(|l| !l.is_empty()))
+            })
+        else {
+            return Ok(LyricResult::None);
+        };
+        let result = results.swap_remove(index);
+
+        let synced_lyric = result.synced_lyrics.as_deref().and_then(SyncedLyric::parse);
+        let lyric = match (&synced_lyric, result.plain_lyrics) {
+            (_, Some(lyric)) if !lyric.is_empty() => lyric,
+            (Some(synced_lyric), _) => synced_lyric.text(),
+            _ => return Ok(LyricResult::None),
+        };
+
+        Ok(LyricResult::Some {
+            track: result.track_name,
+            artists: result.artist_name,
+            lyric,
+            synced_lyric,
+        })
+    }
+}
+
+impl Default for LrclibProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl LyricProvider for LrclibProvider {
+    fn name(&self) -> &'static str {
+        "lrclib"
+    }
+
+    async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        LrclibProvider::get_lyric(self, query).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn query(duration: Option<Duration>) -> LyricQuery {
+        LyricQuery {
+            track: "Lantern Song".to_string(),
+            artists: "The Placeholders".to_string(),
+            duration,
+        }
+    }
+
+    fn parse(duration: Option<Duration>) -> LyricResult {
+        let body = std::fs::read_to_string(crate::fixture_path("lrclib_search.json")).unwrap();
+        LrclibProvider::parse_search_response(&body, &query(duration)).unwrap()
+    }
+
+    #[test]
+    fn parse_search_response_matching_duration() {
+        // the extended mix's duration doesn't match, and a synced lyric is preferred to a plain lyric
+        let LyricResult::Some {
+            track,
+            artists,
+            lyric,
+            synced_lyric,
+        } = parse(Some(Duration::from_secs(202)))
+        else {
+            panic!("a lyric should be found");
+        };
+        assert_eq!(track, "Lantern Song");
+        assert_eq!(artists, "The Placeholders");
+        assert_eq!(
+            lyric,
+            "Carry the lantern down to the shore\nNobody's waiting there anymore\n\nLight it, light it"
+        );
+        let lines = synced_lyric.unwrap().lines;
+        assert_eq!(
+            lines.iter().map(|l| l.time_ms).collect::<Vec<_>>(),
+            [12_000, 15_300, 18_000, 21_750]
+        );
+        assert_eq!(lines[2].text, "");
+    }
+
+    #[test]
+    fn parse_search_response_without_duration() {
+        let LyricResult::Some { synced_lyric, .. } = parse(None) else {
+            panic!("a lyric should be found");
+        };
+        assert_eq!(synced_lyric.unwrap().lines[0].time_ms, 20_100);
+    }
+
+    #[test]
+    fn parse_search_response_not_found() {
+        assert!(matches!(
+            parse(Some(Duration::from_secs(300))),
+            LyricResult::None
+        ));
+        assert!(matches!(
+            LrclibProvider::parse_search_response("[]", &query(None)).unwrap(),
+            LyricResult::None
+        ));
+    }
+}
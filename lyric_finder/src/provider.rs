@@ -0,0 +1,69 @@
+use crate::{LyricQuery, LyricResult};
+
+/// A source of song lyrics
+#[async_trait::async_trait]
+pub trait LyricProvider: Send + Sync {
+    /// Get the provider's name
+    fn name(&self) -> &'static str;
+
+    /// Get the lyric of a song, return [`LyricResult::None`] if the provider doesn't have it
+    async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult>;
+}
+
+/// A chain of lyric providers, which are queried in order until one of them finds the lyric.
+///
+/// Errors from a provider are logged and the next provider is tried.
+/// An error is returned only if every provider fails.
+#[derive(Default)]
+pub struct ProviderChain {
+    providers: Vec<Box<dyn LyricProvider>>,
+}
+
+impl ProviderChain {
+    pub fn new(providers: Vec<Box<dyn LyricProvider>>) -> Self {
+        Self { providers }
+    }
+
+    /// Append a provider to the chain
+    pub fn push(&mut self, provider: impl LyricProvider + 'static) {
+        self.providers.push(Box::new(provider));
+    }
+
+    /// Check if the chain has no provider
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+#[async_trait::async_trait]
+impl LyricProvider for ProviderChain {
+    fn name(&self) -> &'static str {
+        "chain"
+    }
+
+    async fn get_lyric(&self, query: &LyricQuery) -> anyhow::Result<LyricResult> {
+        let mut last_err = None;
+        let mut is_not_found = false;
+        for provider in &self.providers {
+            match provider.get_lyric(query).await {
+                Ok(LyricResult::None) => {
+                    log::debug!("lyric not found from provider {}", provider.name());
+                    is_not_found = true;
+                }
+                Ok(result) => return Ok(result),
+                Err(err) => {
+                    log::warn!(
+                        "failed to get lyric from provider {}: {err:#}",
+                        provider.name()
+                    );
+                    last_err = Some(err);
+                }
+            }
+        }
+
+        match last_err {
+            Some(err) if !is_not_found => Err(err),
+            _ => Ok(LyricResult::None),
+        }
+    }
+}
@@ -34,7 +34,7 @@ impl AuthConfig {
         };
 
         let cache = Cache::new(
-            Some(configs.cache_folder.clone()),
+            Some(configs.profile_cache_folder()),
             None,
             audio_cache_folder,
             None,
@@ -1,20 +1,22 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fs::{create_dir_all, remove_dir_all},
     io::Write,
     net::SocketAddr,
+    sync::Arc,
 };
 
 use anyhow::{Context as _, Result};
 use rand::seq::SliceRandom;
+#[cfg(unix)]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::UdpSocket;
 use tracing::Instrument;
 
 use crate::{
     cli::Request,
-    client::{Client, PlayerRequest},
-    config::get_cache_folder_path,
-    state::{Context, ContextId, Playback, PlaybackMetadata, SharedState},
+    client::{Client, ClientRequest, PlayerRequest, PLAYLIST_ITEMS_BATCH_SIZE},
+    state::{Context, ContextId, Mutex, Playback, PlaybackMetadata, SharedState},
 };
 use rspotify::{
     model::*,
@@ -23,8 +25,231 @@ use rspotify::{
 
 use super::*;
 
-pub async fn start_socket(client: Client, socket: UdpSocket, state: Option<SharedState>) {
+/// UDP subscribers of the client socket's events, each is mapped to its last renewal time
+type UdpSubscribers = Arc<Mutex<HashMap<SocketAddr, std::time::Instant>>>;
+
+const NO_RUNNING_INSTANCE_SUBSCRIBE_ERROR: &str =
+    "Subscribing to player events requires a running `spotify_player` instance";
+
+/// Start the application's client sockets for handling CLI commands.
+///
+/// On Unix systems, CLI commands are handled via a Unix domain socket. The legacy UDP socket
+/// is only started if `enable_udp_client_socket` is set or on systems without Unix domain sockets.
+/// The HTTP API server (`http-api` feature only) is started if `http_api` is configured.
+pub async fn start_client_sockets(client: Client, state: SharedState) {
+    let configs = config::get_config();
+    let subscribers = events::Subscribers::default();
+    tokio::task::spawn(events::start_event_publisher(
+        state.clone(),
+        subscribers.clone(),
+    ));
+
+    let mut tasks = Vec::new();
+
+    #[cfg(feature = "http-api")]
+    if let Some(config) = configs.http_api_config() {
+        tasks.push(tokio::task::spawn(http::start_http_server(
+            config.clone(),
+            client.clone(),
+            state.clone(),
+            subscribers.clone(),
+        )));
+    }
+
+    #[cfg(unix)]
+    {
+        let path = client_socket_path(&configs);
+        tracing::info!("Starting a client socket at {}", path.display());
+        match bind_unix_listener(&path) {
+            Ok(listener) => tasks.push(tokio::task::spawn(start_unix_socket(
+                client.clone(),
+                listener,
+                Some(state.clone()),
+                subscribers.clone(),
+            ))),
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to create a client socket for handling CLI commands: {err:#}"
+                )
+            }
+        }
+    }
+
+    if !cfg!(unix) || configs.app_config.enable_udp_client_socket {
+        let port = configs.app_config.client_port;
+        tracing::info!("Starting a UDP client socket at 127.0.0.1:{port}");
+        match UdpSocket::bind(("127.0.0.1", port)).await {
+            Ok(socket) => tasks.push(tokio::task::spawn(start_udp_socket(
+                client,
+                socket,
+                Some(state),
+                subscribers,
+            ))),
+            Err(err) => {
+                tracing::warn!(
+                    "Failed to create a UDP client socket for handling CLI commands: {err:#}"
+                )
+            }
+        }
+    }
+
+    for task in tasks {
+        task.await.unwrap_or_default();
+    }
+}
+
+/// Create a Unix domain socket listener at `path`, which is only accessible by the current user.
+///
+/// The socket is created inside a folder only accessible by the current user, so that
+/// the socket is never exposed to other users regardless of the process's umask.
+#[cfg(unix)]
+pub fn bind_unix_listener(path: &std::path::Path) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let folder = path
+        .parent()
+        .context("the socket's path should have a parent folder")?;
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(folder)?;
+    // restrict the permissions of a folder created with looser permissions, which fails
+    // if the folder is owned by another user
+    std::fs::set_permissions(folder, std::fs::Permissions::from_mode(0o700))
+        .with_context(|| format!("restrict the permissions of {}", folder.display()))?;
+
+    if path.exists() {
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            anyhow::bail!("another client is already listening on {}", path.display());
+        }
+        // remove the stale socket file left by a previous instance
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(tokio::net::UnixListener::bind(path)?)
+}
+
+/// Handle CLI requests sent to the client's Unix domain socket
+#[cfg(unix)]
+pub async fn start_unix_socket(
+    client: Client,
+    listener: tokio::net::UnixListener,
+    state: Option<SharedState>,
+    subscribers: events::Subscribers,
+) {
+    use std::os::unix::fs::MetadataExt;
+
+    // only connections from the socket's owner are accepted
+    let owner_uid = listener
+        .local_addr()
+        .ok()
+        .and_then(|addr| std::fs::metadata(addr.as_pathname()?).ok())
+        .map(|metadata| metadata.uid());
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::warn!("Failed to accept a connection from the socket: {err:#}");
+                continue;
+            }
+        };
+
+        let peer_uid = stream.peer_cred().ok().map(|cred| cred.uid());
+        if owner_uid.is_none() || peer_uid != owner_uid {
+            tracing::warn!(
+                "Rejected a socket connection from an unauthorized user (uid={peer_uid:?})"
+            );
+            continue;
+        }
+
+        tokio::task::spawn({
+            let client = client.clone();
+            let state = state.clone();
+            let subscribers = subscribers.clone();
+            async move {
+                if let Err(err) = handle_unix_stream(&client, &state, &subscribers, stream).await {
+                    tracing::warn!("Failed to handle a socket connection: {err:#}");
+                }
+            }
+        });
+    }
+}
+
+/// Handle requests sent over a connection to the client's Unix domain socket
+/// until the connection is closed
+#[cfg(unix)]
+async fn handle_unix_stream(
+    client: &Client,
+    state: &Option<SharedState>,
+    subscribers: &events::Subscribers,
+    mut stream: tokio::net::UnixStream,
+) -> Result<()> {
+    loop {
+        let req_buf = match read_frame(&mut stream).await {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+        let request: Request =
+            serde_json::from_slice(&req_buf).context("deserialize the socket request")?;
+
+        if let Request::Subscribe = request {
+            // a subscription takes over the connection, player events are streamed
+            // to the subscriber until the connection is closed
+            if state.is_none() {
+                let response = Response::Err(NO_RUNNING_INSTANCE_SUBSCRIBE_ERROR.into());
+                return write_frame(&mut stream, &serde_json::to_vec(&response)?).await;
+            }
+            let event_sub = events::subscribe(subscribers);
+            write_frame(&mut stream, &serde_json::to_vec(&Response::Ok(Vec::new()))?).await?;
+            while let Ok(event) = event_sub.recv_async().await {
+                write_frame(&mut stream, &serde_json::to_vec(&event)?).await?;
+            }
+            return Ok(());
+        }
+
+        let response = handle_request(client, state, request).await;
+        write_frame(&mut stream, &serde_json::to_vec(&response)?).await?;
+    }
+}
+
+/// Read a length-prefixed message from the client's Unix domain socket
+#[cfg(unix)]
+async fn read_frame(stream: &mut tokio::net::UnixStream) -> std::io::Result<Vec<u8>> {
+    let len = check_frame_size(stream.read_u64().await?)?;
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Write a length-prefixed message to the client's Unix domain socket
+#[cfg(unix)]
+async fn write_frame(stream: &mut tokio::net::UnixStream, data: &[u8]) -> Result<()> {
+    stream.write_u64(data.len() as u64).await?;
+    stream.write_all(data).await?;
+    stream.flush().await?;
+    Ok(())
+}
+
+/// Handle CLI requests sent to the client's legacy UDP socket
+pub async fn start_udp_socket(
+    client: Client,
+    socket: UdpSocket,
+    state: Option<SharedState>,
+    subscribers: events::Subscribers,
+) {
     let mut buf = [0; MAX_REQUEST_SIZE];
+    let socket = Arc::new(socket);
+    let udp_subscribers = UdpSubscribers::default();
+
+    if state.is_some() {
+        tokio::task::spawn(forward_events_to_udp_subscribers(
+            socket.clone(),
+            events::subscribe(&subscribers),
+            udp_subscribers.clone(),
+        ));
+    }
 
     loop {
         match socket.recv_from(&mut buf).await {
@@ -45,31 +270,106 @@ pub async fn start_socket(client: Client, socket: UdpSocket, state: Option<Share
                     }
                 };
 
-                let span = tracing::info_span!("socket_request", request = ?request, dest_addr = ?dest_addr);
-
-                async {
-                    let response = match handle_socket_request(&client, &state, request).await {
-                        Err(err) => {
-                            tracing::error!("Failed to handle socket request: {err:#}");
-                            let msg = format!("Bad request: {err:#}");
-                            Response::Err(msg.into_bytes())
-                        }
-                        Ok(data) => Response::Ok(data),
-                    };
-                    send_response(response, &socket, dest_addr)
+                if let Request::Subscribe = request {
+                    handle_udp_subscribe_request(&socket, &state, &udp_subscribers, dest_addr)
                         .await
                         .unwrap_or_default();
-
-                    tracing::info!("Successfully handled the socket request.",);
+                    continue;
                 }
-                .instrument(span)
-                .await;
+
+                let response = handle_request(&client, &state, request).await;
+                send_udp_response(response, &socket, dest_addr)
+                    .await
+                    .unwrap_or_default();
             }
         }
     }
 }
 
-async fn send_response(
+/// Handle a socket request and construct the corresponding response
+pub async fn handle_request(
+    client: &Client,
+    state: &Option<SharedState>,
+    request: Request,
+) -> Response {
+    let span = tracing::info_span!("socket_request", request = ?request);
+
+    async {
+        match handle_socket_request(client, state, request).await {
+            Err(err) => {
+                tracing::error!("Failed to handle socket request: {err:#}");
+                let msg = format!("Bad request: {err:#}");
+                Response::Err(msg.into_bytes())
+            }
+            Ok(data) => {
+                tracing::info!("Successfully handled the socket request.");
+                Response::Ok(data)
+            }
+        }
+    }
+    .instrument(span)
+    .await
+}
+
+/// Forward player events to the UDP socket's subscribers
+async fn forward_events_to_udp_subscribers(
+    socket: Arc<UdpSocket>,
+    event_sub: flume::Receiver<Event>,
+    udp_subscribers: UdpSubscribers,
+) {
+    while let Ok(event) = event_sub.recv_async().await {
+        // remove subscribers that haven't renewed their subscriptions
+        let dest_addrs = {
+            let mut udp_subscribers = udp_subscribers.lock();
+            udp_subscribers.retain(|_, renewed| renewed.elapsed() < SUBSCRIPTION_TIMEOUT);
+            udp_subscribers.keys().copied().collect::<Vec<_>>()
+        };
+        if dest_addrs.is_empty() {
+            continue;
+        }
+
+        let data = match serde_json::to_vec(&event) {
+            Ok(data) => data,
+            Err(err) => {
+                tracing::error!("Failed to serialize the player event {event:?}: {err:#}");
+                continue;
+            }
+        };
+        for dest_addr in &dest_addrs {
+            if let Err(err) = socket.send_to(&data, dest_addr).await {
+                tracing::warn!("Failed to send the player event to {dest_addr}: {err:#}");
+            }
+        }
+    }
+}
+
+/// Handle a subscription request sent to the UDP socket, which either registers a new subscriber
+/// or renews the subscription of an existing one.
+///
+/// A response is only sent when a new subscriber is registered.
+async fn handle_udp_subscribe_request(
+    socket: &UdpSocket,
+    state: &Option<SharedState>,
+    udp_subscribers: &UdpSubscribers,
+    dest_addr: SocketAddr,
+) -> Result<()> {
+    if state.is_none() {
+        let response = Response::Err(NO_RUNNING_INSTANCE_SUBSCRIBE_ERROR.into());
+        return send_udp_response(response, socket, dest_addr).await;
+    }
+
+    let is_new_subscriber = udp_subscribers
+        .lock()
+        .insert(dest_addr, std::time::Instant::now())
+        .is_none();
+    if is_new_subscriber {
+        tracing::info!("Got a new event subscriber: {dest_addr}");
+        send_udp_response(Response::Ok(Vec::new()), socket, dest_addr).await?;
+    }
+    Ok(())
+}
+
+async fn send_udp_response(
     response: Response,
     socket: &UdpSocket,
     dest_addr: SocketAddr,
@@ -94,12 +394,33 @@ async fn current_playback(
     match state {
         Some(ref state) => Ok(state.player.read().current_playback()),
         None => client
-            .current_playback(None, None::<Vec<_>>)
+            .current_playback(None, Some(&crate::client::PLAYBACK_ADDITIONAL_TYPES))
             .await
             .context("get current playback"),
     }
 }
 
+/// Get the current playback rendered with a playback template,
+/// an empty text is returned if there is no playback
+async fn handle_get_playback_text_request(
+    client: &Client,
+    state: &Option<SharedState>,
+    format: &str,
+) -> Result<Vec<u8>> {
+    let template = crate::template::Template::parse(format).context("invalid playback format")?;
+    let extras = match state {
+        Some(state) => crate::template::PlaybackExtras::new(state),
+        None => crate::template::PlaybackExtras::default(),
+    };
+    let text = current_playback(client, state)
+        .await?
+        .as_ref()
+        .and_then(|playback| crate::template::PlaybackInfo::from_playback(playback, extras))
+        .map(|info| template.render_string(&info))
+        .unwrap_or_default();
+    Ok(text.into_bytes())
+}
+
 async fn handle_socket_request(
     client: &Client,
     state: &Option<SharedState>,
@@ -111,6 +432,9 @@ async fn handle_socket_request(
 
     match request {
         Request::Get(GetRequest::Key(key)) => handle_get_key_request(client, state, key).await,
+        Request::Get(GetRequest::PlaybackText { format }) => {
+            handle_get_playback_text_request(client, state, &format).await
+        }
         Request::Get(GetRequest::Item(item_type, id_or_name)) => {
             handle_get_item_request(client, item_type, id_or_name).await
         }
@@ -162,16 +486,78 @@ async fn handle_socket_request(
             Ok(Vec::new())
         }
         Request::Playlist(command) => {
-            let resp = handle_playlist_request(client, command).await?;
+            let resp = handle_playlist_request(client, state, command).await?;
             Ok(resp.into_bytes())
         }
         Request::Search { query } => {
             let resp = handle_search_request(client, query).await?;
             Ok(resp)
         }
+        Request::Undo { redo } => {
+            let Some(state) = state else {
+                anyhow::bail!("undo requires a running application with the edit journal");
+            };
+            let resp = match (client.undo(state, redo).await?, redo) {
+                (Some(desc), false) => format!("Undid: {desc}"),
+                (Some(desc), true) => format!("Redid: {desc}"),
+                (None, false) => "Nothing to undo".to_string(),
+                (None, true) => "Nothing to redo".to_string(),
+            };
+            Ok(resp.into_bytes())
+        }
+        Request::Timer(command) => {
+            let Some(state) = state else {
+                anyhow::bail!("timer requires a running application");
+            };
+            handle_timer_request(client, state, command).await
+        }
+        Request::Subscribe => anyhow::bail!("subscription request should be handled separately"),
     }
 }
 
+async fn handle_timer_request(
+    client: &Client,
+    state: &SharedState,
+    command: TimerCommand,
+) -> Result<Vec<u8>> {
+    let spec = match command {
+        TimerCommand::Set(spec) => Some(spec),
+        TimerCommand::Cancel => None,
+        TimerCommand::Status => {
+            let now = chrono::Local::now();
+            let sleep_timer = state.player.read().sleep_timer.clone();
+            let sleep_timer = sleep_timer.map(|timer| {
+                serde_json::json!({
+                    "mode": timer.mode.to_string(),
+                    "remaining_secs": state.sleep_timer_remaining().map(|d| d.num_seconds().max(0)),
+                })
+            });
+            let schedules = config::get_config()
+                .app_config
+                .schedules
+                .iter()
+                .map(|schedule| {
+                    serde_json::json!({
+                        "schedule": schedule,
+                        "next_run": schedule.next_run(now).map(|t| t.to_rfc3339()),
+                    })
+                })
+                .collect::<Vec<_>>();
+            return Ok(serde_json::to_vec(&serde_json::json!({
+                "sleep_timer": sleep_timer,
+                "schedules": schedules,
+            }))?);
+        }
+    };
+
+    if let Some(volume) = state.set_sleep_timer(spec)? {
+        client
+            .handle_request(state, ClientRequest::Player(PlayerRequest::Volume(volume)))
+            .await?;
+    }
+    Ok(Vec::new())
+}
+
 async fn handle_get_key_request(
     client: &Client,
     state: &Option<SharedState>,
@@ -435,6 +821,7 @@ async fn handle_playback_request(
                     Ok(playback) => {
                         // update application's states
                         state.player.write().buffered_playback = playback;
+                        state.render.notify();
                         client.update_playback(&state);
                     }
                     Err(err) => {
@@ -454,7 +841,11 @@ async fn handle_playback_request(
     Ok(())
 }
 
-async fn handle_playlist_request(client: &Client, command: PlaylistCommand) -> Result<String> {
+async fn handle_playlist_request(
+    client: &Client,
+    state: &Option<SharedState>,
+    command: PlaylistCommand,
+) -> Result<String> {
     let uid = client.current_user().await?.id;
 
     match command {
@@ -542,7 +933,7 @@ async fn handle_playlist_request(client: &Client, command: PlaylistCommand) -> R
         }
         PlaylistCommand::Sync { id, delete } => {
             // Get import dir/file
-            let imports_dir = get_cache_folder_path()?.join("imports");
+            let imports_dir = config::get_config().profile_cache_folder().join("imports");
 
             let mut result = String::new();
 
@@ -586,10 +977,178 @@ async fn handle_playlist_request(client: &Client, command: PlaylistCommand) -> R
 
             Ok(result)
         }
+        PlaylistCommand::Export { id, format } => playlist_export(client, id, format).await,
+        PlaylistCommand::ImportFile {
+            path,
+            format,
+            name,
+            to,
+        } => playlist_import_file(client, uid, &path, format, name, to).await,
+        PlaylistCommand::Materialize { name } => {
+            crate::smart_playlist::materialize_smart_playlists(
+                client,
+                state.as_ref(),
+                name.as_deref(),
+            )
+            .await
+        }
+    }
+}
+
+/// Exports a playlist into a playlist file's content
+async fn playlist_export(
+    client: &Client,
+    id: PlaylistId<'static>,
+    format: PlaylistFileFormat,
+) -> Result<String> {
+    let (playlist, items) = client
+        .playlist_with_items(id.as_ref())
+        .await
+        .context(format!("Cannot export playlist {}.", id.id()))?;
+
+    let playlist = PlaylistFile {
+        name: Some(playlist.name),
+        tracks: items
+            .into_iter()
+            .filter_map(|item| match item.track {
+                Some(PlayableItem::Track(track)) => Some(track),
+                _ => None,
+            })
+            .map(|t| PlaylistFileTrack {
+                uri: t.id.map(|id| id.uri()),
+                title: t.name,
+                artists: t
+                    .artists
+                    .into_iter()
+                    .map(|a| a.name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                album: t.album.name,
+                duration_ms: t.duration.num_milliseconds().try_into().ok(),
+                isrc: t.external_ids.get("isrc").cloned(),
+            })
+            .collect(),
+    };
+    playlist.to_string(format)
+}
+
+/// Imports tracks from a playlist file into a new playlist or an existing playlist `to`.
+///
+/// Each track is resolved to a Spotify track by its Spotify URI if specified, or by searching
+/// its ISRC, or by searching its title and artist. Tracks that cannot be resolved are reported.
+async fn playlist_import_file(
+    client: &Client,
+    uid: UserId<'static>,
+    path: &std::path::Path,
+    format: PlaylistFileFormat,
+    name: Option<String>,
+    to: Option<PlaylistId<'static>>,
+) -> Result<String> {
+    let content =
+        std::fs::read_to_string(path).context(format!("Read playlist file {}", path.display()))?;
+    let playlist = PlaylistFile::parse(&content, format)?;
+
+    let mut track_ids = Vec::new();
+    let mut unmatched = Vec::new();
+    for (i, track) in playlist.tracks.iter().enumerate() {
+        match resolve_playlist_file_track(client, track).await? {
+            Some(id) => track_ids.push(id),
+            None => unmatched.push((i + 1, track.description())),
+        }
     }
+
+    let (to_id, to_name) = match to {
+        Some(id) => {
+            let playlist = client
+                .playlist(id.as_ref(), None, None)
+                .await
+                .context(format!("Cannot import into {}.", id.id()))?;
+            (id, playlist.name)
+        }
+        None => {
+            let name = name
+                .or(playlist.name)
+                .or_else(|| {
+                    path.file_stem()
+                        .and_then(|s| s.to_str())
+                        .map(|s| s.to_string())
+                })
+                .unwrap_or_default();
+            let playlist = client
+                .user_playlist_create(uid, &name, None, None, None)
+                .await?;
+            (playlist.id, playlist.name)
+        }
+    };
+
+    for ids in track_ids.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+        client
+            .playlist_add_items(
+                to_id.as_ref(),
+                ids.iter().map(|id| PlayableId::Track(id.as_ref())),
+                None,
+            )
+            .await?;
+    }
+
+    let mut result = format!(
+        "Imported {}/{} tracks from {} into {}:{}\n",
+        track_ids.len(),
+        playlist.tracks.len(),
+        path.display(),
+        to_id.id(),
+        to_name
+    );
+    if !unmatched.is_empty() {
+        result += "Unmatched tracks: \n";
+        for (row, desc) in unmatched {
+            result += &format!("    {row}: {desc}\n");
+        }
+    }
+
+    Ok(result)
+}
+
+/// Resolves a track in a playlist file to a Spotify track
+async fn resolve_playlist_file_track(
+    client: &Client,
+    track: &PlaylistFileTrack,
+) -> Result<Option<TrackId<'static>>> {
+    if let Some(id) = track.spotify_id() {
+        return Ok(TrackId::from_id(id.to_owned()).ok());
+    }
+
+    if let Some(isrc) = &track.isrc {
+        if let Some(id) = search_first_track(client, &format!("isrc:{isrc}")).await? {
+            return Ok(Some(id));
+        }
+    }
+
+    if track.title.is_empty() {
+        return Ok(None);
+    }
+    let mut query = format!("track:{}", track.title);
+    if let Some(artist) = track
+        .artists
+        .split(',')
+        .map(str::trim)
+        .find(|a| !a.is_empty())
+    {
+        query += &format!(" artist:{artist}");
+    }
+    search_first_track(client, &query).await
 }
 
-const TRACK_BUFFER_CAP: usize = 100;
+/// Searches for tracks matching a query and returns the ID of the first track
+async fn search_first_track(client: &Client, query: &str) -> Result<Option<TrackId<'static>>> {
+    match client
+        .search_specific_type(query, SearchType::Track)
+        .await?
+    {
+        SearchResult::Tracks(page) => Ok(page.items.into_iter().find_map(|t| t.id)),
+        _ => anyhow::bail!("expect a track search result"),
+    }
+}
 
 /// Imports a playlist into another playlist.
 ///
@@ -631,7 +1190,7 @@ async fn playlist_import(
     };
 
     // Get import dir/file
-    let cache_dir = get_cache_folder_path()?;
+    let cache_dir = config::get_config().profile_cache_folder();
     let imports_dir = cache_dir.join("imports");
     let to_dir = imports_dir.join(import_to.id());
     let from_file = to_dir.join(import_from.id());
@@ -673,7 +1232,7 @@ async fn playlist_import(
             for t in &deleted_hash_set {
                 track_buff.push(PlayableId::Track(t.id.as_ref()));
 
-                if track_buff.len() == TRACK_BUFFER_CAP {
+                if track_buff.len() == PLAYLIST_ITEMS_BATCH_SIZE {
                     client
                         .playlist_remove_all_occurrences_of_items(
                             import_to.as_ref(),
@@ -706,7 +1265,7 @@ async fn playlist_import(
     for t in &new_tracks_hash_set {
         track_buff.push(PlayableId::Track(t.id.as_ref()));
 
-        if track_buff.len() == TRACK_BUFFER_CAP {
+        if track_buff.len() == PLAYLIST_ITEMS_BATCH_SIZE {
             client
                 .playlist_add_items(import_to.as_ref(), track_buff, None)
                 .await?;
@@ -1,7 +1,8 @@
 use clap::{builder::EnumValueParser, value_parser, Arg, ArgAction, ArgGroup, Command};
 use clap_complete::Shell;
 
-use super::{ContextType, ItemType, Key};
+use super::{ContextType, ItemType, Key, PlaylistFileFormat};
+use crate::state::HistoryPeriod;
 
 pub fn init_connect_subcommand() -> Command {
     add_id_or_name_group(Command::new("connect").about("Connect to a Spotify device"))
@@ -12,11 +13,19 @@ pub fn init_get_subcommand() -> Command {
         .about("Get Spotify data")
         .subcommand_required(true)
         .subcommand(
-            Command::new("key").about("Get data by key").arg(
-                Arg::new("key")
-                    .value_parser(EnumValueParser::<Key>::new())
-                    .required(true),
-            ),
+            Command::new("key")
+                .about("Get data by key")
+                .arg(
+                    Arg::new("key")
+                        .value_parser(EnumValueParser::<Key>::new())
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .help("Render the playback with a playback template (see `playback_format` config option) instead of printing JSON. Only supported by the `playback` key"),
+                ),
         )
         .subcommand(add_id_or_name_group(
             Command::new("item").about("Get a Spotify item's data").arg(
@@ -127,6 +136,13 @@ pub fn init_search_command() -> Command {
     Command::new("search")
         .about("Search spotify")
         .arg(Arg::new("query").help("Search query").required(true))
+        .arg(
+            Arg::new("local")
+                .long("local")
+                .short('l')
+                .action(ArgAction::SetTrue)
+                .help("Search the local library index instead of Spotify. The query supports `artist:`, `album:`, `track:` and `year:` filters"),
+        )
 }
 
 pub fn init_like_command() -> Command {
@@ -141,6 +157,68 @@ pub fn init_like_command() -> Command {
         )
 }
 
+pub fn init_watch_command() -> Command {
+    Command::new("watch")
+        .about("Watch player events, printed as newline-delimited JSON")
+        .after_help("Requires a running `spotify_player` instance. Events include track changes, play/pause, seek, volume, device, queue and liked-state changes.")
+}
+
+pub fn init_history_command() -> Command {
+    Command::new("history")
+        .about("Show the top played tracks, artists and albums from the local play history")
+        .arg(
+            Arg::new("period")
+                .short('p')
+                .long("period")
+                .default_value("week")
+                .value_parser(EnumValueParser::<HistoryPeriod>::new())
+                .help("The period of time to compute the statistics over"),
+        )
+        .arg(
+            Arg::new("limit")
+                .short('l')
+                .long("limit")
+                .default_value("10")
+                .value_parser(value_parser!(usize))
+                .help("The maximum number of items in each top list"),
+        )
+}
+
+pub fn init_undo_command() -> Command {
+    Command::new("undo")
+        .about("Undo the last change to playlists or library")
+        .after_help("Requires a running `spotify_player` instance. Changes are recorded in a journal persisted inside the application's cache folder.")
+        .arg(
+            Arg::new("redo")
+                .long("redo")
+                .short('r')
+                .action(ArgAction::SetTrue)
+                .help("Redo the last undone change instead"),
+        )
+}
+
+pub fn init_timer_subcommand() -> Command {
+    Command::new("timer")
+        .about("Sleep timer and scheduled actions")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("set")
+                .about("Set the sleep timer, which pauses the playback when it expires")
+                .arg(
+                    Arg::new("timer")
+                        .required(true)
+                        .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                        .help("Number of minutes, a duration (e.g. `1h30m`, `90s`), `track` (end of the current track) or `context` (end of the current album/playlist)"),
+                ),
+        )
+        .subcommand(Command::new("cancel").about("Cancel the sleep timer"))
+        .subcommand(
+            Command::new("status")
+                .about("Show the sleep timer and the next runs of the scheduled actions"),
+        )
+        .after_help("Requires a running `spotify_player` instance. Scheduled actions are configured with the `schedules` option in `app.toml`.")
+}
+
 pub fn init_authenticate_command() -> Command {
     Command::new("authenticate").about("Authenticate the application")
 }
@@ -204,4 +282,44 @@ pub fn init_playlist_subcommand() -> Command {
                 .long("delete")
                 .action(clap::ArgAction::SetTrue)
                 .help("Deletes any previously imported tracks that are no longer in an imported playlist since last import.")))
+        .subcommand(Command::new("export").about("Exports a playlist into a playlist file.")
+            .arg(Arg::new("id")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new()))
+            .arg(Arg::new("format")
+                .short('f')
+                .long("format")
+                .default_value("json")
+                .value_parser(EnumValueParser::<PlaylistFileFormat>::new())
+                .help("The playlist file's format"))
+            .arg(Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_parser(value_parser!(std::path::PathBuf))
+                .help("The playlist file's path. If not specified, the playlist file is printed to stdout.")))
+        .subcommand(Command::new("import-file").about("Imports tracks from a playlist file into a new playlist.")
+            .arg(Arg::new("path")
+                .required(true)
+                .value_parser(value_parser!(std::path::PathBuf)))
+            .arg(Arg::new("format")
+                .short('f')
+                .long("format")
+                .value_parser(EnumValueParser::<PlaylistFileFormat>::new())
+                .help("The playlist file's format. If not specified, the format is inferred from the file's extension."))
+            .arg(Arg::new("name")
+                .short('n')
+                .long("name")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .help("The new playlist's name. Defaults to the playlist's name stored in the file or the file's name."))
+            .arg(Arg::new("to")
+                .short('t')
+                .long("to")
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .help("The ID of an existing playlist to import into instead of creating a new playlist"))
+            .after_help("Each track is matched to a Spotify track by its Spotify URI, its ISRC or its title and artist. Tracks that cannot be matched are reported."))
+        .subcommand(Command::new("materialize").about("Updates smart playlists' target playlists to contain tracks matching their filters.")
+            .arg(Arg::new("name")
+                .required(false)
+                .value_parser(clap::builder::NonEmptyStringValueParser::new())
+                .help("The name of a smart playlist. If not specified, all smart playlists are materialized."))
+            .after_help("Smart playlists are defined in the `smart_playlists.toml` file inside the application's config folder."))
 }
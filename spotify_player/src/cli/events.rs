@@ -0,0 +1,177 @@
+use std::sync::Arc;
+
+use rspotify::model::{Id, PlayableItem};
+
+use crate::state::{Mutex, SharedState};
+
+use super::Event;
+
+/// the duration between two consecutive checks for new player events
+const EVENT_POLL_DURATION: std::time::Duration = std::time::Duration::from_millis(200);
+/// the maximum number of pending events of a subscriber, a subscriber is dropped
+/// if it doesn't consume its events fast enough
+const MAX_PENDING_EVENTS: usize = 256;
+/// the minimum difference (in ms) between the actual and the expected playback progress
+/// for the playback to be considered seeked
+const SEEK_THRESHOLD_MS: i64 = 2000;
+
+/// Subscribers of the client socket's events
+pub type Subscribers = Arc<Mutex<Vec<flume::Sender<Event>>>>;
+
+#[derive(Default, Debug)]
+/// A snapshot of the player state used to compute new player events
+struct PlayerSnapshot {
+    track_id: Option<String>,
+    track: Option<String>,
+    artists: Vec<String>,
+    album: Option<String>,
+    is_playing: bool,
+    progress_ms: i64,
+    volume: Option<u32>,
+    device_id: Option<String>,
+    device_name: Option<String>,
+    queue: Vec<String>,
+    liked: Option<bool>,
+}
+
+impl PlayerSnapshot {
+    fn new(state: &SharedState) -> Self {
+        let player = state.player.read();
+        let mut snapshot = Self::default();
+
+        if let Some(playback) = player.current_playback() {
+            snapshot.is_playing = playback.is_playing;
+            snapshot.progress_ms = playback
+                .progress
+                .map(|p| p.num_milliseconds())
+                .unwrap_or_default();
+            snapshot.volume = playback.device.volume_percent;
+            snapshot.device_id = playback.device.id;
+            snapshot.device_name = Some(playback.device.name);
+        }
+
+        if let Some(track) = player.current_playing_track() {
+            snapshot.track_id = track.id.as_ref().map(|id| id.uri());
+            snapshot.track = Some(track.name.clone());
+            snapshot.artists = track.artists.iter().map(|a| a.name.clone()).collect();
+            snapshot.album = Some(track.album.name.clone());
+        }
+
+        if let Some(queue) = player.queue.as_ref() {
+            snapshot.queue = queue
+                .queue
+                .iter()
+                .filter_map(|item| match item {
+                    PlayableItem::Track(track) => track.id.as_ref().map(|id| id.uri()),
+                    PlayableItem::Episode(episode) => Some(episode.id.uri()),
+                })
+                .collect();
+        }
+        drop(player);
+
+        snapshot.liked = snapshot
+            .track_id
+            .as_ref()
+            .map(|uri| state.data.read().user_data.saved_tracks.contains_key(uri));
+
+        snapshot
+    }
+
+    /// Compute player events happening between the `prev` snapshot and the current snapshot
+    fn events(&self, prev: &Self, elapsed: std::time::Duration) -> Vec<Event> {
+        let mut events = vec![];
+
+        let is_track_changed = self.track_id != prev.track_id;
+        if is_track_changed {
+            events.push(Event::TrackChanged {
+                track_id: self.track_id.clone(),
+                track: self.track.clone(),
+                artists: self.artists.clone(),
+                album: self.album.clone(),
+            });
+        }
+        if self.is_playing != prev.is_playing {
+            events.push(Event::PlaybackStatusChanged {
+                is_playing: self.is_playing,
+            });
+        }
+        if !is_track_changed {
+            let expected_progress_ms = prev.progress_ms
+                + if prev.is_playing {
+                    elapsed.as_millis() as i64
+                } else {
+                    0
+                };
+            if (self.progress_ms - expected_progress_ms).abs() > SEEK_THRESHOLD_MS {
+                events.push(Event::Seeked {
+                    position_ms: self.progress_ms,
+                });
+            }
+        }
+        if self.volume != prev.volume {
+            events.push(Event::VolumeChanged {
+                volume: self.volume,
+            });
+        }
+        if self.device_id != prev.device_id || self.device_name != prev.device_name {
+            events.push(Event::DeviceChanged {
+                device_id: self.device_id.clone(),
+                device_name: self.device_name.clone(),
+            });
+        }
+        if self.queue != prev.queue {
+            events.push(Event::QueueChanged {
+                track_ids: self.queue.clone(),
+            });
+        }
+        if let (Some(track_id), Some(liked)) = (self.track_id.as_ref(), self.liked) {
+            if !is_track_changed && prev.liked != Some(liked) {
+                events.push(Event::LikedStateChanged {
+                    track_id: track_id.clone(),
+                    liked,
+                });
+            }
+        }
+
+        events
+    }
+}
+
+/// Register a new subscriber, which receives player events via the returned channel
+/// until the channel is dropped
+pub fn subscribe(subscribers: &Subscribers) -> flume::Receiver<Event> {
+    let (event_pub, event_sub) = flume::bounded(MAX_PENDING_EVENTS);
+    subscribers.lock().push(event_pub);
+    event_sub
+}
+
+/// Start a task that watches the application's state for new player events
+/// and pushes them to the client socket's subscribers
+pub async fn start_event_publisher(state: SharedState, subscribers: Subscribers) {
+    let mut prev = PlayerSnapshot::new(&state);
+    let mut timer = std::time::Instant::now();
+
+    loop {
+        tokio::time::sleep(EVENT_POLL_DURATION).await;
+
+        let snapshot = PlayerSnapshot::new(&state);
+        let events = snapshot.events(&prev, timer.elapsed());
+        prev = snapshot;
+        timer = std::time::Instant::now();
+
+        for event in events {
+            tracing::info!("Got a new player event: {event:?}");
+            // subscribers whose channels are dropped or full are removed
+            subscribers
+                .lock()
+                .retain(|event_pub| match event_pub.try_send(event.clone()) {
+                    Ok(()) => true,
+                    Err(flume::TrySendError::Full(_)) => {
+                        tracing::warn!("Dropped a slow subscriber with too many pending events");
+                        false
+                    }
+                    Err(flume::TrySendError::Disconnected(_)) => false,
+                });
+        }
+    }
+}
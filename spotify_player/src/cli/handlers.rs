@@ -1,6 +1,10 @@
 use crate::{
     auth::{new_session, new_session_with_new_creds, AuthConfig},
     client,
+    state::{
+        load_data_from_file_cache, FileCacheKey, HistoryPeriod, LibraryIndex, PlayHistory,
+        Playlist, LOCAL_SEARCH_LIMIT,
+    },
 };
 
 use super::*;
@@ -8,23 +12,73 @@ use anyhow::{Context, Result};
 use clap::{ArgMatches, Id};
 use clap_complete::{generate, Shell};
 use rspotify::clients::BaseClient;
+#[cfg(unix)]
+use std::io::Read;
+use std::io::Write;
+#[cfg(not(unix))]
 use std::net::UdpSocket;
+use std::path::PathBuf;
+
+/// A connection to a `spotify_player` client's socket
+enum Connection {
+    #[cfg(unix)]
+    Unix(std::os::unix::net::UnixStream),
+    #[cfg(not(unix))]
+    Udp(UdpSocket),
+}
 
-fn receive_response(socket: &UdpSocket) -> Result<Response> {
-    // read response from the server's socket, which can be split into
-    // smaller chunks of data
-    let mut data = Vec::new();
-    let mut buf = [0; 4096];
-    loop {
-        let (n_bytes, _) = socket.recv_from(&mut buf)?;
-        if n_bytes == 0 {
-            // end of chunk
-            break;
+impl Connection {
+    fn send_request(&mut self, request: &Request) -> Result<()> {
+        let request_buf = serde_json::to_vec(request)?;
+        match self {
+            #[cfg(unix)]
+            Self::Unix(stream) => {
+                // messages are prefixed by their length
+                stream.write_all(&(request_buf.len() as u64).to_be_bytes())?;
+                stream.write_all(&request_buf)?;
+            }
+            #[cfg(not(unix))]
+            Self::Udp(socket) => {
+                assert!(request_buf.len() <= MAX_REQUEST_SIZE);
+                socket.send(&request_buf)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Receive a message (a response or a player event) from the client's socket
+    fn receive(&mut self) -> Result<Vec<u8>> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(stream) => {
+                let mut len_buf = [0; 8];
+                stream.read_exact(&mut len_buf)?;
+                let mut data = vec![0; check_frame_size(u64::from_be_bytes(len_buf))?];
+                stream.read_exact(&mut data)?;
+                Ok(data)
+            }
+            #[cfg(not(unix))]
+            Self::Udp(socket) => {
+                // read response from the server's socket, which can be split into
+                // smaller chunks of data
+                let mut data = Vec::new();
+                let mut buf = [0; 4096];
+                loop {
+                    let (n_bytes, _) = socket.recv_from(&mut buf)?;
+                    if n_bytes == 0 {
+                        // end of chunk
+                        break;
+                    }
+                    data.extend_from_slice(&buf[..n_bytes]);
+                }
+                Ok(data)
+            }
         }
-        data.extend_from_slice(&buf[..n_bytes]);
     }
 
-    Ok(serde_json::from_slice(&data)?)
+    fn receive_response(&mut self) -> Result<Response> {
+        Ok(serde_json::from_slice(&self.receive()?)?)
+    }
 }
 
 fn get_id_or_name(args: &ArgMatches) -> IdOrName {
@@ -56,7 +110,13 @@ fn handle_get_subcommand(args: &ArgMatches) -> Result<Request> {
                 .get_one::<Key>("key")
                 .expect("key is required")
                 .to_owned();
-            Request::Get(GetRequest::Key(key))
+            match (key, args.get_one::<String>("format")) {
+                (Key::Playback, Some(format)) => Request::Get(GetRequest::PlaybackText {
+                    format: format.to_owned(),
+                }),
+                (_, Some(_)) => anyhow::bail!("`--format` is only supported by the `playback` key"),
+                (key, None) => Request::Get(GetRequest::Key(key)),
+            }
         }
         "item" => {
             let item_type = args
@@ -138,11 +198,69 @@ fn handle_playback_subcommand(args: &ArgMatches) -> Result<Request> {
     Ok(Request::Playback(command))
 }
 
+/// Create a new client to handle CLI commands when there is no running `spotify_player` instance
+fn new_standalone_client(
+    configs: &config::Configs,
+) -> Result<(tokio::runtime::Runtime, client::Client)> {
+    let auth_config = AuthConfig::new(configs)?;
+    let rt = tokio::runtime::Runtime::new()?;
+    let session = rt.block_on(new_session(&auth_config, false))?;
+
+    // create a Spotify API client
+    let client = client::Client::new(session, auth_config, configs.client_id());
+    rt.block_on(client.refresh_token())?;
+
+    Ok((rt, client))
+}
+
+/// Tries to connect to a running client, if exists, via the client's Unix domain socket.
+/// If no running client found, create a new client running in a separate thread to
+/// handle the socket request.
+#[cfg(unix)]
+fn try_connect_to_client(configs: &config::Configs) -> Result<Connection> {
+    use std::os::unix::net::UnixStream;
+
+    let path = client_socket_path(configs);
+    match UnixStream::connect(&path) {
+        Ok(stream) => return Ok(Connection::Unix(stream)),
+        Err(err)
+            if matches!(
+                err.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) => {}
+        Err(err) => return Err(err.into()),
+    }
+
+    // no running `spotify_player` instance found,
+    // initialize a new client to handle the current CLI command
+    let (rt, client) = new_standalone_client(configs)?;
+
+    // create a client socket for handling CLI commands
+    let listener = {
+        let _guard = rt.enter();
+        bind_unix_listener(&path)?
+    };
+
+    // spawn a thread to handle the CLI request
+    std::thread::spawn(move || {
+        rt.block_on(start_unix_socket(
+            client,
+            listener,
+            None,
+            Default::default(),
+        ))
+    });
+
+    Ok(Connection::Unix(UnixStream::connect(&path)?))
+}
+
 /// Tries to connect to a running client, if exists, by sending a connection request
 /// to the client via a UDP socket.
 /// If no running client found, create a new client running in a separate thread to
 /// handle the socket request.
-fn try_connect_to_client(socket: &UdpSocket, configs: &config::Configs) -> Result<()> {
+#[cfg(not(unix))]
+fn try_connect_to_client(configs: &config::Configs) -> Result<Connection> {
+    let socket = UdpSocket::bind("127.0.0.1:0")?;
     let port = configs.app_config.client_port;
     socket.connect(("127.0.0.1", port))?;
 
@@ -152,37 +270,35 @@ fn try_connect_to_client(socket: &UdpSocket, configs: &config::Configs) -> Resul
         if let std::io::ErrorKind::ConnectionRefused = err.kind() {
             // no running `spotify_player` instance found,
             // initialize a new client to handle the current CLI command
-
-            let auth_config = AuthConfig::new(configs)?;
-            let rt = tokio::runtime::Runtime::new()?;
-            let session = rt.block_on(new_session(&auth_config, false))?;
-
-            // create a Spotify API client
-            let client =
-                client::Client::new(session, auth_config, configs.app_config.client_id.clone());
-            rt.block_on(client.refresh_token())?;
+            let (rt, client) = new_standalone_client(configs)?;
 
             // create a client socket for handling CLI commands
             let client_socket = rt.block_on(tokio::net::UdpSocket::bind(("127.0.0.1", port)))?;
 
             // spawn a thread to handle the CLI request
-            std::thread::spawn(move || rt.block_on(start_socket(client, client_socket, None)));
+            std::thread::spawn(move || {
+                rt.block_on(start_udp_socket(
+                    client,
+                    client_socket,
+                    None,
+                    Default::default(),
+                ))
+            });
         } else {
             return Err(err.into());
         }
     }
 
-    Ok(())
+    Ok(Connection::Udp(socket))
 }
 
 pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
-    let socket = UdpSocket::bind("127.0.0.1:0")?;
     let configs = config::get_config();
 
     // handle commands that don't require a client separately
     match cmd {
         "authenticate" => {
-            let auth_config = AuthConfig::new(configs)?;
+            let auth_config = AuthConfig::new(&configs)?;
             let rt = tokio::runtime::Runtime::new()?;
             rt.block_on(new_session_with_new_creds(&auth_config))?;
             std::process::exit(0);
@@ -196,10 +312,40 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
             generate(gen, &mut cmd, name, &mut std::io::stdout());
             std::process::exit(0);
         }
+        "history" => {
+            // play history is stored locally, so there is no need to connect to a client
+            let period = *args
+                .get_one::<HistoryPeriod>("period")
+                .expect("period should have a default value");
+            let limit = *args
+                .get_one::<usize>("limit")
+                .expect("limit should have a default value");
+            let stats = PlayHistory::new(&configs.profile_cache_folder()).stats(period, limit);
+            println!("{}", serde_json::to_string(&*stats)?);
+            std::process::exit(0);
+        }
+        "search" if args.get_flag("local") => {
+            // the library index is stored locally, so there is no need to connect to a client
+            let query = args.get_one::<String>("query").expect("query is required");
+            let playlists: Vec<Playlist> =
+                load_data_from_file_cache(FileCacheKey::Playlists, &configs.profile_cache_folder())
+                    .unwrap_or_default();
+            let results = LibraryIndex::new(&configs.profile_cache_folder()).search(
+                query,
+                &playlists,
+                LOCAL_SEARCH_LIMIT,
+            );
+            println!("{}", serde_json::to_string(&results)?);
+            std::process::exit(0);
+        }
         _ => {}
     }
 
-    try_connect_to_client(&socket, configs).context("try to connect to a client")?;
+    let mut connection = try_connect_to_client(&configs).context("try to connect to a client")?;
+
+    if cmd == "watch" {
+        return watch_events(&mut connection);
+    }
 
     // construct a socket request based on the CLI command and its arguments
     let request = match cmd {
@@ -216,27 +362,107 @@ pub fn handle_cli_subcommand(cmd: &str, args: &ArgMatches) -> Result<()> {
                 .expect("query is required")
                 .to_owned(),
         },
+        "undo" => Request::Undo {
+            redo: args.get_flag("redo"),
+        },
+        "timer" => handle_timer_subcommand(args)?,
         _ => unreachable!(),
     };
 
+    // output path of `playlist export` command, `None` if the command is not `playlist export`
+    let export_output = match (cmd, args.subcommand()) {
+        ("playlist", Some(("export", args))) => Some(args.get_one::<PathBuf>("output").cloned()),
+        _ => None,
+    };
+
     // send the request to the client's socket
-    let request_buf = serde_json::to_vec(&request)?;
-    assert!(request_buf.len() <= MAX_REQUEST_SIZE);
-    socket.send(&request_buf)?;
+    connection.send_request(&request)?;
 
     // receive and handle a response from the client's socket
-    match receive_response(&socket)? {
+    match connection.receive_response()? {
         Response::Err(err) => {
             eprintln!("{}", String::from_utf8_lossy(&err));
             std::process::exit(1);
         }
         Response::Ok(data) => {
-            println!("{}", String::from_utf8_lossy(&data).replace("\\n", "\n"));
+            match export_output {
+                // an exported playlist file is written as is
+                Some(Some(path)) => std::fs::write(path, &data)?,
+                Some(None) => std::io::stdout().write_all(&data)?,
+                None => println!("{}", String::from_utf8_lossy(&data).replace("\\n", "\n")),
+            }
             std::process::exit(0);
         }
     }
 }
 
+/// Subscribe to the client's player events and print them to stdout
+/// (one JSON object per line) until the process is terminated
+fn watch_events(connection: &mut Connection) -> Result<()> {
+    connection.send_request(&Request::Subscribe)?;
+    if let Response::Err(err) = connection.receive_response()? {
+        eprintln!("{}", String::from_utf8_lossy(&err));
+        std::process::exit(1);
+    }
+
+    let mut stdout = std::io::stdout();
+    let mut print_event = |data: &[u8]| -> Result<()> {
+        stdout.write_all(data)?;
+        stdout.write_all(b"\n")?;
+        stdout.flush()?;
+        Ok(())
+    };
+
+    match connection {
+        // player events are streamed over the connection
+        #[cfg(unix)]
+        Connection::Unix(_) => loop {
+            print_event(&connection.receive()?)?;
+        },
+        #[cfg(not(unix))]
+        Connection::Udp(socket) => {
+            // the subscription needs to be renewed periodically, so the socket
+            // shouldn't block for longer than the renewal duration
+            socket.set_read_timeout(Some(SUBSCRIPTION_RENEWAL_DURATION))?;
+            let request_buf = serde_json::to_vec(&Request::Subscribe)?;
+            let mut renewal_timer = std::time::Instant::now();
+            let mut buf = [0; 65536];
+
+            loop {
+                match socket.recv(&mut buf) {
+                    Ok(n_bytes) => print_event(&buf[..n_bytes])?,
+                    Err(err)
+                        if matches!(
+                            err.kind(),
+                            std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                        ) => {}
+                    Err(err) => return Err(err.into()),
+                }
+
+                if renewal_timer.elapsed() >= SUBSCRIPTION_RENEWAL_DURATION {
+                    socket.send(&request_buf)?;
+                    renewal_timer = std::time::Instant::now();
+                }
+            }
+        }
+    }
+}
+
+fn handle_timer_subcommand(args: &ArgMatches) -> Result<Request> {
+    let (cmd, args) = args.subcommand().expect("timer subcommand is required");
+    let command = match cmd {
+        "set" => TimerCommand::Set(
+            args.get_one::<String>("timer")
+                .expect("timer arg is required")
+                .parse()?,
+        ),
+        "cancel" => TimerCommand::Cancel,
+        "status" => TimerCommand::Status,
+        _ => unreachable!(),
+    };
+    Ok(Request::Timer(command))
+}
+
 fn handle_playlist_subcommand(args: &ArgMatches) -> Result<Request> {
     let (cmd, args) = args.subcommand().expect("playlist subcommand is required");
     let command = match cmd {
@@ -319,6 +545,53 @@ fn handle_playlist_subcommand(args: &ArgMatches) -> Result<Request> {
 
             PlaylistCommand::Sync { id: pid, delete }
         }
+        "export" => {
+            let id_s = args
+                .get_one::<String>("id")
+                .expect("Playlist id is required.")
+                .to_owned();
+
+            let id = PlaylistId::from_id(id_s)?;
+            let format = *args
+                .get_one::<PlaylistFileFormat>("format")
+                .expect("format should have a default value");
+
+            PlaylistCommand::Export { id, format }
+        }
+        "import-file" => {
+            let path = args
+                .get_one::<PathBuf>("path")
+                .expect("Playlist file's path is required.");
+
+            let format = match args.get_one::<PlaylistFileFormat>("format") {
+                Some(format) => *format,
+                None => PlaylistFileFormat::from_path(path).context(format!(
+                    "Cannot infer the playlist file's format from {}, please specify it with `--format`",
+                    path.display()
+                ))?,
+            };
+            let name = args.get_one::<String>("name").cloned();
+            let to = args
+                .get_one::<String>("to")
+                .map(|id| PlaylistId::from_id(id.to_owned()))
+                .transpose()?;
+
+            // the playlist file is read by the client, which may run in a different working directory
+            let path = path
+                .canonicalize()
+                .context(format!("Cannot find playlist file {}", path.display()))?;
+
+            println!("Importing '{}'...\n", path.display());
+            PlaylistCommand::ImportFile {
+                path,
+                format,
+                name,
+                to,
+            }
+        }
+        "materialize" => PlaylistCommand::Materialize {
+            name: args.get_one::<String>("name").cloned(),
+        },
         _ => unreachable!(),
     };
 
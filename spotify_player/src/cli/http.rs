@@ -0,0 +1,451 @@
+use std::{collections::HashMap, convert::Infallible};
+
+use anyhow::{Context as _, Result};
+use clap::ValueEnum;
+use http_body_util::{combinators::BoxBody, BodyExt, Full, Limited, StreamBody};
+use hyper::{
+    body::{Bytes, Frame, Incoming},
+    header, Method, StatusCode,
+};
+use hyper_util::rt::TokioIo;
+
+use crate::{client::Client, config, state::SharedState};
+
+use super::*;
+
+/// the maximum size of a request's body
+const MAX_BODY_SIZE: usize = 1024 * 1024;
+/// the duration between two consecutive keep-alive messages of an event stream
+const KEEP_ALIVE_DURATION: std::time::Duration = std::time::Duration::from_secs(15);
+
+type Body = BoxBody<Bytes, Infallible>;
+
+/// A route of the HTTP API
+enum Route {
+    /// a request handled the same way as requests sent to the client socket
+    Request(Request),
+    /// a Server-Sent Events stream of player events
+    Events,
+}
+
+/// Start the application's HTTP API server, which exposes operations of the client socket
+/// as REST endpoints.
+///
+/// Endpoints are described in the OpenAPI document located at `docs/openapi.yaml`.
+pub async fn start_http_server(
+    config: config::HttpApiConfig,
+    client: Client,
+    state: SharedState,
+    subscribers: events::Subscribers,
+) {
+    if config.token.is_empty() {
+        tracing::error!("Refused to start the HTTP API server: `http_api.token` must not be empty");
+        return;
+    }
+
+    tracing::info!("Starting the HTTP API server at {}", config.bind_address);
+    let listener = match tokio::net::TcpListener::bind(&config.bind_address).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("Failed to create the HTTP API server: {err:#}");
+            return;
+        }
+    };
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::warn!("Failed to accept a connection to the HTTP API server: {err:#}");
+                continue;
+            }
+        };
+
+        let service = hyper::service::service_fn({
+            let config = config.clone();
+            let client = client.clone();
+            let state = state.clone();
+            let subscribers = subscribers.clone();
+            move |request| {
+                let config = config.clone();
+                let client = client.clone();
+                let state = state.clone();
+                let subscribers = subscribers.clone();
+                async move {
+                    Ok::<_, Infallible>(
+                        handle_http_request(&config, &client, &state, &subscribers, request).await,
+                    )
+                }
+            }
+        });
+
+        tokio::task::spawn(async move {
+            if let Err(err) = hyper::server::conn::http1::Builder::new()
+                .serve_connection(TokioIo::new(stream), service)
+                .await
+            {
+                tracing::warn!("Failed to serve a HTTP API connection: {err:#}");
+            }
+        });
+    }
+}
+
+async fn handle_http_request(
+    config: &config::HttpApiConfig,
+    client: &Client,
+    state: &SharedState,
+    subscribers: &events::Subscribers,
+    request: hyper::Request<Incoming>,
+) -> hyper::Response<Body> {
+    if let Some(response) = reject_unauthorized(request.headers(), &config.token) {
+        return response;
+    }
+
+    let route = match parse_route(request).await {
+        Ok(Some(route)) => route,
+        Ok(None) => return text_response(StatusCode::NOT_FOUND, "not found"),
+        Err(err) => return text_response(StatusCode::BAD_REQUEST, &format!("{err:#}")),
+    };
+
+    match route {
+        Route::Events => event_stream_response(subscribers),
+        Route::Request(request) => {
+            let content_type = response_content_type(&request);
+            match client::handle_request(client, &Some(state.clone()), request).await {
+                Response::Ok(data) => hyper::Response::builder()
+                    .header(header::CONTENT_TYPE, content_type)
+                    .body(Full::new(Bytes::from(data)).boxed())
+                    .expect("valid response"),
+                Response::Err(err) => {
+                    text_response(StatusCode::BAD_REQUEST, &String::from_utf8_lossy(&err))
+                }
+            }
+        }
+    }
+}
+
+/// Check the bearer token of a HTTP request, return an unauthorized response if the token is invalid
+fn reject_unauthorized(
+    headers: &header::HeaderMap,
+    expected_token: &str,
+) -> Option<hyper::Response<Body>> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if token.is_some_and(|token| constant_time_eq(token.as_bytes(), expected_token.as_bytes())) {
+        return None;
+    }
+
+    let mut response = text_response(StatusCode::UNAUTHORIZED, "invalid bearer token");
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        header::HeaderValue::from_static("Bearer"),
+    );
+    Some(response)
+}
+
+/// Parse a HTTP request into a route of the HTTP API, return `None` if no route found
+async fn parse_route<B>(request: hyper::Request<B>) -> Result<Option<Route>>
+where
+    B: hyper::body::Body,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let query = request
+        .uri()
+        .query()
+        .map(|q| {
+            form_urlencoded::parse(q.as_bytes())
+                .into_owned()
+                .collect::<HashMap<_, _>>()
+        })
+        .unwrap_or_default();
+
+    let body = Limited::new(request.into_body(), MAX_BODY_SIZE)
+        .collect()
+        .await
+        .map_err(|err| anyhow::anyhow!("failed to read the request's body: {err}"))?
+        .to_bytes();
+
+    let segments = match path.strip_prefix("/api/v1/") {
+        Some(path) => path.split('/').collect::<Vec<_>>(),
+        None => return Ok(None),
+    };
+
+    let request = match (&method, segments.as_slice()) {
+        (&Method::GET, ["events"]) => return Ok(Some(Route::Events)),
+        (&Method::GET, ["keys", key]) => {
+            let key = Key::from_str(key, true).map_err(|err| anyhow::anyhow!(err))?;
+            match (key, query.get("format")) {
+                (Key::Playback, Some(format)) => Request::Get(GetRequest::PlaybackText {
+                    format: format.clone(),
+                }),
+                (_, Some(_)) => {
+                    anyhow::bail!(
+                        "`format` query parameter is only supported by the `playback` key"
+                    )
+                }
+                (key, None) => Request::Get(GetRequest::Key(key)),
+            }
+        }
+        (&Method::GET, ["items", item_type]) => {
+            let item_type =
+                ItemType::from_str(item_type, true).map_err(|err| anyhow::anyhow!(err))?;
+            let name = query
+                .get("name")
+                .context("missing `name` query parameter")?;
+            Request::Get(GetRequest::Item(item_type, IdOrName::Name(name.clone())))
+        }
+        (&Method::GET, ["items", item_type, id]) => {
+            let item_type =
+                ItemType::from_str(item_type, true).map_err(|err| anyhow::anyhow!(err))?;
+            Request::Get(GetRequest::Item(item_type, IdOrName::Id(id.to_string())))
+        }
+        (&Method::GET, ["search"]) => Request::Search {
+            query: query
+                .get("query")
+                .context("missing `query` query parameter")?
+                .clone(),
+        },
+        (&Method::POST, ["playback"]) => Request::Playback(parse_body(&body)?),
+        (&Method::POST, ["connect"]) => Request::Connect(parse_body(&body)?),
+        (&Method::POST, ["like"]) => Request::Like {
+            unlike: query.get("unlike").is_some_and(|v| v == "true"),
+        },
+        (&Method::POST, ["playlist"]) => match parse_body(&body)? {
+            // the HTTP client shouldn't be able to read files on the application's machine
+            PlaylistCommand::ImportFile { .. } => {
+                anyhow::bail!("`ImportFile` playlist command is not supported by the HTTP API")
+            }
+            command => Request::Playlist(command),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(Route::Request(request)))
+}
+
+/// Get the content type of the data returned by a request
+fn response_content_type(request: &Request) -> &'static str {
+    match request {
+        Request::Get(GetRequest::Key(_) | GetRequest::Item(..)) | Request::Search { .. } => {
+            "application/json"
+        }
+        Request::Playlist(PlaylistCommand::Export { format, .. }) => format.content_type(),
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+/// Compare a bearer token with the expected token in a constant time, which
+/// doesn't leak the token's content via the comparison's timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .fold(0, |acc, (x, y)| std::hint::black_box(acc | (x ^ y)))
+            == 0
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T> {
+    serde_json::from_slice(body).context("failed to parse the request's body")
+}
+
+/// Construct a Server-Sent Events response streaming player events to the HTTP client
+fn event_stream_response(subscribers: &events::Subscribers) -> hyper::Response<Body> {
+    let event_sub = events::subscribe(subscribers);
+    let (frame_pub, frame_sub) = flume::unbounded::<Result<Frame<Bytes>, Infallible>>();
+
+    tokio::task::spawn(async move {
+        loop {
+            let data = match tokio::time::timeout(KEEP_ALIVE_DURATION, event_sub.recv_async()).await
+            {
+                Ok(Ok(event)) => match serde_json::to_string(&event) {
+                    Ok(event) => format!("data: {event}\n\n"),
+                    Err(err) => {
+                        tracing::error!("Failed to serialize the player event {event:?}: {err:#}");
+                        continue;
+                    }
+                },
+                Ok(Err(_)) => break,
+                // a comment line is sent periodically to detect closed connections
+                Err(_) => ":\n\n".to_string(),
+            };
+            if frame_pub.send(Ok(Frame::data(Bytes::from(data)))).is_err() {
+                break;
+            }
+        }
+    });
+
+    hyper::Response::builder()
+        .header(header::CONTENT_TYPE, "text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        .body(StreamBody::new(frame_sub.into_stream()).boxed())
+        .expect("valid response")
+}
+
+fn text_response(status: StatusCode, text: &str) -> hyper::Response<Body> {
+    hyper::Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Full::new(Bytes::from(text.to_string())).boxed())
+        .expect("valid response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn route(method: Method, uri: &str, body: &str) -> Result<Option<Route>> {
+        let request = hyper::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Full::new(Bytes::from(body.to_string())))
+            .expect("valid request");
+        parse_route(request).await
+    }
+
+    fn request_content_type(route: Option<Route>) -> &'static str {
+        match route {
+            Some(Route::Request(request)) => response_content_type(&request),
+            _ => panic!("expected a request route"),
+        }
+    }
+
+    #[test]
+    fn compare_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
+
+    #[test]
+    fn reject_invalid_bearer_tokens() {
+        let headers = |value: Option<&'static str>| {
+            let mut headers = header::HeaderMap::new();
+            if let Some(value) = value {
+                headers.insert(
+                    header::AUTHORIZATION,
+                    header::HeaderValue::from_static(value),
+                );
+            }
+            headers
+        };
+
+        assert!(reject_unauthorized(&headers(Some("Bearer secret")), "secret").is_none());
+        for value in [
+            None,
+            Some("Bearer wrong"),
+            Some("secret"),
+            Some("Basic secret"),
+        ] {
+            let response = reject_unauthorized(&headers(value), "secret").expect("unauthorized");
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(
+                response.headers().get(header::WWW_AUTHENTICATE),
+                Some(&header::HeaderValue::from_static("Bearer"))
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn refuse_import_file() {
+        let body = serde_json::to_string(&PlaylistCommand::ImportFile {
+            path: "/etc/passwd".into(),
+            format: PlaylistFileFormat::M3u,
+            name: None,
+            to: None,
+        })
+        .unwrap();
+        assert!(route(Method::POST, "/api/v1/playlist", &body)
+            .await
+            .is_err());
+
+        let body = serde_json::to_string(&PlaylistCommand::List).unwrap();
+        assert!(matches!(
+            route(Method::POST, "/api/v1/playlist", &body).await,
+            Ok(Some(Route::Request(Request::Playlist(
+                PlaylistCommand::List
+            ))))
+        ));
+    }
+
+    #[tokio::test]
+    async fn map_routes() {
+        assert!(matches!(
+            route(Method::GET, "/api/v1/events", "").await,
+            Ok(Some(Route::Events))
+        ));
+        assert!(matches!(
+            route(Method::GET, "/api/v2/events", "").await,
+            Ok(None)
+        ));
+        assert!(matches!(
+            route(Method::POST, "/api/v1/events", "").await,
+            Ok(None)
+        ));
+        assert!(matches!(
+            route(Method::GET, "/api/v1/unknown", "").await,
+            Ok(None)
+        ));
+        assert!(route(Method::GET, "/api/v1/keys/devices?format=x", "")
+            .await
+            .is_err());
+        assert!(route(Method::GET, "/api/v1/search", "").await.is_err());
+
+        let playback = route(Method::GET, "/api/v1/keys/playback?format={track}", "")
+            .await
+            .unwrap();
+        assert!(matches!(
+            playback,
+            Some(Route::Request(Request::Get(GetRequest::PlaybackText { ref format })))
+                if format == "{track}"
+        ));
+        assert_eq!(request_content_type(playback), "text/plain; charset=utf-8");
+
+        let devices = route(Method::GET, "/api/v1/keys/devices", "")
+            .await
+            .unwrap();
+        assert!(matches!(
+            devices,
+            Some(Route::Request(Request::Get(GetRequest::Key(Key::Devices))))
+        ));
+        assert_eq!(request_content_type(devices), "application/json");
+
+        let search = route(Method::GET, "/api/v1/search?query=foo%20bar", "")
+            .await
+            .unwrap();
+        assert!(matches!(
+            search,
+            Some(Route::Request(Request::Search { ref query })) if query == "foo bar"
+        ));
+        assert_eq!(request_content_type(search), "application/json");
+
+        let like = route(Method::POST, "/api/v1/like?unlike=true", "")
+            .await
+            .unwrap();
+        assert!(matches!(
+            like,
+            Some(Route::Request(Request::Like { unlike: true }))
+        ));
+        assert_eq!(request_content_type(like), "text/plain; charset=utf-8");
+    }
+
+    #[test]
+    fn map_export_content_types() {
+        for format in [
+            PlaylistFileFormat::M3u,
+            PlaylistFileFormat::Csv,
+            PlaylistFileFormat::Json,
+            PlaylistFileFormat::Xspf,
+        ] {
+            let request = Request::Playlist(PlaylistCommand::Export {
+                id: PlaylistId::from_id("37i9dQZF1DXcBWIGoYBM5M").unwrap(),
+                format,
+            });
+            assert_eq!(response_content_type(&request), format.content_type());
+        }
+    }
+}
@@ -1,15 +1,69 @@
 mod client;
 mod commands;
+mod events;
 mod handlers;
+#[cfg(feature = "http-api")]
+mod http;
+mod playlist_file;
 
-use crate::config;
+use crate::{config, state::SleepTimerSpec};
 use rspotify::model::*;
 use serde::{Deserialize, Serialize};
 
+/// the maximum size of a request sent via the legacy UDP socket
 const MAX_REQUEST_SIZE: usize = 4096;
+/// name of the folder storing the client's Unix domain socket, which is only accessible by the current user
+#[cfg(unix)]
+const CLIENT_SOCKET_FOLDER: &str = "spotify_player";
+/// name of the client's Unix domain socket file without the extension
+#[cfg(unix)]
+const CLIENT_SOCKET_FILE_STEM: &str = "spotify_player";
+/// the maximum size of a message sent over the Unix domain socket
+#[cfg(unix)]
+const MAX_FRAME_SIZE: u64 = 16 * 1024 * 1024;
 
-pub use client::start_socket;
+/// the duration between two consecutive renewals of an event subscription
+#[cfg(not(unix))]
+const SUBSCRIPTION_RENEWAL_DURATION: std::time::Duration = std::time::Duration::from_secs(10);
+/// an event subscription expires if it's not renewed within this duration
+const SUBSCRIPTION_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+pub use client::start_client_sockets;
+#[cfg(not(unix))]
+use client::start_udp_socket;
+#[cfg(unix)]
+use client::{bind_unix_listener, start_unix_socket};
 pub use handlers::handle_cli_subcommand;
+use playlist_file::{PlaylistFile, PlaylistFileFormat, PlaylistFileTrack};
+
+/// Get the path of the client's Unix domain socket, which is placed inside a `spotify_player` folder
+/// of the user's runtime directory (`$XDG_RUNTIME_DIR`) if exists or the application's cache folder otherwise.
+/// Applications started with different profiles listen on different sockets.
+///
+/// Messages sent over the socket are JSON-encoded and framed by a prefix
+/// storing the message's length as a big-endian `u64`.
+#[cfg(unix)]
+fn client_socket_path(configs: &config::Configs) -> std::path::PathBuf {
+    dirs_next::runtime_dir()
+        .unwrap_or_else(|| configs.cache_folder.clone())
+        .join(CLIENT_SOCKET_FOLDER)
+        .join(match config::active_profile_name() {
+            Some(name) => format!("{CLIENT_SOCKET_FILE_STEM}-{name}.sock"),
+            None => format!("{CLIENT_SOCKET_FILE_STEM}.sock"),
+        })
+}
+
+/// Check that a message of `len` bytes received from the Unix domain socket doesn't exceed [`MAX_FRAME_SIZE`]
+#[cfg(unix)]
+fn check_frame_size(len: u64) -> std::io::Result<usize> {
+    if len > MAX_FRAME_SIZE {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("message size ({len} bytes) exceeds the maximum size ({MAX_FRAME_SIZE} bytes)"),
+        ));
+    }
+    Ok(len as usize)
+}
 
 #[derive(Debug, Serialize, Deserialize, clap::ValueEnum, Clone)]
 pub enum Key {
@@ -49,6 +103,10 @@ enum ItemId {
 #[derive(Debug, Serialize, Deserialize)]
 pub enum GetRequest {
     Key(Key),
+    /// the current playback rendered with a playback template
+    PlaybackText {
+        format: String,
+    },
     Item(ItemType, IdOrName),
 }
 
@@ -82,6 +140,29 @@ pub enum PlaylistCommand {
         id: Option<PlaylistId<'static>>,
         delete: bool,
     },
+    Export {
+        id: PlaylistId<'static>,
+        format: PlaylistFileFormat,
+    },
+    ImportFile {
+        path: std::path::PathBuf,
+        format: PlaylistFileFormat,
+        /// name of the new playlist, defaults to the playlist file's name
+        name: Option<String>,
+        /// an existing playlist to import into instead of creating a new playlist
+        to: Option<PlaylistId<'static>>,
+    },
+    Materialize {
+        /// name of the smart playlist to materialize, all smart playlists are materialized if not specified
+        name: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum TimerCommand {
+    Set(SleepTimerSpec),
+    Cancel,
+    Status,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -118,6 +199,41 @@ pub enum Request {
     Like { unlike: bool },
     Playlist(PlaylistCommand),
     Search { query: String },
+    Undo { redo: bool },
+    Timer(TimerCommand),
+    Subscribe,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "event")]
+/// A player event pushed to subscribers of the client socket
+pub enum Event {
+    TrackChanged {
+        track_id: Option<String>,
+        track: Option<String>,
+        artists: Vec<String>,
+        album: Option<String>,
+    },
+    PlaybackStatusChanged {
+        is_playing: bool,
+    },
+    Seeked {
+        position_ms: i64,
+    },
+    VolumeChanged {
+        volume: Option<u32>,
+    },
+    DeviceChanged {
+        device_id: Option<String>,
+        device_name: Option<String>,
+    },
+    QueueChanged {
+        track_ids: Vec<String>,
+    },
+    LikedStateChanged {
+        track_id: String,
+        liked: bool,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -163,6 +279,10 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
         .subcommand(commands::init_playlist_subcommand())
         .subcommand(commands::init_generate_command())
         .subcommand(commands::init_search_command())
+        .subcommand(commands::init_history_command())
+        .subcommand(commands::init_watch_command())
+        .subcommand(commands::init_undo_command())
+        .subcommand(commands::init_timer_subcommand())
         .arg(
             clap::Arg::new("theme")
                 .short('t')
@@ -185,6 +305,13 @@ pub fn init_cli() -> anyhow::Result<clap::Command> {
                 .value_name("FOLDER")
                 .default_value(default_cache_folder.into_os_string())
                 .help("Path to the application's cache folder"),
+        )
+        .arg(
+            clap::Arg::new("profile")
+                .long("profile")
+                .value_name("PROFILE")
+                .global(true)
+                .help("Name of the profile (specified in the application's configurations) to use"),
         );
 
     #[cfg(feature = "daemon")]
@@ -0,0 +1,283 @@
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+/// prefix of a track's Spotify URL
+const SPOTIFY_TRACK_URL_PREFIX: &str = "https://open.spotify.com/track/";
+/// prefix of an XSPF identifier storing a track's ISRC
+const ISRC_URN_PREFIX: &str = "urn:isrc:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+/// A playlist file format
+pub enum PlaylistFileFormat {
+    M3u,
+    Csv,
+    Json,
+    Xspf,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A playlist stored in a playlist file
+pub struct PlaylistFile {
+    pub name: Option<String>,
+    pub tracks: Vec<PlaylistFileTrack>,
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+/// A track (row) in a playlist file
+pub struct PlaylistFileTrack {
+    /// the track's Spotify URI
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub title: String,
+    /// the track's artists, separated by commas
+    #[serde(default)]
+    pub artists: String,
+    #[serde(default)]
+    pub album: String,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+    #[serde(default)]
+    pub isrc: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename = "playlist")]
+struct Xspf {
+    #[serde(rename = "@version", default)]
+    version: String,
+    #[serde(rename = "@xmlns", default)]
+    xmlns: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(rename = "trackList")]
+    track_list: XspfTrackList,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct XspfTrackList {
+    #[serde(default)]
+    track: Vec<XspfTrack>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct XspfTrack {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    #[serde(default)]
+    identifier: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    creator: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    album: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration: Option<u64>,
+}
+
+impl PlaylistFileFormat {
+    /// Infer a playlist file format from a file's extension
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "m3u" | "m3u8" => Some(Self::M3u),
+            "csv" => Some(Self::Csv),
+            "json" => Some(Self::Json),
+            "xspf" => Some(Self::Xspf),
+            _ => None,
+        }
+    }
+
+    /// Get the media type of a playlist file in the format
+    #[cfg(feature = "http-api")]
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::M3u => "audio/x-mpegurl; charset=utf-8",
+            Self::Csv => "text/csv; charset=utf-8",
+            Self::Json => "application/json",
+            Self::Xspf => "application/xspf+xml",
+        }
+    }
+}
+
+impl PlaylistFileTrack {
+    /// Get the track's Spotify ID from its URI, which can also be a Spotify URL
+    pub fn spotify_id(&self) -> Option<&str> {
+        let uri = self.uri.as_deref()?;
+        let id = uri
+            .strip_prefix("spotify:track:")
+            .or_else(|| uri.strip_prefix(SPOTIFY_TRACK_URL_PREFIX))?;
+        // Spotify URLs can include a query string, e.g. `?si=...`
+        Some(id.split('?').next().unwrap_or(id))
+    }
+
+    /// Get a human-readable description of the track
+    pub fn description(&self) -> String {
+        match (self.artists.is_empty(), &self.uri) {
+            (false, _) => format!("{} - {}", self.artists, self.title),
+            (true, Some(uri)) if self.title.is_empty() => uri.clone(),
+            _ => self.title.clone(),
+        }
+    }
+
+    fn spotify_url(&self) -> Option<String> {
+        self.spotify_id()
+            .map(|id| format!("{SPOTIFY_TRACK_URL_PREFIX}{id}"))
+    }
+}
+
+impl PlaylistFile {
+    /// Serialize the playlist into a playlist file's content
+    pub fn to_string(&self, format: PlaylistFileFormat) -> Result<String> {
+        match format {
+            PlaylistFileFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            PlaylistFileFormat::Csv => {
+                let mut writer = csv::Writer::from_writer(Vec::new());
+                for track in &self.tracks {
+                    writer.serialize(track)?;
+                }
+                Ok(String::from_utf8(writer.into_inner()?)?)
+            }
+            PlaylistFileFormat::M3u => {
+                let mut s = String::from("#EXTM3U\n");
+                if let Some(name) = &self.name {
+                    s += &format!("#PLAYLIST:{name}\n");
+                }
+                for track in &self.tracks {
+                    let duration = track.duration_ms.map(|d| d / 1000).unwrap_or_default();
+                    s += &format!("#EXTINF:{duration},{}\n", track.description());
+                    s += &track.spotify_url().unwrap_or_default();
+                    s += "\n";
+                }
+                Ok(s)
+            }
+            PlaylistFileFormat::Xspf => {
+                let xspf = Xspf {
+                    version: "1".to_string(),
+                    xmlns: "http://xspf.org/ns/0/".to_string(),
+                    title: self.name.clone(),
+                    track_list: XspfTrackList {
+                        track: self
+                            .tracks
+                            .iter()
+                            .map(|t| XspfTrack {
+                                location: t.spotify_url(),
+                                identifier: t
+                                    .uri
+                                    .iter()
+                                    .cloned()
+                                    .chain(
+                                        t.isrc
+                                            .iter()
+                                            .map(|isrc| format!("{ISRC_URN_PREFIX}{isrc}")),
+                                    )
+                                    .collect(),
+                                title: Some(t.title.clone()),
+                                creator: Some(t.artists.clone()),
+                                album: Some(t.album.clone()),
+                                duration: t.duration_ms,
+                            })
+                            .collect(),
+                    },
+                };
+                Ok(format!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n",
+                    quick_xml::se::to_string(&xspf)?
+                ))
+            }
+        }
+    }
+
+    /// Parse a playlist file's content
+    pub fn parse(content: &str, format: PlaylistFileFormat) -> Result<Self> {
+        match format {
+            PlaylistFileFormat::Json => {
+                serde_json::from_str(content).context("failed to parse the JSON playlist file")
+            }
+            PlaylistFileFormat::Csv => {
+                let tracks = csv::Reader::from_reader(content.as_bytes())
+                    .deserialize()
+                    .collect::<Result<Vec<_>, _>>()
+                    .context("failed to parse the CSV playlist file")?;
+                Ok(Self { name: None, tracks })
+            }
+            PlaylistFileFormat::M3u => Ok(Self::parse_m3u(content)),
+            PlaylistFileFormat::Xspf => {
+                let xspf: Xspf = quick_xml::de::from_str(content)
+                    .context("failed to parse the XSPF playlist file")?;
+                let tracks = xspf
+                    .track_list
+                    .track
+                    .into_iter()
+                    .map(|t| {
+                        let isrc = t
+                            .identifier
+                            .iter()
+                            .find_map(|i| i.strip_prefix(ISRC_URN_PREFIX))
+                            .map(|i| i.to_string());
+                        let uri = t.identifier.into_iter().chain(t.location).find(|i| {
+                            i.starts_with("spotify:track:")
+                                || i.starts_with(SPOTIFY_TRACK_URL_PREFIX)
+                        });
+                        PlaylistFileTrack {
+                            uri,
+                            title: t.title.unwrap_or_default(),
+                            artists: t.creator.unwrap_or_default(),
+                            album: t.album.unwrap_or_default(),
+                            duration_ms: t.duration,
+                            isrc,
+                        }
+                    })
+                    .collect();
+                Ok(Self {
+                    name: xspf.title,
+                    tracks,
+                })
+            }
+        }
+    }
+
+    /// Parse an (extended) M3U playlist file's content, in which a track's title and artists
+    /// are taken from its `#EXTINF` line or, if not specified, its location's file name
+    fn parse_m3u(content: &str) -> Self {
+        let mut playlist = Self::default();
+        let mut track = PlaylistFileTrack::default();
+
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            if let Some(name) = line.strip_prefix("#PLAYLIST:") {
+                playlist.name = Some(name.trim().to_string());
+            } else if let Some(info) = line.strip_prefix("#EXTINF:") {
+                let (duration, desc) = info.split_once(',').unwrap_or((info, ""));
+                track.duration_ms = duration
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|d| *d > 0)
+                    .map(|d| d * 1000);
+                (track.artists, track.title) = split_description(desc);
+            } else if !line.starts_with('#') {
+                if line.starts_with("spotify:track:") || line.starts_with(SPOTIFY_TRACK_URL_PREFIX)
+                {
+                    track.uri = Some(line.to_string());
+                } else if track.title.is_empty() {
+                    let stem = std::path::Path::new(line)
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .unwrap_or(line);
+                    (track.artists, track.title) = split_description(stem);
+                }
+                playlist.tracks.push(std::mem::take(&mut track));
+            }
+        }
+
+        playlist
+    }
+}
+
+/// Split a track's description in the `{artists} - {title}` format into its artists and title
+fn split_description(desc: &str) -> (String, String) {
+    match desc.split_once(" - ") {
+        Some((artists, title)) => (artists.trim().to_string(), title.trim().to_string()),
+        None => (String::new(), desc.trim().to_string()),
+    }
+}
@@ -2,7 +2,7 @@ use anyhow::Context;
 use rspotify::model::PlayableItem;
 use tracing::Instrument;
 
-use crate::{config, state::*};
+use crate::{config, scrobbler, state::*};
 
 #[cfg(feature = "lyric-finder")]
 use crate::utils::map_join;
@@ -11,6 +11,16 @@ use super::ClientRequest;
 
 struct PlayerEventHandlerState {
     add_track_to_queue_req_timer: std::time::Instant,
+    /// the play record of the currently playing track
+    play_record: Option<PlayRecord>,
+    play_record_progress: chrono::Duration,
+    play_record_timer: std::time::Instant,
+    scrobbler_pub: Option<flume::Sender<scrobbler::ScrobblerRequest>>,
+    /// the start time of the latest play record notified to the scrobbler
+    scrobbler_record_started_at: Option<chrono::DateTime<chrono::Utc>>,
+    scrobbler_record_scrobbled: bool,
+    /// the active profile's name, `None` for the default profile
+    profile: Option<String>,
 }
 
 /// starts the client's request handler
@@ -72,24 +82,164 @@ fn handle_playback_change_event(
         client_pub.send(ClientRequest::GetCurrentUserQueue)?;
     }
 
-    // handle fake track repeat mode
+    // re-queue the current track (fake track repeat mode) or feed the application's queue
+    // into Spotify's queue if the current track is about to end, while
+    // ensuring that only one `AddTrackToQueue` request is made
+    let is_track_ending = playback.is_playing
+        && player
+            .playback_progress()
+            .is_some_and(|progress| progress + chrono::TimeDelta::seconds(5) >= track.duration)
+        && handler_state.add_track_to_queue_req_timer.elapsed()
+            > std::time::Duration::from_secs(10);
+    if !is_track_ending {
+        return Ok(());
+    }
+
     if playback.fake_track_repeat_state {
-        if let Some(progress) = player.playback_progress() {
-            // re-queue the current track if it's about to end while
-            // ensuring that only one `AddTrackToQueue` request is made
-            if progress + chrono::TimeDelta::seconds(5) >= track.duration
-                && playback.is_playing
-                && handler_state.add_track_to_queue_req_timer.elapsed()
-                    > std::time::Duration::from_secs(10)
-            {
-                tracing::info!(
-                    "fake track repeat mode is enabled, add the current track ({}) to queue",
-                    track.name
-                );
-                client_pub.send(ClientRequest::AddTrackToQueue(track.id.clone().unwrap()))?;
-                handler_state.add_track_to_queue_req_timer = std::time::Instant::now();
+        tracing::info!(
+            "fake track repeat mode is enabled, add the current track ({}) to queue",
+            track.name
+        );
+        client_pub.send(ClientRequest::AddTrackToQueue(track.id.clone().unwrap()))?;
+        handler_state.add_track_to_queue_req_timer = std::time::Instant::now();
+    } else if !player.local_queue.is_empty() {
+        drop(player);
+        let next_track = {
+            let mut player = state.player.write();
+            (!player.local_queue.is_empty()).then(|| player.local_queue.remove(0))
+        };
+        if let Some(track) = next_track {
+            tracing::info!(
+                "add the next track ({}) in the application's queue to queue",
+                track.name
+            );
+            client_pub.send(ClientRequest::AddTrackToQueue(track.id))?;
+            handler_state.add_track_to_queue_req_timer = std::time::Instant::now();
+        }
+    }
+
+    Ok(())
+}
+
+/// records the currently playing track into the user's play history
+fn handle_play_history_event(
+    state: &SharedState,
+    handler_state: &mut PlayerEventHandlerState,
+) -> anyhow::Result<()> {
+    let finished_record = update_play_record(state, handler_state);
+
+    let mut data = state.data.write();
+    if let Some(record) = finished_record {
+        if record.played_ms > 0 {
+            tracing::info!("Adding a new play record to the play history: {record:?}");
+            data.play_history
+                .add(record)
+                .context("add a new play record")?;
+        }
+    }
+    // the current play is kept in the history, so it can be flushed when the application is shut down
+    data.play_history
+        .set_pending(handler_state.play_record.clone());
+
+    Ok(())
+}
+
+/// updates the play record of the currently playing track,
+/// returns the previous play record if its play is finished
+fn update_play_record(
+    state: &SharedState,
+    handler_state: &mut PlayerEventHandlerState,
+) -> Option<PlayRecord> {
+    let elapsed = handler_state.play_record_timer.elapsed();
+    handler_state.play_record_timer = std::time::Instant::now();
+
+    let player = state.player.read();
+    let track = match player.current_playing_track() {
+        Some(track) => track,
+        // the playback is stopped (or no track is playing), which finishes the current play
+        None => return handler_state.play_record.take(),
+    };
+    let track_uri = track.id.as_ref()?.uri();
+    let is_playing = player
+        .buffered_playback
+        .as_ref()
+        .map(|p| p.is_playing)
+        .unwrap_or_default();
+    let progress = player.playback_progress().unwrap_or_default();
+
+    let is_same_play = match handler_state.play_record {
+        // the track is considered to be played again if it's restarted from the beginning
+        Some(ref record) => {
+            record.track_uri == track_uri
+                && (progress >= handler_state.play_record_progress
+                    || progress >= chrono::Duration::seconds(5))
+        }
+        None => false,
+    };
+    handler_state.play_record_progress = progress;
+
+    if is_same_play {
+        if is_playing {
+            if let Some(record) = handler_state.play_record.as_mut() {
+                record.played_ms += elapsed.as_millis() as u64;
             }
         }
+        None
+    } else {
+        handler_state.play_record.replace(PlayRecord {
+            track_uri,
+            track: track.name.clone(),
+            artists: track.artists.iter().map(|a| a.name.clone()).collect(),
+            album: track.album.name.clone(),
+            context_uri: player.playing_context_id().map(|id| id.uri()),
+            started_at: chrono::Utc::now(),
+            played_ms: 0,
+            duration_ms: track.duration.num_milliseconds() as u64,
+        })
+    }
+}
+
+/// notifies the scrobbler about the currently playing track, which is
+/// scrobbled once it satisfies the scrobbling requirements
+fn handle_scrobble_event(handler_state: &mut PlayerEventHandlerState) -> anyhow::Result<()> {
+    let (scrobbler_pub, record) = match (
+        handler_state.scrobbler_pub.as_ref(),
+        handler_state.play_record.as_ref(),
+    ) {
+        (Some(scrobbler_pub), Some(record)) => (scrobbler_pub, record),
+        _ => return Ok(()),
+    };
+
+    if handler_state.scrobbler_record_started_at != Some(record.started_at) {
+        handler_state.scrobbler_record_started_at = Some(record.started_at);
+        handler_state.scrobbler_record_scrobbled = false;
+        scrobbler_pub.send(scrobbler::ScrobblerRequest::NowPlaying(record.into()))?;
+    }
+
+    if !handler_state.scrobbler_record_scrobbled && scrobbler::should_scrobble(record) {
+        handler_state.scrobbler_record_scrobbled = true;
+        scrobbler_pub.send(scrobbler::ScrobblerRequest::Scrobble(record.into()))?;
+    }
+
+    Ok(())
+}
+
+/// handles a switch of the active profile
+fn handle_profile_change_event(handler_state: &mut PlayerEventHandlerState) -> anyhow::Result<()> {
+    let profile = config::active_profile_name();
+    if handler_state.profile == profile {
+        return Ok(());
+    }
+    handler_state.profile = profile;
+
+    // the old profile's play has been flushed into its play history when switching the profile,
+    // and the new profile's plays are scrobbled by a scrobbler re-created for the new profile
+    handler_state.play_record = None;
+    handler_state.play_record_progress = chrono::Duration::zero();
+    handler_state.scrobbler_record_started_at = None;
+    handler_state.scrobbler_record_scrobbled = false;
+    if let Some(scrobbler_pub) = handler_state.scrobbler_pub.as_ref() {
+        scrobbler_pub.send(scrobbler::ScrobblerRequest::SwitchProfile)?;
     }
 
     Ok(())
@@ -124,12 +274,14 @@ fn handle_page_change_event(
                             ContextId::Artist(_) => ContextPageUIState::new_artist(),
                             ContextId::Playlist(_) => ContextPageUIState::new_playlist(),
                             ContextId::Tracks(_) => ContextPageUIState::new_tracks(),
+                            ContextId::Show(_) => ContextPageUIState::new_show(),
                         });
                     }
                     None => {
                         *page_state = None;
                     }
                 }
+                state.render.notify();
             }
 
             // request new context's data if not found in memory
@@ -147,6 +299,7 @@ fn handle_page_change_event(
             track,
             artists,
             scroll_offset,
+            offset_ms,
         } => {
             if let Some(current_track) = state.player.read().current_playing_track() {
                 if current_track.name != *track {
@@ -154,10 +307,13 @@ fn handle_page_change_event(
                     track.clone_from(&current_track.name);
                     *artists = map_join(&current_track.artists, |a| &a.name, ", ");
                     *scroll_offset = 0;
+                    *offset_ms = 0;
+                    state.render.notify();
 
                     client_pub.send(ClientRequest::GetLyric {
                         track: track.clone(),
                         artists: artists.clone(),
+                        duration: current_track.duration.to_std().unwrap_or_default(),
                     })?;
                 }
             }
@@ -173,9 +329,12 @@ fn handle_player_event(
     client_pub: &flume::Sender<ClientRequest>,
     handler_state: &mut PlayerEventHandlerState,
 ) -> anyhow::Result<()> {
+    handle_profile_change_event(handler_state).context("handle profile change event")?;
     handle_page_change_event(state, client_pub).context("handle page change event")?;
     handle_playback_change_event(state, client_pub, handler_state)
         .context("handle playback change event")?;
+    handle_play_history_event(state, handler_state).context("handle play history event")?;
+    handle_scrobble_event(handler_state).context("handle scrobble event")?;
 
     Ok(())
 }
@@ -185,6 +344,7 @@ fn handle_player_event(
 pub async fn start_player_event_watchers(
     state: SharedState,
     client_pub: flume::Sender<ClientRequest>,
+    scrobbler_pub: Option<flume::Sender<scrobbler::ScrobblerRequest>>,
 ) {
     let configs = config::get_config();
 
@@ -211,6 +371,13 @@ pub async fn start_player_event_watchers(
     let refresh_duration = std::time::Duration::from_secs(1);
     let mut handler_state = PlayerEventHandlerState {
         add_track_to_queue_req_timer: std::time::Instant::now(),
+        play_record: None,
+        play_record_progress: chrono::Duration::zero(),
+        play_record_timer: std::time::Instant::now(),
+        scrobbler_pub,
+        scrobbler_record_started_at: None,
+        scrobbler_record_scrobbled: false,
+        profile: config::active_profile_name(),
     };
 
     loop {
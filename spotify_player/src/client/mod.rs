@@ -1,5 +1,9 @@
 use std::ops::Deref;
-use std::{borrow::Cow, collections::HashMap, sync::Arc};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::config;
 use crate::{auth::AuthConfig, state::*};
@@ -12,7 +16,7 @@ use librespot_core::session::Session;
 use reqwest::StatusCode;
 use rspotify::{
     http::Query,
-    model::{FullPlaylist, Market, Page, SimplifiedPlaylist},
+    model::{FullPlaylist, ItemPositions, Market, Page, SimplifiedPlaylist},
     prelude::*,
 };
 
@@ -25,15 +29,30 @@ pub use request::*;
 use serde::Deserialize;
 
 const SPOTIFY_API_ENDPOINT: &str = "https://api.spotify.com/v1";
+/// the maximum number of items per request when adding/removing playlist items
+pub const PLAYLIST_ITEMS_BATCH_SIZE: usize = 100;
+/// the maximum number of tracks per request when adding/removing/checking saved tracks
+const SAVED_TRACKS_BATCH_SIZE: usize = 50;
+/// the types of items returned when getting the current playback, so that playing episodes are included
+pub const PLAYBACK_ADDITIONAL_TYPES: [rspotify_model::AdditionalType; 2] = [
+    rspotify_model::AdditionalType::Track,
+    rspotify_model::AdditionalType::Episode,
+];
 
 /// The application's Spotify client
 #[derive(Clone)]
 pub struct Client {
     http: reqwest::Client,
     spotify: Arc<spotify::Spotify>,
-    auth_config: AuthConfig,
+    auth_config: Arc<Mutex<AuthConfig>>,
     #[cfg(feature = "streaming")]
     stream_conn: Arc<Mutex<Option<librespot_connect::spirc::Spirc>>>,
+    #[cfg(feature = "streaming")]
+    fader: Arc<crate::streaming::Fader>,
+    #[cfg(feature = "streaming")]
+    dsp: Arc<crate::dsp::Dsp>,
+    /// whether a task indexing the user's library is running
+    is_indexing_library: Arc<std::sync::atomic::AtomicBool>,
 }
 
 impl Deref for Client {
@@ -53,10 +72,17 @@ impl Client {
         Self {
             spotify: Arc::new(spotify::Spotify::new(session, client_id)),
             http: reqwest::Client::new(),
-            auth_config,
+            auth_config: Arc::new(Mutex::new(auth_config)),
+            is_indexing_library: Arc::new(std::sync::atomic::AtomicBool::new(false)),
 
             #[cfg(feature = "streaming")]
             stream_conn: Arc::new(Mutex::new(None)),
+            #[cfg(feature = "streaming")]
+            fader: Arc::new(crate::streaming::Fader::new(
+                &config::get_config().app_config.device,
+            )),
+            #[cfg(feature = "streaming")]
+            dsp: Arc::new(crate::dsp::Dsp::new(&config::get_config().app_config.dsp)),
         }
     }
 
@@ -86,7 +112,8 @@ impl Client {
 
     /// Create a new client session
     async fn new_session(&self, state: &SharedState) -> Result<()> {
-        let session = crate::auth::new_session(&self.auth_config, false).await?;
+        let auth_config = self.auth_config.lock().clone();
+        let session = crate::auth::new_session(&auth_config, false).await?;
         *self.session.lock().await = Some(session);
 
         tracing::info!("Used a new session for Spotify client.");
@@ -101,6 +128,133 @@ impl Client {
         Ok(())
     }
 
+    /// Switch to another profile, `None` for the default profile.
+    ///
+    /// The client's session and the application's data are rebuilt using
+    /// the new profile's credentials and caches.
+    pub async fn switch_profile(&self, state: &SharedState, name: Option<String>) -> Result<()> {
+        let configs = config::get_config();
+        let old_profile = config::active_profile_name();
+        config::set_active_profile(name.as_deref())?;
+
+        // create a new session using the profile's cached credentials,
+        // reverting to the old profile on failure
+        let auth_config = match AuthConfig::new(&configs) {
+            Ok(auth_config) => auth_config,
+            Err(err) => {
+                config::set_active_profile(old_profile.as_deref())?;
+                return Err(err);
+            }
+        };
+        let session = match crate::auth::new_session(&auth_config, false).await {
+            Ok(session) => session,
+            Err(err) => {
+                config::set_active_profile(old_profile.as_deref())?;
+                return Err(err.context(format!(
+                    "create a new session for profile {}",
+                    name.as_deref().unwrap_or("default")
+                )));
+            }
+        };
+
+        *self.session.lock().await = Some(session);
+        *self.auth_config.lock() = auth_config;
+        self.set_client_id(configs.client_id());
+        self.refresh_token().await?;
+        tracing::info!(
+            "Switched to profile {}",
+            name.as_deref().unwrap_or("default")
+        );
+
+        // reset the application's states, the old profile's pending play record is
+        // flushed into the old profile's play history before its data is dropped
+        state.flush_play_history();
+        *state.data.write() = AppData::new(&configs.profile_cache_folder());
+        *state.player.write() = PlayerState::default();
+        {
+            let mut ui = state.ui.lock();
+            ui.history = vec![PageState::Library {
+                state: LibraryPageUIState::new(),
+            }];
+            ui.popup = None;
+            ui.track_selection = TrackSelection::default();
+        }
+
+        self.initialize_playback(state)
+            .await
+            .context("initialize playback")?;
+
+        // retrieve the new profile's user data
+        for request in [
+            ClientRequest::GetCurrentUser,
+            ClientRequest::GetUserPlaylists,
+            ClientRequest::GetUserFollowedArtists,
+            ClientRequest::GetUserSavedAlbums,
+            ClientRequest::GetUserSavedTracks,
+            ClientRequest::GetUserSavedShows,
+        ] {
+            Box::pin(self.handle_request(state, request)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Get a track's lyric from the lyric providers specified in the application's configurations.
+    ///
+    /// Lyrics retrieved from remote providers are stored in the application's lyric file cache,
+    /// which is looked up after local providers and before remote providers.
+    #[cfg(feature = "lyric-finder")]
+    async fn get_lyric(
+        &self,
+        query: &lyric_finder::LyricQuery,
+    ) -> Result<lyric_finder::LyricResult> {
+        use lyric_finder::{LyricProvider, LyricResult, ProviderChain};
+
+        let configs = config::get_config();
+        let mut local_providers = ProviderChain::default();
+        let mut remote_providers = ProviderChain::default();
+        for provider in &configs.app_config.lyric_providers {
+            match provider {
+                config::LyricProviderType::Local => {
+                    if let Some(folder) = configs.app_config.lyric_folder.as_ref() {
+                        let provider = lyric_finder::LocalProvider::new(folder);
+                        if remote_providers.is_empty() {
+                            local_providers.push(provider);
+                        } else {
+                            remote_providers.push(provider);
+                        }
+                    }
+                }
+                config::LyricProviderType::Lrclib => {
+                    remote_providers
+                        .push(lyric_finder::LrclibProvider::from_http_client(&self.http));
+                }
+                config::LyricProviderType::Genius => {
+                    remote_providers.push(lyric_finder::Client::from_http_client(&self.http));
+                }
+            }
+        }
+
+        if let Ok(result @ LyricResult::Some { .. }) = local_providers.get_lyric(query).await {
+            return Ok(result);
+        }
+
+        let key = format!("{} {}", query.track, query.artists);
+        if let Some(result) = load_lyric_from_file_cache(&key, &configs.cache_folder) {
+            return Ok(result);
+        }
+
+        let result = remote_providers.get_lyric(query).await?;
+        if matches!(result, LyricResult::Some { .. }) {
+            if let Err(err) = store_lyric_into_file_cache(&key, &configs.cache_folder, &result) {
+                tracing::error!(
+                    "Failed to store the lyric of {key} into the cache folder: {err:#}"
+                );
+            }
+        }
+        Ok(result)
+    }
+
     /// Check if the current session is valid and if invalid, create a new session
     pub async fn check_valid_session(&self, state: &SharedState) -> Result<()> {
         if self.session().await.is_invalid() {
@@ -115,7 +269,13 @@ impl Client {
     /// Create a new streaming connection
     #[cfg(feature = "streaming")]
     pub async fn new_streaming_connection(&self, state: &SharedState) {
-        let new_conn = crate::streaming::new_connection(self.clone(), state.clone()).await;
+        let new_conn = crate::streaming::new_connection(
+            self.clone(),
+            state.clone(),
+            self.fader.clone(),
+            self.dsp.clone(),
+        )
+        .await;
 
         let mut stream_conn = self.stream_conn.lock();
         // shutdown old streaming connection and replace it with a new connection
@@ -125,6 +285,22 @@ impl Client {
         *stream_conn = Some(new_conn);
     }
 
+    /// Pause the playback on a device.
+    /// If the device is the integrated device, the playback is faded out before being paused.
+    async fn pause_playback_with_fade(&self, device_id: Option<&str>) -> Result<()> {
+        #[cfg(feature = "streaming")]
+        if device_id == Some(self.session().await.device_id()) {
+            self.fader.fade_out().await;
+            let result = self.pause_playback(device_id).await;
+            if result.is_err() {
+                self.fader.cancel_fade_out();
+            }
+            return Ok(result?);
+        }
+
+        Ok(self.pause_playback(device_id).await?)
+    }
+
     /// Handle a player request, return a new playback metadata on success
     pub async fn handle_player_request(
         &self,
@@ -172,7 +348,7 @@ impl Client {
 
             PlayerRequest::Pause => {
                 if playback.is_playing {
-                    self.pause_playback(device_id).await?;
+                    self.pause_playback_with_fade(device_id).await?;
                     playback.is_playing = false;
                 }
             }
@@ -180,7 +356,7 @@ impl Client {
                 if !playback.is_playing {
                     self.resume_playback(device_id, None).await?
                 } else {
-                    self.pause_playback(device_id).await?
+                    self.pause_playback_with_fade(device_id).await?
                 }
                 playback.is_playing = !playback.is_playing;
             }
@@ -203,6 +379,18 @@ impl Client {
 
                 playback.shuffle_state = !playback.shuffle_state;
             }
+            #[cfg(all(target_os = "linux", feature = "media-control"))]
+            PlayerRequest::SetRepeat(repeat_state) => {
+                self.repeat(repeat_state, device_id).await?;
+
+                playback.repeat_state = repeat_state;
+            }
+            #[cfg(all(target_os = "linux", feature = "media-control"))]
+            PlayerRequest::SetShuffle(shuffle) => {
+                self.shuffle(shuffle, device_id).await?;
+
+                playback.shuffle_state = shuffle;
+            }
             PlayerRequest::Volume(volume) => {
                 self.volume(volume, device_id).await?;
 
@@ -257,14 +445,24 @@ impl Client {
                     .insert(category.id, playlists);
             }
             #[cfg(feature = "lyric-finder")]
-            ClientRequest::GetLyric { track, artists } => {
-                let client = lyric_finder::Client::from_http_client(&self.http);
+            ClientRequest::GetLyric {
+                track,
+                artists,
+                duration,
+            } => {
                 let query = format!("{track} {artists}");
 
                 if !state.data.read().caches.lyrics.contains_key(&query) {
-                    let result = client.get_lyric(&query).await.context(format!(
-                        "failed to get lyric for track {track} - artists {artists}"
-                    ))?;
+                    let result = self
+                        .get_lyric(&lyric_finder::LyricQuery {
+                            track: track.clone(),
+                            artists: artists.clone(),
+                            duration: Some(duration),
+                        })
+                        .await
+                        .context(format!(
+                            "failed to get lyric for track {track} - artists {artists}"
+                        ))?;
 
                     state
                         .data
@@ -278,6 +476,13 @@ impl Client {
             ClientRequest::RestartIntegratedClient => {
                 self.new_session(state).await?;
             }
+            #[cfg(feature = "streaming")]
+            ClientRequest::SetEqPreset(name) => {
+                self.dsp.set_preset(&name)?;
+            }
+            ClientRequest::SwitchProfile(name) => {
+                self.switch_profile(state, name).await?;
+            }
             ClientRequest::GetCurrentUser => {
                 let user = self.current_user().await?;
                 state.data.write().user_data.user = Some(user);
@@ -302,17 +507,18 @@ impl Client {
                 let playlists = self.current_user_playlists().await?;
                 store_data_into_file_cache(
                     FileCacheKey::Playlists,
-                    &config::get_config().cache_folder,
+                    &config::get_config().profile_cache_folder(),
                     &playlists,
                 )
                 .context("store user's playlists into the cache folder")?;
                 state.data.write().user_data.playlists = playlists;
+                self.spawn_library_indexing(state);
             }
             ClientRequest::GetUserFollowedArtists => {
                 let artists = self.current_user_followed_artists().await?;
                 store_data_into_file_cache(
                     FileCacheKey::FollowedArtists,
-                    &config::get_config().cache_folder,
+                    &config::get_config().profile_cache_folder(),
                     &artists,
                 )
                 .context("store user's followed artists into the cache folder")?;
@@ -322,11 +528,22 @@ impl Client {
                 let albums = self.current_user_saved_albums().await?;
                 store_data_into_file_cache(
                     FileCacheKey::SavedAlbums,
-                    &config::get_config().cache_folder,
+                    &config::get_config().profile_cache_folder(),
                     &albums,
                 )
                 .context("store user's saved albums into the cache folder")?;
                 state.data.write().user_data.saved_albums = albums;
+                self.spawn_library_indexing(state);
+            }
+            ClientRequest::GetUserSavedShows => {
+                let shows = self.current_user_saved_shows().await?;
+                store_data_into_file_cache(
+                    FileCacheKey::SavedShows,
+                    &config::get_config().profile_cache_folder(),
+                    &shows,
+                )
+                .context("store user's saved shows into the cache folder")?;
+                state.data.write().user_data.saved_shows = shows;
             }
             ClientRequest::GetUserTopTracks => {
                 let uri = &USER_TOP_TRACKS_ID.uri;
@@ -350,10 +567,11 @@ impl Client {
                     .collect::<HashMap<_, _>>();
                 store_data_into_file_cache(
                     FileCacheKey::SavedTracks,
-                    &config::get_config().cache_folder,
+                    &config::get_config().profile_cache_folder(),
                     &tracks_hm,
                 )
                 .context("store user's saved tracks into the cache folder")?;
+                update_library_index(state, &USER_LIKED_TRACKS_ID.uri, &tracks)?;
 
                 let mut data = state.data.write();
                 data.user_data.saved_tracks = tracks_hm;
@@ -389,6 +607,7 @@ impl Client {
                         }
                         ContextId::Album(album_id) => self.album_context(album_id).await?,
                         ContextId::Artist(artist_id) => self.artist_context(artist_id).await?,
+                        ContextId::Show(show_id) => self.show_context(show_id).await?,
                         ContextId::Tracks(_) => {
                             anyhow::bail!(
                                 "`GetContext` request for `tracks` context is not supported!"
@@ -396,6 +615,12 @@ impl Client {
                         }
                     };
 
+                    if let Context::Playlist { tracks, .. } | Context::Album { tracks, .. } =
+                        &context
+                    {
+                        update_library_index(state, &uri, tracks)?;
+                    }
+
                     state
                         .data
                         .write()
@@ -405,7 +630,18 @@ impl Client {
                 }
             }
             ClientRequest::Search(query) => {
-                if !state.data.read().caches.search.contains_key(&query) {
+                if let Some(local_query) = query.strip_prefix(LOCAL_SEARCH_PREFIX) {
+                    // local search results are always re-computed as the library index can change
+                    let mut data = state.data.write();
+                    let results = data.library.search(
+                        local_query,
+                        &data.user_data.playlists,
+                        LOCAL_SEARCH_LIMIT,
+                    );
+                    data.caches
+                        .search
+                        .insert(query, results, *TTL_CACHE_DURATION);
+                } else if !state.data.read().caches.search.contains_key(&query) {
                     let results = self.search(&query).await?;
 
                     state
@@ -439,8 +675,14 @@ impl Client {
                     .await?
             }
             ClientRequest::AddTrackToPlaylist(playlist_id, track_id) => {
-                self.add_track_to_playlist(state, playlist_id, track_id)
-                    .await?;
+                self.apply_and_record_edit(
+                    state,
+                    Edit::AddPlaylistTracks {
+                        playlist_id,
+                        track_ids: vec![track_id],
+                    },
+                )
+                .await?;
             }
             ClientRequest::AddAlbumToQueue(album_id) => {
                 let album_context = self.album_context(album_id).await?;
@@ -452,15 +694,83 @@ impl Client {
                     }
                 }
             }
+            ClientRequest::AddEpisodeToQueue(episode_id) => {
+                self.add_item_to_queue(PlayableId::Episode(episode_id), None)
+                    .await?
+            }
+            ClientRequest::MarkEpisodePlayed(episode_id, played) => {
+                let played_episodes = {
+                    let mut data = state.data.write();
+                    data.user_data
+                        .played_episodes
+                        .insert(episode_id.uri(), played);
+                    data.user_data.played_episodes.clone()
+                };
+                store_data_into_file_cache(
+                    FileCacheKey::PlayedEpisodes,
+                    &config::get_config().profile_cache_folder(),
+                    &played_episodes,
+                )
+                .context("store played episodes into the cache folder")?;
+            }
             ClientRequest::DeleteTrackFromPlaylist(playlist_id, track_id) => {
-                self.delete_track_from_playlist(state, playlist_id, track_id)
+                self.apply_and_record_edit(
+                    state,
+                    Edit::DeletePlaylistTracks {
+                        playlist_id,
+                        track_ids: vec![track_id],
+                    },
+                )
+                .await?;
+            }
+            ClientRequest::AddTracksToQueue(track_ids) => {
+                self.add_tracks_to_queue(state, track_ids).await?;
+            }
+            ClientRequest::AddTracksToPlaylist(playlist_id, track_ids) => {
+                self.apply_and_record_edit(
+                    state,
+                    Edit::AddPlaylistTracks {
+                        playlist_id,
+                        track_ids,
+                    },
+                )
+                .await?;
+            }
+            ClientRequest::DeleteTracksFromPlaylist(playlist_id, track_ids) => {
+                self.apply_and_record_edit(
+                    state,
+                    Edit::DeletePlaylistTracks {
+                        playlist_id,
+                        track_ids,
+                    },
+                )
+                .await?;
+            }
+            ClientRequest::AddTracksToLiked(tracks) => {
+                let items = tracks.into_iter().map(Item::Track).collect();
+                self.apply_and_record_edit(state, Edit::AddToLibrary(items))
+                    .await?;
+            }
+            ClientRequest::DeleteTracksFromLiked(track_ids) => {
+                let ids = track_ids.into_iter().map(ItemId::Track).collect();
+                self.apply_and_record_edit(state, Edit::DeleteFromLibrary(ids))
                     .await?;
             }
+            ClientRequest::AddTracksToLocalQueue { tracks, play_next } => {
+                let queue = &mut state.player.write().local_queue;
+                if play_next {
+                    queue.splice(0..0, tracks);
+                } else {
+                    queue.extend(tracks);
+                }
+            }
             ClientRequest::AddToLibrary(item) => {
-                self.add_to_library(state, item).await?;
+                self.apply_and_record_edit(state, Edit::AddToLibrary(vec![item]))
+                    .await?;
             }
             ClientRequest::DeleteFromLibrary(id) => {
-                self.delete_from_library(state, id).await?;
+                self.apply_and_record_edit(state, Edit::DeleteFromLibrary(vec![id]))
+                    .await?;
             }
             ClientRequest::GetCurrentUserQueue => {
                 let queue = self.current_user_queue().await?;
@@ -470,16 +780,16 @@ impl Client {
                 playlist_id,
                 insert_index,
                 range_start,
-                range_length,
                 snapshot_id,
             } => {
-                self.reorder_playlist_items(
+                self.apply_and_record_edit(
                     state,
-                    playlist_id,
-                    insert_index,
-                    range_start,
-                    range_length,
-                    snapshot_id.as_deref(),
+                    Edit::ReorderPlaylistItem {
+                        playlist_id,
+                        insert_index,
+                        range_start,
+                        snapshot_id,
+                    },
                 )
                 .await?;
             }
@@ -488,6 +798,7 @@ impl Client {
                 public,
                 collab,
                 desc,
+                track_ids,
             } => {
                 let user_id = state
                     .data
@@ -497,17 +808,39 @@ impl Client {
                     .as_ref()
                     .map(|u| u.id.to_owned())
                     .unwrap();
-                self.create_new_playlist(
-                    state,
-                    user_id,
-                    playlist_name.as_str(),
-                    public,
-                    collab,
-                    desc.as_str(),
-                )
-                .await?;
+                let playlist_id = self
+                    .create_new_playlist(
+                        state,
+                        user_id,
+                        playlist_name.as_str(),
+                        public,
+                        collab,
+                        desc.as_str(),
+                    )
+                    .await?;
+                if !track_ids.is_empty() {
+                    self.add_tracks_to_playlist(state, playlist_id.clone(), track_ids)
+                        .await?;
+                }
+
+                // a created playlist is reverted by unfollowing (deleting) it
+                let entry = JournalEntry {
+                    desc: format!("create playlist \"{playlist_name}\""),
+                    timestamp: chrono::Utc::now(),
+                    edits: vec![Edit::DeleteFromLibrary(vec![ItemId::Playlist(playlist_id)])],
+                };
+                update_journal(state, |journal| journal.record(entry))?;
             }
+            ClientRequest::Undo => match self.undo(state, false).await? {
+                Some(desc) => tracing::info!("Undid: {desc}"),
+                None => tracing::info!("Nothing to undo"),
+            },
+            ClientRequest::Redo => match self.undo(state, true).await? {
+                Some(desc) => tracing::info!("Redid: {desc}"),
+                None => tracing::info!("Nothing to redo"),
+            },
         };
+        state.render.notify();
 
         tracing::info!(
             "Successfully handled the client request, took: {}ms",
@@ -638,7 +971,7 @@ impl Client {
         // otherwise, use the first available device.
         let id = devices
             .iter()
-            .position(|d| d.0 == configs.app_config.default_device)
+            .position(|d| d.0 == configs.default_device())
             .unwrap_or_default();
 
         Ok(Some(devices.remove(id).1))
@@ -652,7 +985,11 @@ impl Client {
         let tracks = self.all_paging_items(first_page, &market_query()).await?;
         Ok(tracks
             .into_iter()
-            .filter_map(|t| Track::try_from_full_track(t.track))
+            .filter_map(|t| {
+                let mut track = Track::try_from_full_track(t.track)?;
+                track.added_at = t.added_at.timestamp() as u64;
+                Some(track)
+            })
             .collect())
     }
 
@@ -741,6 +1078,16 @@ impl Client {
         Ok(albums.into_iter().map(|a| a.album.into()).collect())
     }
 
+    /// Get all saved shows of the current user
+    pub async fn current_user_saved_shows(&self) -> Result<Vec<Show>> {
+        let first_page = self.get_saved_show_manual(Some(50), None).await?;
+
+        let shows = self.all_paging_items(first_page, &Query::new()).await?;
+
+        // converts `rspotify_model::Show` into `state::Show`
+        Ok(shows.into_iter().map(|s| s.show.into()).collect())
+    }
+
     /// Get all albums of an artist
     pub async fn artist_albums(&self, artist_id: ArtistId<'_>) -> Result<Vec<Album>> {
         let payload = market_query();
@@ -780,7 +1127,7 @@ impl Client {
     }
 
     /// Start a playback
-    async fn start_playback(&self, playback: Playback, device_id: Option<&str>) -> Result<()> {
+    pub async fn start_playback(&self, playback: Playback, device_id: Option<&str>) -> Result<()> {
         match playback {
             Playback::Context(id, offset) => match id {
                 ContextId::Album(id) => {
@@ -795,6 +1142,10 @@ impl Client {
                     self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
                         .await?
                 }
+                ContextId::Show(id) => {
+                    self.start_context_playback(PlayContextId::from(id), device_id, offset, None)
+                        .await?
+                }
                 ContextId::Tracks(_) => {
                     anyhow::bail!("`StartPlayback` request for `tracks` context is not supported")
                 }
@@ -869,16 +1220,105 @@ impl Client {
         Ok(tracks)
     }
 
+    /// Spawn a background task indexing tracks of the user's library sources (playlists and saved albums)
+    /// that haven't been indexed into the local library index, and removing sources no longer in the library
+    fn spawn_library_indexing(&self, state: &SharedState) {
+        // only one indexing task runs at a time, which picks up sources added while it's running
+        if self
+            .is_indexing_library
+            .swap(true, std::sync::atomic::Ordering::SeqCst)
+        {
+            return;
+        }
+
+        let client = self.clone();
+        let state = state.clone();
+        tokio::task::spawn(async move {
+            if let Err(err) = client.index_missing_library_sources(&state).await {
+                tracing::error!("Failed to index the user's library: {err:#}");
+            }
+            client
+                .is_indexing_library
+                .store(false, std::sync::atomic::Ordering::SeqCst);
+        });
+    }
+
+    async fn index_missing_library_sources(&self, state: &SharedState) -> Result<()> {
+        let mut visited = HashSet::new();
+        loop {
+            let missing = {
+                let mut data = state.data.write();
+                let uris = data.user_data.library_source_uris();
+                let changed = data.library.retain_sources(|uri| uris.contains(uri));
+                let missing = data
+                    .user_data
+                    .playlists
+                    .iter()
+                    .map(|p| ContextId::Playlist(p.id.clone()))
+                    .chain(
+                        data.user_data
+                            .saved_albums
+                            .iter()
+                            .map(|a| ContextId::Album(a.id.clone())),
+                    )
+                    .filter(|id| {
+                        !data.library.contains_source(&id.uri()) && visited.insert(id.uri())
+                    })
+                    .collect::<Vec<_>>();
+                if missing.is_empty() {
+                    if changed {
+                        data.library
+                            .store()
+                            .context("store the library index into the cache folder")?;
+                    }
+                    return Ok(());
+                }
+                missing
+            };
+
+            tracing::info!("Indexing {} library sources...", missing.len());
+            for id in missing {
+                let uri = id.uri();
+                let context = match id {
+                    ContextId::Playlist(playlist_id) => self.playlist_context(playlist_id).await,
+                    ContextId::Album(album_id) => self.album_context(album_id).await,
+                    _ => unreachable!("library sources are either playlists or albums"),
+                };
+                match context {
+                    Ok(Context::Playlist { tracks, .. } | Context::Album { tracks, .. }) => {
+                        state.data.write().library.update_source(&uri, &tracks);
+                    }
+                    Ok(_) => {}
+                    Err(err) => tracing::warn!("Failed to index library source {uri}: {err:#}"),
+                }
+            }
+            let data = state.data.read();
+            tracing::info!("Indexed {} tracks in the library", data.library.n_tracks());
+            data.library
+                .store()
+                .context("store the library index into the cache folder")?;
+        }
+    }
+
     /// Search for items (tracks, artists, albums, playlists) matching a given query
     pub async fn search(&self, query: &str) -> Result<SearchResults> {
-        let (track_result, artist_result, album_result, playlist_result) = tokio::try_join!(
+        let (
+            track_result,
+            artist_result,
+            album_result,
+            playlist_result,
+            show_result,
+            episode_result,
+        ) = tokio::try_join!(
             self.search_specific_type(query, rspotify_model::SearchType::Track),
             self.search_specific_type(query, rspotify_model::SearchType::Artist),
             self.search_specific_type(query, rspotify_model::SearchType::Album),
-            self.search_specific_type(query, rspotify_model::SearchType::Playlist)
+            self.search_specific_type(query, rspotify_model::SearchType::Playlist),
+            self.search_specific_type(query, rspotify_model::SearchType::Show),
+            self.search_specific_type(query, rspotify_model::SearchType::Episode)
         )?;
 
-        let (tracks, artists, albums, playlists) = (
+        let (tracks, artists, albums, playlists, shows, episodes) = (
             match track_result {
                 rspotify_model::SearchResult::Tracks(p) => p
                     .items
@@ -907,13 +1347,36 @@ impl Client {
                 }
                 _ => anyhow::bail!("expect a playlist search result"),
             },
+            match show_result {
+                rspotify_model::SearchResult::Shows(p) => {
+                    p.items.into_iter().map(|s| s.into()).collect()
+                }
+                _ => anyhow::bail!("expect a show search result"),
+            },
+            match episode_result {
+                rspotify_model::SearchResult::Episodes(p) => p.items,
+                _ => anyhow::bail!("expect an episode search result"),
+            },
         );
 
+        // simplified episodes don't include their shows, so get the full episodes
+        let episodes = if episodes.is_empty() {
+            vec![]
+        } else {
+            self.get_several_episodes(episodes.into_iter().map(|e| e.id), Some(Market::FromToken))
+                .await?
+                .into_iter()
+                .map(Episode::from)
+                .collect()
+        };
+
         Ok(SearchResults {
             tracks,
             artists,
             albums,
             playlists,
+            shows,
+            episodes,
         })
     }
 
@@ -929,86 +1392,165 @@ impl Client {
             .await?)
     }
 
-    /// Add a track to a playlist
-    pub async fn add_track_to_playlist(
+    /// Add tracks to the playback queue, one request per track
+    async fn add_tracks_to_queue(
         &self,
         state: &SharedState,
-        playlist_id: PlaylistId<'_>,
-        track_id: TrackId<'_>,
+        track_ids: Vec<TrackId<'static>>,
     ) -> Result<()> {
-        // remove all the occurrences of the track to ensure no duplication in the playlist
-        self.playlist_remove_all_occurrences_of_items(
-            playlist_id.as_ref(),
-            [PlayableId::Track(track_id.as_ref())],
-            None,
-        )
-        .await?;
+        let progress = BulkProgressReporter::new(state, "Adding tracks to queue", track_ids.len());
+        for (i, track_id) in track_ids.into_iter().enumerate() {
+            progress.report(i);
+            self.add_item_to_queue(PlayableId::Track(track_id), None)
+                .await?;
+        }
+        Ok(())
+    }
 
-        self.playlist_add_items(
-            playlist_id.as_ref(),
-            [PlayableId::Track(track_id.as_ref())],
-            None,
-        )
-        .await?;
+    /// Add tracks to a playlist in batches, return the playlist's new snapshot ID
+    async fn add_tracks_to_playlist(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'static>,
+        track_ids: Vec<TrackId<'static>>,
+    ) -> Result<Option<String>> {
+        let progress =
+            BulkProgressReporter::new(state, "Adding tracks to playlist", track_ids.len());
+        let mut snapshot_id = None;
+        for (i, batch) in track_ids.chunks(PLAYLIST_ITEMS_BATCH_SIZE).enumerate() {
+            progress.report(i * PLAYLIST_ITEMS_BATCH_SIZE);
+            let items = batch.iter().map(|id| PlayableId::Track(id.as_ref()));
+            // remove all the occurrences of the tracks to ensure no duplication in the playlist
+            self.playlist_remove_all_occurrences_of_items(
+                playlist_id.as_ref(),
+                items.clone(),
+                None,
+            )
+            .await?;
+            let result = self
+                .playlist_add_items(playlist_id.as_ref(), items, None)
+                .await?;
+            snapshot_id = Some(result.snapshot_id);
+        }
 
-        // After adding a new track to a playlist, remove the cache of that playlist to force refetching new data
+        // After adding new tracks to a playlist, remove the cache of that playlist to force refetching new data
         state.data.write().caches.context.remove(&playlist_id.uri());
 
-        Ok(())
+        Ok(snapshot_id)
     }
 
-    /// Remove a track from a playlist
-    pub async fn delete_track_from_playlist(
+    /// Remove tracks from a playlist in batches
+    async fn delete_tracks_from_playlist(
         &self,
         state: &SharedState,
-        playlist_id: PlaylistId<'_>,
-        track_id: TrackId<'_>,
+        playlist_id: PlaylistId<'static>,
+        track_ids: Vec<TrackId<'static>>,
     ) -> Result<()> {
-        // remove all the occurrences of the track to ensure no duplication in the playlist
-        self.playlist_remove_all_occurrences_of_items(
-            playlist_id.as_ref(),
-            [PlayableId::Track(track_id.as_ref())],
-            None,
-        )
-        .await?;
+        let progress =
+            BulkProgressReporter::new(state, "Deleting tracks from playlist", track_ids.len());
+        for (i, batch) in track_ids.chunks(PLAYLIST_ITEMS_BATCH_SIZE).enumerate() {
+            progress.report(i * PLAYLIST_ITEMS_BATCH_SIZE);
+            self.playlist_remove_all_occurrences_of_items(
+                playlist_id.as_ref(),
+                batch.iter().map(|id| PlayableId::Track(id.as_ref())),
+                None,
+            )
+            .await?;
 
-        // After making a delete request, update the playlist in-memory data stored inside the app caches.
-        if let Some(Context::Playlist { tracks, .. }) = state
-            .data
-            .write()
-            .caches
-            .context
-            .get_mut(&playlist_id.uri())
-        {
-            tracks.retain(|t| t.id != track_id);
+            // update the playlist in-memory data stored inside the app caches
+            if let Some(Context::Playlist { tracks, .. }) = state
+                .data
+                .write()
+                .caches
+                .context
+                .get_mut(&playlist_id.uri())
+            {
+                tracks.retain(|t| !batch.contains(&t.id));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Add tracks to the user's liked tracks in batches, return the newly liked tracks
+    async fn add_tracks_to_liked(
+        &self,
+        state: &SharedState,
+        tracks: Vec<Track>,
+    ) -> Result<Vec<Track>> {
+        let progress = BulkProgressReporter::new(state, "Adding tracks to liked", tracks.len());
+        let mut liked_tracks = Vec::new();
+        for (i, batch) in tracks.chunks(SAVED_TRACKS_BATCH_SIZE).enumerate() {
+            progress.report(i * SAVED_TRACKS_BATCH_SIZE);
+            // only add tracks that are not liked yet to avoid duplicated items
+            let contains = self
+                .current_user_saved_tracks_contains(batch.iter().map(|t| t.id.as_ref()))
+                .await?;
+            let new_tracks = batch
+                .iter()
+                .zip(contains)
+                .filter_map(|(t, contains)| if contains { None } else { Some(t) })
+                .collect::<Vec<_>>();
+            if new_tracks.is_empty() {
+                continue;
+            }
+
+            self.current_user_saved_tracks_add(new_tracks.iter().map(|t| t.id.as_ref()))
+                .await?;
+            // update the in-memory `user_data`
+            let saved_tracks = &mut state.data.write().user_data.saved_tracks;
+            for track in new_tracks {
+                saved_tracks.insert(track.id.uri(), track.clone());
+                liked_tracks.push(track.clone());
+            }
         }
+        Ok(liked_tracks)
+    }
 
+    /// Delete tracks from the user's liked tracks in batches
+    async fn delete_tracks_from_liked(
+        &self,
+        state: &SharedState,
+        track_ids: Vec<TrackId<'static>>,
+    ) -> Result<()> {
+        let progress =
+            BulkProgressReporter::new(state, "Deleting tracks from liked", track_ids.len());
+        for (i, batch) in track_ids.chunks(SAVED_TRACKS_BATCH_SIZE).enumerate() {
+            progress.report(i * SAVED_TRACKS_BATCH_SIZE);
+            self.current_user_saved_tracks_delete(batch.iter().map(|id| id.as_ref()))
+                .await?;
+            // update the in-memory `user_data`
+            let saved_tracks = &mut state.data.write().user_data.saved_tracks;
+            for id in batch {
+                saved_tracks.remove(&id.uri());
+            }
+        }
         Ok(())
     }
 
-    /// Reorder items in a playlist
+    /// Move a playlist item from `range_start` to `insert_index`, return the playlist's new snapshot ID
     async fn reorder_playlist_items(
         &self,
         state: &SharedState,
         playlist_id: PlaylistId<'_>,
         insert_index: usize,
         range_start: usize,
-        range_length: Option<usize>,
         snapshot_id: Option<&str>,
-    ) -> Result<()> {
+    ) -> Result<String> {
         let insert_before = match insert_index > range_start {
             true => insert_index + 1,
             false => insert_index,
         };
 
-        self.playlist_reorder_items(
-            playlist_id.clone(),
-            Some(range_start as i32),
-            Some(insert_before as i32),
-            range_length.map(|range_length| range_length as u32),
-            snapshot_id,
-        )
-        .await?;
+        let result = self
+            .playlist_reorder_items(
+                playlist_id.clone(),
+                Some(range_start as i32),
+                Some(insert_before as i32),
+                None,
+                snapshot_id,
+            )
+            .await?;
 
         // After making a reorder request, update the playlist in-memory data stored inside the app caches.
         if let Some(Context::Playlist { tracks, .. }) = state
@@ -1022,52 +1564,56 @@ impl Client {
             tracks.insert(insert_index, track);
         }
 
-        Ok(())
+        Ok(result.snapshot_id)
     }
 
     /// Add a Spotify item to current user's library.
-    async fn add_to_library(&self, state: &SharedState, item: Item) -> Result<()> {
+    /// Return `false` if the item already exists in the library.
+    async fn add_to_library(&self, state: &SharedState, item: Item) -> Result<bool> {
         // Before adding new item, checks if that item already exists in the library to avoid adding a duplicated item.
         match item {
             Item::Track(track) => {
                 let contains = self
                     .current_user_saved_tracks_contains([track.id.as_ref()])
                     .await?;
-                if !contains[0] {
-                    self.current_user_saved_tracks_add([track.id.as_ref()])
-                        .await?;
-                    // update the in-memory `user_data`
-                    state
-                        .data
-                        .write()
-                        .user_data
-                        .saved_tracks
-                        .insert(track.id.uri(), track);
+                if contains[0] {
+                    return Ok(false);
                 }
+                self.current_user_saved_tracks_add([track.id.as_ref()])
+                    .await?;
+                // update the in-memory `user_data`
+                state
+                    .data
+                    .write()
+                    .user_data
+                    .saved_tracks
+                    .insert(track.id.uri(), track);
             }
             Item::Album(album) => {
                 let contains = self
                     .current_user_saved_albums_contains([album.id.as_ref()])
                     .await?;
-                if !contains[0] {
-                    self.current_user_saved_albums_add([album.id.as_ref()])
-                        .await?;
-                    // update the in-memory `user_data`
-                    state.data.write().user_data.saved_albums.insert(0, album);
+                if contains[0] {
+                    return Ok(false);
                 }
+                self.current_user_saved_albums_add([album.id.as_ref()])
+                    .await?;
+                // update the in-memory `user_data`
+                state.data.write().user_data.saved_albums.insert(0, album);
             }
             Item::Artist(artist) => {
                 let follows = self.user_artist_check_follow([artist.id.as_ref()]).await?;
-                if !follows[0] {
-                    self.user_follow_artists([artist.id.as_ref()]).await?;
-                    // update the in-memory `user_data`
-                    state
-                        .data
-                        .write()
-                        .user_data
-                        .followed_artists
-                        .insert(0, artist);
+                if follows[0] {
+                    return Ok(false);
                 }
+                self.user_follow_artists([artist.id.as_ref()]).await?;
+                // update the in-memory `user_data`
+                state
+                    .data
+                    .write()
+                    .user_data
+                    .followed_artists
+                    .insert(0, artist);
             }
             Item::Playlist(playlist) => {
                 let user_id = state
@@ -1078,19 +1624,30 @@ impl Client {
                     .as_ref()
                     .map(|u| u.id.clone());
 
-                if let Some(user_id) = user_id {
-                    let follows = self
-                        .playlist_check_follow(playlist.id.as_ref(), &[user_id])
-                        .await?;
-                    if !follows[0] {
-                        self.playlist_follow(playlist.id.as_ref(), None).await?;
-                        // update the in-memory `user_data`
-                        state.data.write().user_data.playlists.insert(0, playlist);
-                    }
+                let Some(user_id) = user_id else {
+                    return Ok(false);
+                };
+                let follows = self
+                    .playlist_check_follow(playlist.id.as_ref(), &[user_id])
+                    .await?;
+                if follows[0] {
+                    return Ok(false);
+                }
+                self.playlist_follow(playlist.id.as_ref(), None).await?;
+                // update the in-memory `user_data`
+                state.data.write().user_data.playlists.insert(0, playlist);
+            }
+            Item::Show(show) => {
+                let contains = self.check_users_saved_shows([show.id.as_ref()]).await?;
+                if contains[0] {
+                    return Ok(false);
                 }
+                self.save_shows([show.id.as_ref()]).await?;
+                // update the in-memory `user_data`
+                state.data.write().user_data.saved_shows.insert(0, show);
             }
         }
-        Ok(())
+        Ok(true)
     }
 
     // Delete a Spotify item from user's library
@@ -1128,7 +1685,381 @@ impl Client {
                     .retain(|p| p.id != id);
                 self.playlist_unfollow(id).await?;
             }
+            ItemId::Show(id) => {
+                state
+                    .data
+                    .write()
+                    .user_data
+                    .saved_shows
+                    .retain(|s| s.id != id);
+                self.remove_users_saved_shows([id], None).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply an edit to the user's playlists or library and record it into the edit journal
+    async fn apply_and_record_edit(&self, state: &SharedState, edit: Edit) -> Result<()> {
+        let desc = edit.desc(&state.data.read().user_data);
+        self.apply_and_record_edits(state, desc, vec![edit]).await
+    }
+
+    /// Apply edits to the user's playlists or library and record them into the edit journal
+    /// as a single change described by `desc`
+    pub async fn apply_and_record_edits(
+        &self,
+        state: &SharedState,
+        desc: String,
+        mut edits: Vec<Edit>,
+    ) -> Result<()> {
+        edits.retain(|edit| !edit.is_empty());
+        // on failure, the applied edits are still recorded, so they can be reverted
+        let (edits, result) = match self.apply_edits(state, edits).await {
+            Ok(edits) => (edits, Ok(())),
+            Err(err) => (err.inverse_edits, Err(err.err)),
+        };
+        if !edits.is_empty() {
+            let entry = JournalEntry {
+                desc,
+                timestamp: chrono::Utc::now(),
+                edits,
+            };
+            update_journal(state, |journal| journal.record(entry))?;
+        }
+        result
+    }
+
+    /// Revert the last change recorded in the edit journal, or re-apply the last reverted change if `redo` is true.
+    /// Return the change's description, `None` if there is no change to revert or re-apply.
+    pub async fn undo(&self, state: &SharedState, redo: bool) -> Result<Option<String>> {
+        let Some(entry) = state.data.write().journal.pop(redo) else {
+            return Ok(None);
+        };
+
+        match self.apply_edits(state, entry.edits.clone()).await {
+            Ok(edits) => {
+                let desc = entry.desc.clone();
+                let entry = JournalEntry {
+                    desc: entry.desc,
+                    timestamp: chrono::Utc::now(),
+                    edits,
+                };
+                update_journal(state, |journal| journal.push(entry, !redo))?;
+                Ok(Some(desc))
+            }
+            Err(err) => {
+                // put back the unapplied edits, so they can be applied again later,
+                // and record the applied edits' inverses as a separate change
+                let result = update_journal(state, |journal| {
+                    if !err.unapplied_edits.is_empty() {
+                        let entry = JournalEntry {
+                            desc: entry.desc.clone(),
+                            timestamp: entry.timestamp,
+                            edits: err.unapplied_edits,
+                        };
+                        journal.push(entry, redo);
+                    }
+                    if !err.inverse_edits.is_empty() {
+                        let entry = JournalEntry {
+                            desc: format!("{} (partially applied)", entry.desc),
+                            timestamp: chrono::Utc::now(),
+                            edits: err.inverse_edits,
+                        };
+                        journal.push(entry, !redo);
+                    }
+                });
+                if let Err(store_err) = result {
+                    tracing::error!("{store_err:#}");
+                }
+                Err(err.err)
+            }
+        }
+    }
+
+    /// Apply edits in order, return the edits reverting them
+    async fn apply_edits(
+        &self,
+        state: &SharedState,
+        edits: Vec<Edit>,
+    ) -> std::result::Result<Vec<Edit>, ApplyEditsError> {
+        let mut inverse_edits = Vec::new();
+        let mut edits = edits.into_iter();
+        while let Some(edit) = edits.next() {
+            match self.apply_edit(state, edit.clone()).await {
+                Ok(mut edit_inverses) => {
+                    // edits are reverted in the reverse order of being applied
+                    edit_inverses.append(&mut inverse_edits);
+                    inverse_edits = edit_inverses;
+                }
+                Err(err) => {
+                    return Err(ApplyEditsError {
+                        err,
+                        inverse_edits,
+                        unapplied_edits: std::iter::once(edit).chain(edits).collect(),
+                    })
+                }
+            }
+        }
+        Ok(inverse_edits)
+    }
+
+    /// Apply an edit to the user's playlists or library, return the edits reverting it
+    async fn apply_edit(&self, state: &SharedState, edit: Edit) -> Result<Vec<Edit>> {
+        let mut inverse_edits = match edit {
+            Edit::AddPlaylistTracks {
+                playlist_id,
+                track_ids,
+            } => {
+                let item_ids = self.playlist_item_ids(playlist_id.as_ref()).await?;
+                // existing occurrences of the tracks are removed before appending the tracks
+                let occurrences = playlist_track_occurrences(&item_ids, &track_ids);
+                let n_remaining_items = item_ids.len() - occurrences.len();
+                let snapshot_id = self
+                    .add_tracks_to_playlist(state, playlist_id.clone(), track_ids.clone())
+                    .await?;
+
+                let appended_items = track_ids
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, track_id)| PlaylistItem {
+                        track_id,
+                        position: n_remaining_items + i,
+                    })
+                    .collect();
+                vec![
+                    Edit::RemovePlaylistItems {
+                        playlist_id: playlist_id.clone(),
+                        items: appended_items,
+                        snapshot_id,
+                    },
+                    Edit::InsertPlaylistItems {
+                        playlist_id,
+                        items: occurrences,
+                    },
+                ]
+            }
+            Edit::DeletePlaylistTracks {
+                playlist_id,
+                track_ids,
+            } => {
+                let item_ids = self.playlist_item_ids(playlist_id.as_ref()).await?;
+                let occurrences = playlist_track_occurrences(&item_ids, &track_ids);
+                self.delete_tracks_from_playlist(state, playlist_id.clone(), track_ids)
+                    .await?;
+                vec![Edit::InsertPlaylistItems {
+                    playlist_id,
+                    items: occurrences,
+                }]
+            }
+            Edit::InsertPlaylistItems { playlist_id, items } => {
+                let snapshot_id = self
+                    .insert_playlist_items(state, playlist_id.as_ref(), &items)
+                    .await?;
+                vec![Edit::RemovePlaylistItems {
+                    playlist_id,
+                    items,
+                    snapshot_id,
+                }]
+            }
+            Edit::RemovePlaylistItems {
+                playlist_id,
+                items,
+                snapshot_id,
+            } => {
+                self.remove_playlist_items(state, playlist_id.as_ref(), &items, snapshot_id)
+                    .await?;
+                vec![Edit::InsertPlaylistItems { playlist_id, items }]
+            }
+            Edit::ReorderPlaylistItem {
+                playlist_id,
+                insert_index,
+                range_start,
+                snapshot_id,
+            } => {
+                let snapshot_id = self
+                    .reorder_playlist_items(
+                        state,
+                        playlist_id.as_ref(),
+                        insert_index,
+                        range_start,
+                        snapshot_id.as_deref(),
+                    )
+                    .await?;
+                vec![Edit::ReorderPlaylistItem {
+                    playlist_id,
+                    insert_index: range_start,
+                    range_start: insert_index,
+                    snapshot_id: Some(snapshot_id),
+                }]
+            }
+            Edit::AddToLibrary(items) => {
+                let mut tracks = Vec::new();
+                let mut added_ids = Vec::new();
+                for item in items {
+                    match item {
+                        // tracks are added together in batches
+                        Item::Track(track) => tracks.push(track),
+                        item => {
+                            let id = item.id();
+                            if self.add_to_library(state, item).await? {
+                                added_ids.push(id);
+                            }
+                        }
+                    }
+                }
+                if !tracks.is_empty() {
+                    let liked_tracks = self.add_tracks_to_liked(state, tracks).await?;
+                    added_ids.extend(liked_tracks.into_iter().map(|t| ItemId::Track(t.id)));
+                }
+                vec![Edit::DeleteFromLibrary(added_ids)]
+            }
+            Edit::DeleteFromLibrary(ids) => {
+                // the items' data is required to add them back to the library,
+                // so it is fetched before deleting any item
+                let mut deleted_items = Vec::new();
+                for id in &ids {
+                    deleted_items.push(self.library_item(state, id).await?);
+                }
+
+                let mut track_ids = Vec::new();
+                for id in ids {
+                    match id {
+                        // tracks are deleted together in batches
+                        ItemId::Track(id) => track_ids.push(id),
+                        id => self.delete_from_library(state, id).await?,
+                    }
+                }
+                if !track_ids.is_empty() {
+                    self.delete_tracks_from_liked(state, track_ids).await?;
+                }
+                vec![Edit::AddToLibrary(deleted_items)]
+            }
+        };
+
+        inverse_edits.retain(|edit| !edit.is_empty());
+        Ok(inverse_edits)
+    }
+
+    /// Get the data of an item in the user's library, fetching it from Spotify if not available in the app's data
+    async fn library_item(&self, state: &SharedState, id: &ItemId) -> Result<Item> {
+        let item = state.data.read().user_data.library_item(id);
+        if let Some(item) = item {
+            return Ok(item);
+        }
+
+        let item = match id {
+            ItemId::Track(id) => Item::Track(self.track(id.as_ref()).await?),
+            ItemId::Album(id) => Item::Album(
+                self.album(id.as_ref(), Some(Market::FromToken))
+                    .await?
+                    .into(),
+            ),
+            ItemId::Artist(id) => Item::Artist(self.artist(id.as_ref()).await?.into()),
+            ItemId::Playlist(id) => Item::Playlist(
+                self.http_get::<FullPlaylist>(
+                    &format!("{SPOTIFY_API_ENDPOINT}/playlists/{}", id.id()),
+                    &market_query(),
+                )
+                .await?
+                .into(),
+            ),
+            ItemId::Show(id) => Item::Show(
+                self.get_a_show(id.as_ref(), Some(Market::FromToken))
+                    .await?
+                    .into(),
+            ),
+        };
+        Ok(item)
+    }
+
+    /// Get the track IDs of a playlist's items, `None` for items that are not tracks (e.g. episodes)
+    async fn playlist_item_ids(
+        &self,
+        playlist_id: PlaylistId<'_>,
+    ) -> Result<Vec<Option<TrackId<'static>>>> {
+        let (_, items) = self.playlist_with_items(playlist_id).await?;
+        Ok(items
+            .into_iter()
+            .map(|item| match item.track {
+                Some(rspotify_model::PlayableItem::Track(track)) => track.id,
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Insert tracks into a playlist at their positions, return the playlist's new snapshot ID
+    async fn insert_playlist_items(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'_>,
+        items: &[PlaylistItem],
+    ) -> Result<Option<String>> {
+        let progress = BulkProgressReporter::new(state, "Restoring playlist tracks", items.len());
+        let mut snapshot_id = None;
+        let mut n_done = 0;
+        // Items are inserted in the order of their positions, so each item ends up at its position.
+        // Items with consecutive positions are inserted together.
+        for group in items.chunk_by(|x, y| x.position + 1 == y.position) {
+            for batch in group.chunks(PLAYLIST_ITEMS_BATCH_SIZE) {
+                progress.report(n_done);
+                let result = self
+                    .playlist_add_items(
+                        playlist_id.as_ref(),
+                        batch
+                            .iter()
+                            .map(|item| PlayableId::Track(item.track_id.as_ref())),
+                        Some(batch[0].position as u32),
+                    )
+                    .await?;
+                snapshot_id = Some(result.snapshot_id);
+                n_done += batch.len();
+            }
+        }
+
+        // remove the cache of the playlist to force refetching new data
+        state.data.write().caches.context.remove(&playlist_id.uri());
+
+        Ok(snapshot_id)
+    }
+
+    /// Remove tracks at specific positions of a playlist's snapshot
+    async fn remove_playlist_items(
+        &self,
+        state: &SharedState,
+        playlist_id: PlaylistId<'_>,
+        items: &[PlaylistItem],
+        mut snapshot_id: Option<String>,
+    ) -> Result<()> {
+        let progress = BulkProgressReporter::new(state, "Removing playlist tracks", items.len());
+        // Items are removed in batches starting from the playlist's end,
+        // so positions of the remaining items are unchanged in the new snapshot.
+        for (i, batch) in items.rchunks(PLAYLIST_ITEMS_BATCH_SIZE).enumerate() {
+            progress.report(i * PLAYLIST_ITEMS_BATCH_SIZE);
+            let positions = batch
+                .iter()
+                .map(|item| [item.position as u32])
+                .collect::<Vec<_>>();
+            let item_positions = batch
+                .iter()
+                .zip(&positions)
+                .map(|(item, positions)| ItemPositions {
+                    id: PlayableId::Track(item.track_id.as_ref()),
+                    positions,
+                })
+                .collect::<Vec<_>>();
+            let result = self
+                .playlist_remove_specific_occurrences_of_items(
+                    playlist_id.as_ref(),
+                    item_positions,
+                    snapshot_id.as_deref(),
+                )
+                .await?;
+            snapshot_id = Some(result.snapshot_id);
         }
+
+        // remove the cache of the playlist to force refetching new data
+        state.data.write().caches.context.remove(&playlist_id.uri());
+
         Ok(())
     }
 
@@ -1147,6 +2078,33 @@ impl Client {
         let playlist_uri = playlist_id.uri();
         tracing::info!("Get playlist context: {}", playlist_uri);
 
+        let (playlist, items) = self.playlist_with_items(playlist_id).await?;
+        let tracks = items
+            .into_iter()
+            .filter_map(|item| match item.track {
+                Some(rspotify_model::PlayableItem::Track(track)) => {
+                    let mut track = Track::try_from_full_track(track)?;
+                    track.added_at = item
+                        .added_at
+                        .map(|t| t.timestamp() as u64)
+                        .unwrap_or_default();
+                    Some(track)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Context::Playlist {
+            playlist: playlist.into(),
+            tracks,
+        })
+    }
+
+    /// Get a playlist and its items
+    pub async fn playlist_with_items(
+        &self,
+        playlist_id: PlaylistId<'_>,
+    ) -> Result<(FullPlaylist, Vec<rspotify_model::PlaylistItem>)> {
         // TODO: this should use `rspotify::playlist` API instead of `internal_call`
         // See: https://github.com/ramsayleung/rspotify/issues/459
         // let playlist = self
@@ -1159,24 +2117,11 @@ impl Client {
             )
             .await?;
 
-        // get the playlist's tracks
+        // get the playlist's items
         let first_page = playlist.tracks.clone();
-        let tracks = self
-            .all_paging_items(first_page, &market_query())
-            .await?
-            .into_iter()
-            .filter_map(|item| match item.track {
-                Some(rspotify_model::PlayableItem::Track(track)) => {
-                    Track::try_from_full_track(track)
-                }
-                _ => None,
-            })
-            .collect::<Vec<_>>();
+        let items = self.all_paging_items(first_page, &market_query()).await?;
 
-        Ok(Context::Playlist {
-            playlist: playlist.into(),
-            tracks,
-        })
+        Ok((playlist, items))
     }
 
     /// Get an album context data
@@ -1253,6 +2198,28 @@ impl Client {
         })
     }
 
+    /// Get a show context data
+    pub async fn show_context(&self, show_id: ShowId<'_>) -> Result<Context> {
+        let show_uri = show_id.uri();
+        tracing::info!("Get show context: {}", show_uri);
+
+        let show = self.get_a_show(show_id, Some(Market::FromToken)).await?;
+        let first_page = show.episodes.clone();
+
+        // converts `rspotify_model::FullShow` into `state::Show`
+        let show: Show = show.into();
+
+        // get the show's episodes
+        let episodes = self
+            .all_paging_items(first_page, &market_query())
+            .await?
+            .into_iter()
+            .map(|e| Episode::from_simplified_episode(e, show.clone()))
+            .collect::<Vec<_>>();
+
+        Ok(Context::Show { show, episodes })
+    }
+
     /// Make a GET HTTP request to the Spotify server
     async fn http_get<T>(&self, url: &str, payload: &Query<'_>) -> Result<T>
     where
@@ -1349,21 +2316,28 @@ impl Client {
     ) -> Result<()> {
         let new_track = {
             // update the playback state
-            let playback = self.current_playback(None, None::<Vec<_>>).await?;
+            let playback = self
+                .current_playback(None, Some(&PLAYBACK_ADDITIONAL_TYPES))
+                .await?;
             let mut player = state.player.write();
 
-            let prev_track_name = player
-                .current_playing_track()
-                .map(|t| t.name.to_owned())
-                .unwrap_or_default();
+            // the name of the playing item, which is either a track or an episode
+            let item_name = |player: &PlayerState| match player.playback.as_ref() {
+                Some(rspotify_model::CurrentPlaybackContext {
+                    item: Some(item), ..
+                }) => match item {
+                    rspotify_model::PlayableItem::Track(t) => t.name.to_owned(),
+                    rspotify_model::PlayableItem::Episode(e) => e.name.to_owned(),
+                },
+                _ => String::new(),
+            };
+
+            let prev_track_name = item_name(&player);
 
             player.playback = playback;
             player.playback_last_updated_time = Some(std::time::Instant::now());
 
-            let curr_track_name = player
-                .current_playing_track()
-                .map(|t| t.name.to_owned())
-                .unwrap_or_default();
+            let curr_track_name = item_name(&player);
 
             let new_track = prev_track_name != curr_track_name && !curr_track_name.is_empty();
             // check if we need to update the buffered playback
@@ -1392,11 +2366,13 @@ impl Client {
 
             new_track
         };
+        state.render.notify();
 
         if !new_track {
             return Ok(());
         }
         self.handle_new_track_event(state).await?;
+        state.render.notify();
 
         Ok(())
     }
@@ -1405,22 +2381,36 @@ impl Client {
     async fn handle_new_track_event(&self, state: &SharedState) -> Result<()> {
         let configs = config::get_config();
 
-        let track = match state.player.read().current_playing_track() {
-            None => return Ok(()),
-            Some(track) => track.clone(),
+        let (track, episode) = {
+            let player = state.player.read();
+            (
+                player.current_playing_track().cloned(),
+                player.current_playing_episode().cloned(),
+            )
         };
 
-        let url = match crate::utils::get_track_album_image_url(&track) {
+        // an episode's cover image is its own image or its show's image
+        let (url, name) = match (&track, &episode) {
+            (Some(track), _) => (
+                crate::utils::get_track_album_image_url(track),
+                format!(
+                    "{}-{}",
+                    track.album.name,
+                    crate::utils::map_join(&track.album.artists, |a| &a.name, ", ")
+                ),
+            ),
+            (None, Some(episode)) => (
+                crate::utils::get_episode_image_url(episode),
+                format!("{}-{}", episode.show.name, episode.show.publisher),
+            ),
+            (None, None) => return Ok(()),
+        };
+        let url = match url {
             Some(url) => url,
             None => return Ok(()),
         };
 
-        let path = (format!(
-            "{}-{}-cover.jpg",
-            track.album.name,
-            crate::utils::map_join(&track.album.artists, |a| &a.name, ", ")
-        ))
-        .replace('/', ""); // remove invalid characters from the file's name
+        let path = format!("{name}-cover.jpg").replace('/', ""); // remove invalid characters from the file's name
         let path = configs.cache_folder.join("image").join(path);
 
         if configs.app_config.enable_cover_image_cache {
@@ -1445,7 +2435,15 @@ impl Client {
         if configs.app_config.enable_notify
             && (!configs.app_config.notify_streaming_only || self.stream_conn.lock().is_some())
         {
-            Self::notify_new_track(track, &path)?;
+            // computed before locking the player state, which is also locked to compute the playback's extra data
+            let extras = crate::template::PlaybackExtras::new(state);
+            let playback = state.player.read().current_playback();
+            if let Some(info) = playback
+                .as_ref()
+                .and_then(|p| crate::template::PlaybackInfo::from_playback(p, extras))
+            {
+                Self::notify_new_track(&info, &path)?;
+            }
         }
 
         Ok(())
@@ -1460,7 +2458,7 @@ impl Client {
         public: bool,
         collab: bool,
         desc: &str,
-    ) -> Result<()> {
+    ) -> Result<PlaylistId<'static>> {
         let playlist: Playlist = self
             .user_playlist_create(
                 user_id,
@@ -1476,59 +2474,23 @@ impl Client {
             playlist.name,
             playlist.id
         );
+        let playlist_id = playlist.id.clone();
         state.data.write().user_data.playlists.insert(0, playlist);
-        Ok(())
+        Ok(playlist_id)
     }
 
     #[cfg(feature = "notify")]
     /// Create a notification for a new track
     fn notify_new_track(
-        track: rspotify_model::FullTrack,
+        info: &crate::template::PlaybackInfo,
         cover_img_path: &std::path::Path,
     ) -> Result<()> {
         let mut n = notify_rust::Notification::new();
 
-        let re = regex::Regex::new(r"\{.*?\}").unwrap();
-        // Generate a text described a track from a format string.
-        // For example, a format string "{track} - {artists}" will generate
-        // a text consisting of the track's name followed by a dash then artists' names.
-        let get_text_from_format_str = |format_str: &str| {
-            let mut text = String::new();
-
-            let mut ptr = 0;
-            for m in re.find_iter(format_str) {
-                let s = m.start();
-                let e = m.end();
-
-                if ptr < s {
-                    text += &format_str[ptr..s];
-                }
-                ptr = e;
-                match m.as_str() {
-                    "{track}" => text += &track.name,
-                    "{artists}" => {
-                        text += &crate::utils::map_join(&track.artists, |a| &a.name, ", ")
-                    }
-                    "{album}" => text += &track.album.name,
-                    _ => continue,
-                }
-            }
-            if ptr < format_str.len() {
-                text += &format_str[ptr..];
-            }
-
-            text
-        };
-
         let configs = config::get_config();
-
         n.appname("spotify_player")
-            .summary(&get_text_from_format_str(
-                &configs.app_config.notify_format.summary,
-            ))
-            .body(&get_text_from_format_str(
-                &configs.app_config.notify_format.body,
-            ));
+            .summary(&configs.templates.notify_summary.render_string(info))
+            .body(&configs.templates.notify_body.render_string(info));
         if cover_img_path.exists() {
             n.icon(cover_img_path.to_str().context("valid cover_img_path")?);
         }
@@ -1587,3 +2549,81 @@ impl Client {
         albums
     }
 }
+
+/// Update the local library index with tracks of a context if the context is a part of the user's library
+fn update_library_index(state: &SharedState, uri: &str, tracks: &[Track]) -> Result<()> {
+    let mut data = state.data.write();
+    if data.user_data.library_source_uris().contains(uri) && data.library.update_source(uri, tracks)
+    {
+        data.library
+            .store()
+            .context("store the library index into the cache folder")?;
+    }
+    Ok(())
+}
+
+/// Get the positions of occurrences of `track_ids` in a playlist whose items' track IDs are `item_ids`
+fn playlist_track_occurrences(
+    item_ids: &[Option<TrackId<'static>>],
+    track_ids: &[TrackId<'static>],
+) -> Vec<PlaylistItem> {
+    item_ids
+        .iter()
+        .enumerate()
+        .filter_map(|(position, id)| match id {
+            Some(id) if track_ids.contains(id) => Some(PlaylistItem {
+                track_id: id.clone(),
+                position,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Update the edit journal and store it into the cache folder
+fn update_journal(state: &SharedState, f: impl FnOnce(&mut Journal)) -> Result<()> {
+    let mut data = state.data.write();
+    f(&mut data.journal);
+    data.journal
+        .store()
+        .context("store the edit journal into the cache folder")
+}
+
+/// An error applying a list of edits, some of which may have been applied before the error
+struct ApplyEditsError {
+    err: anyhow::Error,
+    /// the edits reverting the applied edits
+    inverse_edits: Vec<Edit>,
+    /// the edits that are not applied, starting with the failed edit
+    unapplied_edits: Vec<Edit>,
+}
+
+/// A reporter of a bulk action's progress, which is displayed by the UI until the reporter is dropped
+struct BulkProgressReporter<'a> {
+    state: &'a SharedState,
+    desc: &'static str,
+    total: usize,
+}
+
+impl<'a> BulkProgressReporter<'a> {
+    fn new(state: &'a SharedState, desc: &'static str, total: usize) -> Self {
+        Self { state, desc, total }
+    }
+
+    /// Report the number of processed items
+    fn report(&self, n_done: usize) {
+        self.state.ui.lock().bulk_progress = Some(BulkProgress {
+            desc: self.desc,
+            n_done,
+            total: self.total,
+        });
+        self.state.render.notify();
+    }
+}
+
+impl Drop for BulkProgressReporter<'_> {
+    fn drop(&mut self) {
+        self.state.ui.lock().bulk_progress = None;
+        self.state.render.notify();
+    }
+}
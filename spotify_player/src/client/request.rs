@@ -11,6 +11,12 @@ pub enum PlayerRequest {
     SeekTrack(chrono::Duration),
     Repeat,
     Shuffle,
+    /// only sent by the MPRIS server, other clients toggle the state with `Repeat`
+    #[cfg(all(target_os = "linux", feature = "media-control"))]
+    SetRepeat(rspotify_model::RepeatState),
+    /// only sent by the MPRIS server, other clients toggle the state with `Shuffle`
+    #[cfg(all(target_os = "linux", feature = "media-control"))]
+    SetShuffle(bool),
     Volume(u8),
     ToggleMute,
     TransferPlayback(String, bool),
@@ -28,6 +34,7 @@ pub enum ClientRequest {
     GetUserSavedAlbums,
     GetUserFollowedArtists,
     GetUserSavedTracks,
+    GetUserSavedShows,
     GetUserTopTracks,
     GetUserRecentlyPlayedTracks,
     GetContext(ContextId),
@@ -39,13 +46,25 @@ pub enum ClientRequest {
     Search(String),
     AddTrackToQueue(TrackId<'static>),
     AddAlbumToQueue(AlbumId<'static>),
+    AddEpisodeToQueue(EpisodeId<'static>),
+    /// mark an episode as played or unplayed
+    MarkEpisodePlayed(EpisodeId<'static>, bool),
     AddTrackToPlaylist(PlaylistId<'static>, TrackId<'static>),
     DeleteTrackFromPlaylist(PlaylistId<'static>, TrackId<'static>),
+    AddTracksToQueue(Vec<TrackId<'static>>),
+    AddTracksToPlaylist(PlaylistId<'static>, Vec<TrackId<'static>>),
+    DeleteTracksFromPlaylist(PlaylistId<'static>, Vec<TrackId<'static>>),
+    AddTracksToLiked(Vec<Track>),
+    DeleteTracksFromLiked(Vec<TrackId<'static>>),
+    /// add tracks to the application's queue, either before (`play_next`) or after the queued tracks
+    AddTracksToLocalQueue {
+        tracks: Vec<Track>,
+        play_next: bool,
+    },
     ReorderPlaylistItems {
         playlist_id: PlaylistId<'static>,
         insert_index: usize,
         range_start: usize,
-        range_length: Option<usize>,
         snapshot_id: Option<String>,
     },
     AddToLibrary(Item),
@@ -56,13 +75,25 @@ pub enum ClientRequest {
     GetLyric {
         track: String,
         artists: String,
+        duration: std::time::Duration,
     },
     #[cfg(feature = "streaming")]
     RestartIntegratedClient,
+    /// switch the integrated client's equalizer preset
+    #[cfg(feature = "streaming")]
+    SetEqPreset(String),
+    /// switch to another profile, `None` for the default profile
+    SwitchProfile(Option<String>),
     CreatePlaylist {
         playlist_name: String,
         public: bool,
         collab: bool,
         desc: String,
+        /// tracks added to the playlist after it's created
+        track_ids: Vec<TrackId<'static>>,
     },
+    /// revert the last change recorded in the edit journal
+    Undo,
+    /// re-apply the last change reverted by `Undo`
+    Redo,
 }
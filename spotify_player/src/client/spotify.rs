@@ -9,7 +9,7 @@ use rspotify::{
 };
 use std::{fmt, sync::Arc};
 
-use crate::token;
+use crate::{state::RwLock, token};
 
 #[derive(Clone, Default)]
 /// A Spotify client to interact with Spotify API server
@@ -18,7 +18,7 @@ pub struct Spotify {
     oauth: OAuth,
     config: Config,
     token: Arc<Mutex<Option<Token>>>,
-    client_id: String,
+    client_id: Arc<RwLock<String>>,
     http: HttpClient,
     // session should always be non-empty, but `Option` is used to implement `Default`,
     // which is required to implement `rspotify::BaseClient` trait
@@ -50,10 +50,15 @@ impl Spotify {
             token: Arc::new(Mutex::new(None)),
             http: HttpClient::default(),
             session: Arc::new(tokio::sync::Mutex::new(Some(session))),
-            client_id,
+            client_id: Arc::new(RwLock::new(client_id)),
         }
     }
 
+    /// sets the Spotify client ID used to retrieve access tokens
+    pub fn set_client_id(&self, client_id: String) {
+        *self.client_id.write() = client_id;
+    }
+
     pub async fn session(&self) -> Session {
         self.session
             .lock()
@@ -113,7 +118,8 @@ impl BaseClient for Spotify {
             return Ok(old_token);
         }
 
-        match token::get_token(&session, &self.client_id).await {
+        let client_id = self.client_id.read().clone();
+        match token::get_token(&session, &client_id).await {
             Ok(token) => Ok(Some(token)),
             Err(err) => {
                 tracing::error!("Failed to get a new token: {err:#}");
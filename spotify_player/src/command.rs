@@ -1,7 +1,18 @@
-use crate::state::{Album, Artist, DataReadGuard, Playlist, Track};
+use crate::state::{Album, Artist, DataReadGuard, Episode, Playlist, Show, Track};
 use serde::Deserialize;
 
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(
+    Copy,
+    Clone,
+    Debug,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    strum::EnumIter,
+    strum::IntoStaticStr,
+)]
 /// Application's command
 pub enum Command {
     None,
@@ -37,13 +48,20 @@ pub enum Command {
 
     #[cfg(feature = "streaming")]
     RestartIntegratedClient,
+    #[cfg(feature = "streaming")]
+    SwitchEqPreset,
+    #[cfg(feature = "streaming")]
+    ToggleVisualizer,
 
     FocusNextWindow,
     FocusPreviousWindow,
 
     SwitchTheme,
+    ReloadConfig,
     SwitchDevice,
+    SwitchProfile,
     Search,
+    OpenCommandLine,
     Queue,
 
     ShowActionsOnSelectedItem,
@@ -60,9 +78,14 @@ pub enum Command {
     LikedTrackPage,
     #[cfg(feature = "lyric-finder")]
     LyricPage,
+    #[cfg(feature = "lyric-finder")]
+    IncreaseLyricOffset,
+    #[cfg(feature = "lyric-finder")]
+    DecreaseLyricOffset,
     LibraryPage,
     SearchPage,
     BrowsePage,
+    HistoryPage,
     PreviousPage,
     OpenSpotifyLinkFromClipboard,
 
@@ -76,17 +99,34 @@ pub enum Command {
     MovePlaylistItemUp,
     MovePlaylistItemDown,
 
+    DeleteQueueItem,
+    ClearQueue,
+    SaveQueueAsPlaylist,
+
+    ToggleTrackSelection,
+    SelectTrackRange,
+    SelectAllTracks,
+    ClearTrackSelection,
+
     CreatePlaylist,
+
+    Undo,
+    Redo,
+
+    CycleSleepTimer,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize)]
+#[derive(Clone, Copy, Debug, Deserialize, strum::EnumIter, strum::IntoStaticStr)]
 pub enum Action {
     GoToArtist,
     GoToAlbum,
     GoToRadio,
+    GoToShow,
     AddToLibrary,
     AddToPlaylist,
     AddToQueue,
+    PlayNext,
+    PlayLater,
     AddToLiked,
     DeleteFromLiked,
     DeleteFromLibrary,
@@ -97,6 +137,8 @@ pub enum Action {
     CopyLink,
     Follow,
     Unfollow,
+    MarkAsPlayed,
+    MarkAsUnplayed,
 }
 
 #[derive(Debug)]
@@ -105,6 +147,8 @@ pub enum ActionContext {
     Album(Album),
     Artist(Artist),
     Playlist(Playlist),
+    Show(Show),
+    Episode(Episode),
 }
 
 pub enum CommandOrAction {
@@ -136,6 +180,18 @@ impl From<Playlist> for ActionContext {
     }
 }
 
+impl From<Show> for ActionContext {
+    fn from(v: Show) -> Self {
+        Self::Show(v)
+    }
+}
+
+impl From<Episode> for ActionContext {
+    fn from(v: Episode) -> Self {
+        Self::Episode(v)
+    }
+}
+
 impl ActionContext {
     pub fn get_available_actions(&self, data: &DataReadGuard) -> Vec<Action> {
         match self {
@@ -143,10 +199,28 @@ impl ActionContext {
             Self::Album(album) => construct_album_actions(album, data),
             Self::Artist(artist) => construct_artist_actions(artist, data),
             Self::Playlist(playlist) => construct_playlist_actions(playlist, data),
+            Self::Show(show) => construct_show_actions(show, data),
+            Self::Episode(episode) => construct_episode_actions(episode, data),
         }
     }
 }
 
+/// constructs a list of actions on multiple selected tracks
+pub fn construct_bulk_track_actions(is_modifiable_playlist: bool) -> Vec<Action> {
+    let mut actions = vec![
+        Action::AddToPlaylist,
+        Action::AddToQueue,
+        Action::PlayNext,
+        Action::PlayLater,
+        Action::AddToLiked,
+        Action::DeleteFromLiked,
+    ];
+    if is_modifiable_playlist {
+        actions.push(Action::DeleteFromPlaylist);
+    }
+    actions
+}
+
 /// constructs a list of actions on a track
 pub fn construct_track_actions(track: &Track, data: &DataReadGuard) -> Vec<Action> {
     let mut actions = vec![
@@ -158,6 +232,8 @@ pub fn construct_track_actions(track: &Track, data: &DataReadGuard) -> Vec<Actio
         Action::CopyLink,
         Action::AddToPlaylist,
         Action::AddToQueue,
+        Action::PlayNext,
+        Action::PlayLater,
     ];
 
     if data.user_data.is_liked_track(track) {
@@ -215,6 +291,30 @@ pub fn construct_playlist_actions(playlist: &Playlist, data: &DataReadGuard) ->
     actions
 }
 
+/// constructs a list of actions on a show
+pub fn construct_show_actions(show: &Show, data: &DataReadGuard) -> Vec<Action> {
+    let mut actions = vec![Action::CopyLink];
+
+    if data.user_data.saved_shows.iter().any(|s| s.id == show.id) {
+        actions.push(Action::DeleteFromLibrary);
+    } else {
+        actions.push(Action::AddToLibrary);
+    }
+    actions
+}
+
+/// constructs a list of actions on an episode
+pub fn construct_episode_actions(episode: &Episode, data: &DataReadGuard) -> Vec<Action> {
+    let mut actions = vec![Action::GoToShow, Action::CopyLink, Action::AddToQueue];
+
+    if data.user_data.is_played_episode(episode) {
+        actions.push(Action::MarkAsUnplayed);
+    } else {
+        actions.push(Action::MarkAsPlayed);
+    }
+    actions
+}
+
 impl Command {
     pub fn desc(&self) -> &'static str {
         match self {
@@ -235,6 +335,12 @@ impl Command {
             Self::ClosePopup => "close a popup",
             #[cfg(feature = "streaming")]
             Self::RestartIntegratedClient => "restart the integrated client",
+            #[cfg(feature = "streaming")]
+            Self::SwitchEqPreset => {
+                "open a popup for switching the integrated client's equalizer preset"
+            }
+            #[cfg(feature = "streaming")]
+            Self::ToggleVisualizer => "toggle the integrated client's audio visualizer",
             Self::SelectNextOrScrollDown => "select the next item in a list/table or scroll down",
             Self::SelectPreviousOrScrollUp => {
                 "select the previous item in a list/table or scroll up"
@@ -260,7 +366,10 @@ impl Command {
             Self::FocusNextWindow => "focus the next focusable window (if any)",
             Self::FocusPreviousWindow => "focus the previous focusable window (if any)",
             Self::SwitchTheme => "open a popup for switching theme",
+            Self::ReloadConfig => "reload the application's configurations",
             Self::SwitchDevice => "open a popup for switching device",
+            Self::SwitchProfile => "open a popup for switching profile",
+            Self::OpenCommandLine => "open a command line for running commands (e.g. `:vol +10`)",
             Self::Search => "open a popup for searching in the current page",
             Self::BrowseUserPlaylists => "open a popup for browsing user's playlists",
             Self::BrowseUserFollowedArtists => "open a popup for browsing user's followed artists",
@@ -271,9 +380,16 @@ impl Command {
             Self::LikedTrackPage => "go to the user liked track page",
             #[cfg(feature = "lyric-finder")]
             Self::LyricPage => "go to the lyric page of the current track",
+            #[cfg(feature = "lyric-finder")]
+            Self::IncreaseLyricOffset => "show synced lyric lines 0.25s earlier",
+            #[cfg(feature = "lyric-finder")]
+            Self::DecreaseLyricOffset => "show synced lyric lines 0.25s later",
             Self::LibraryPage => "go to the user library page",
             Self::SearchPage => "go to the search page",
             Self::BrowsePage => "go to the browse page",
+            Self::HistoryPage => {
+                "go to the play history page (cycle the statistics period if already opened)"
+            }
             Self::Queue => "go to the queue page",
             Self::OpenCommandHelp => "go to the command help page",
             Self::PreviousPage => "go to the previous page",
@@ -284,9 +400,23 @@ impl Command {
             Self::SortTrackByDuration => "sort the track table (if any) by track's duration",
             Self::SortTrackByAddedDate => "sort the track table (if any) by track's added date",
             Self::ReverseTrackOrder => "reverse the order of the track table (if any)",
-            Self::MovePlaylistItemUp => "move playlist item up one position",
-            Self::MovePlaylistItemDown => "move playlist item down one position",
+            Self::MovePlaylistItemUp => "move playlist/queue item up one position",
+            Self::MovePlaylistItemDown => "move playlist/queue item down one position",
+            Self::DeleteQueueItem => "delete the selected item from the application's queue",
+            Self::ClearQueue => "clear the application's queue",
+            Self::SaveQueueAsPlaylist => "save the current queue as a new playlist",
+            Self::ToggleTrackSelection => "toggle the selection of the selected track",
+            Self::SelectTrackRange => {
+                "select tracks between the last toggled track and the selected track"
+            }
+            Self::SelectAllTracks => "select all tracks matching the current search (if any)",
+            Self::ClearTrackSelection => "clear the track selection",
             Self::CreatePlaylist => "create a new playlist",
+            Self::Undo => "undo the last change to playlists or library",
+            Self::Redo => "redo the last undone change to playlists or library",
+            Self::CycleSleepTimer => {
+                "cycle the sleep timer (15/30/45/60 minutes, end of track, end of context, off)"
+            }
         }
     }
 }
@@ -122,6 +122,16 @@ impl Default for KeymapConfig {
                     key_sequence: "R".into(),
                     command: Command::RestartIntegratedClient,
                 },
+                #[cfg(feature = "streaming")]
+                Keymap {
+                    key_sequence: "E".into(),
+                    command: Command::SwitchEqPreset,
+                },
+                #[cfg(feature = "streaming")]
+                Keymap {
+                    key_sequence: "M-v".into(),
+                    command: Command::ToggleVisualizer,
+                },
                 Keymap {
                     key_sequence: "tab".into(),
                     command: Command::FocusNextWindow,
@@ -134,10 +144,22 @@ impl Default for KeymapConfig {
                     key_sequence: "T".into(),
                     command: Command::SwitchTheme,
                 },
+                Keymap {
+                    key_sequence: "M-c".into(),
+                    command: Command::ReloadConfig,
+                },
                 Keymap {
                     key_sequence: "D".into(),
                     command: Command::SwitchDevice,
                 },
+                Keymap {
+                    key_sequence: "P".into(),
+                    command: Command::SwitchProfile,
+                },
+                Keymap {
+                    key_sequence: ":".into(),
+                    command: Command::OpenCommandLine,
+                },
                 Keymap {
                     key_sequence: "u p".into(),
                     command: Command::BrowseUserPlaylists,
@@ -176,6 +198,16 @@ impl Default for KeymapConfig {
                     key_sequence: "l".into(),
                     command: Command::LyricPage,
                 },
+                #[cfg(feature = "lyric-finder")]
+                Keymap {
+                    key_sequence: "]".into(),
+                    command: Command::IncreaseLyricOffset,
+                },
+                #[cfg(feature = "lyric-finder")]
+                Keymap {
+                    key_sequence: "[".into(),
+                    command: Command::DecreaseLyricOffset,
+                },
                 Keymap {
                     key_sequence: "g l".into(),
                     command: Command::LibraryPage,
@@ -188,6 +220,10 @@ impl Default for KeymapConfig {
                     key_sequence: "g b".into(),
                     command: Command::BrowsePage,
                 },
+                Keymap {
+                    key_sequence: "g h".into(),
+                    command: Command::HistoryPage,
+                },
                 Keymap {
                     key_sequence: "backspace".into(),
                     command: Command::PreviousPage,
@@ -308,6 +344,34 @@ impl Default for KeymapConfig {
                     key_sequence: "C-j".into(),
                     command: Command::MovePlaylistItemDown,
                 },
+                Keymap {
+                    key_sequence: "d".into(),
+                    command: Command::DeleteQueueItem,
+                },
+                Keymap {
+                    key_sequence: "X".into(),
+                    command: Command::ClearQueue,
+                },
+                Keymap {
+                    key_sequence: "S".into(),
+                    command: Command::SaveQueueAsPlaylist,
+                },
+                Keymap {
+                    key_sequence: "v".into(),
+                    command: Command::ToggleTrackSelection,
+                },
+                Keymap {
+                    key_sequence: "V".into(),
+                    command: Command::SelectTrackRange,
+                },
+                Keymap {
+                    key_sequence: "C-a".into(),
+                    command: Command::SelectAllTracks,
+                },
+                Keymap {
+                    key_sequence: "C-v".into(),
+                    command: Command::ClearTrackSelection,
+                },
                 Keymap {
                     key_sequence: "N".into(),
                     command: Command::CreatePlaylist,
@@ -316,6 +380,18 @@ impl Default for KeymapConfig {
                     key_sequence: "g c".into(),
                     command: Command::JumpToCurrentTrackInContext,
                 },
+                Keymap {
+                    key_sequence: "U".into(),
+                    command: Command::Undo,
+                },
+                Keymap {
+                    key_sequence: "C-y".into(),
+                    command: Command::Redo,
+                },
+                Keymap {
+                    key_sequence: "C-t".into(),
+                    command: Command::CycleSleepTimer,
+                },
             ],
         }
     }
@@ -1,4 +1,5 @@
 mod keymap;
+mod smart_playlist;
 mod theme;
 
 const DEFAULT_CONFIG_FOLDER: &str = ".config/spotify-player";
@@ -6,43 +7,153 @@ const DEFAULT_CACHE_FOLDER: &str = ".cache/spotify-player";
 const APP_CONFIG_FILE: &str = "app.toml";
 const THEME_CONFIG_FILE: &str = "theme.toml";
 const KEYMAP_CONFIG_FILE: &str = "keymap.toml";
+const SMART_PLAYLISTS_CONFIG_FILE: &str = "smart_playlists.toml";
+/// name of the folder storing profiles' caches, relative to the application's cache folder
+const PROFILES_CACHE_FOLDER: &str = "profiles";
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use config_parser2::*;
 use librespot_core::config::SessionConfig;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use std::{
     path::{Path, PathBuf},
-    sync::OnceLock,
+    sync::Arc,
 };
 
+use crate::template::Template;
 use keymap::*;
+use smart_playlist::*;
 use theme::*;
 
+pub use smart_playlist::SmartPlaylist;
 pub use theme::Theme;
 
-static CONFIGS: OnceLock<Configs> = OnceLock::new();
+/// the application's configurations, which are replaced upon a reload
+static CONFIGS: parking_lot::RwLock<Option<Arc<Configs>>> = parking_lot::const_rwlock(None);
+/// name of the active profile, `None` for the default profile
+static ACTIVE_PROFILE: parking_lot::RwLock<Option<String>> = parking_lot::const_rwlock(None);
 
 #[derive(Debug)]
 pub struct Configs {
     pub app_config: AppConfig,
     pub keymap_config: KeymapConfig,
     pub theme_config: ThemeConfig,
+    pub smart_playlists_config: SmartPlaylistsConfig,
+    pub templates: Templates,
+    pub config_folder: std::path::PathBuf,
     pub cache_folder: std::path::PathBuf,
+    /// the theme specified by the `--theme` CLI argument, which overrides the `theme` config option
+    pub theme_override: Option<String>,
+}
+
+/// Templates parsed from the application's format options
+#[derive(Debug)]
+pub struct Templates {
+    pub playback: Template,
+    #[cfg(feature = "notify")]
+    pub notify_summary: Template,
+    #[cfg(feature = "notify")]
+    pub notify_body: Template,
 }
 
 impl Configs {
     pub fn new(config_folder: &std::path::Path, cache_folder: &std::path::Path) -> Result<Self> {
+        let app_config = AppConfig::new(config_folder)?;
         Ok(Self {
-            app_config: AppConfig::new(config_folder)?,
+            templates: Templates::new(&app_config)?,
+            app_config,
             keymap_config: KeymapConfig::new(config_folder)?,
             theme_config: ThemeConfig::new(config_folder)?,
+            smart_playlists_config: SmartPlaylistsConfig::new(config_folder)?,
+            config_folder: config_folder.to_path_buf(),
             cache_folder: cache_folder.to_path_buf(),
+            theme_override: None,
         })
     }
+
+    /// Get the paths of the configuration files
+    pub fn config_files(&self) -> [PathBuf; 4] {
+        [
+            APP_CONFIG_FILE,
+            KEYMAP_CONFIG_FILE,
+            THEME_CONFIG_FILE,
+            SMART_PLAYLISTS_CONFIG_FILE,
+        ]
+        .map(|file| self.config_folder.join(file))
+    }
+
+    /// Get the configurations of the active profile, `None` for the default profile
+    pub fn active_profile(&self) -> Option<&ProfileConfig> {
+        let name = ACTIVE_PROFILE.read();
+        let name = name.as_deref()?;
+        self.app_config.profiles.iter().find(|p| p.name == name)
+    }
+
+    /// Get the folder storing the active profile's caches (credentials, user data, play history, etc).
+    ///
+    /// The default profile uses the application's cache folder, while other profiles
+    /// use a `profiles/{name}` sub-folder of it.
+    pub fn profile_cache_folder(&self) -> PathBuf {
+        match self.active_profile() {
+            Some(profile) => self
+                .cache_folder
+                .join(PROFILES_CACHE_FOLDER)
+                .join(&profile.name),
+            None => self.cache_folder.clone(),
+        }
+    }
+
+    /// Get the Spotify client ID of the active profile
+    pub fn client_id(&self) -> String {
+        self.active_profile()
+            .and_then(|p| p.client_id.clone())
+            .unwrap_or_else(|| self.app_config.client_id.clone())
+    }
+
+    /// Get the default device of the active profile
+    pub fn default_device(&self) -> String {
+        self.active_profile()
+            .and_then(|p| p.default_device.clone())
+            .unwrap_or_else(|| self.app_config.default_device.clone())
+    }
+
+    /// Get the HTTP API configurations of the active profile, `None` if the HTTP API is disabled
+    #[cfg(feature = "http-api")]
+    pub fn http_api_config(&self) -> Option<&HttpApiConfig> {
+        self.active_profile()
+            .and_then(|p| p.http_api.as_ref())
+            .or(self.app_config.http_api.as_ref())
+    }
+
+    /// Get the scrobbler configurations of the active profile, `None` if scrobbling is disabled
+    pub fn scrobbler_config(&self) -> Option<&ScrobblerConfig> {
+        self.active_profile()
+            .and_then(|p| p.scrobbler.as_ref())
+            .or(self.app_config.scrobbler.as_ref())
+    }
 }
 
+/// application config options that can be applied without restarting the application
+const RELOADABLE_APP_CONFIG_OPTIONS: &[&str] = &[
+    "theme",
+    "playback_format",
+    "notify_format",
+    "notify_timeout_in_secs",
+    "page_size_in_rows",
+    "play_icon",
+    "pause_icon",
+    "liked_icon",
+    "border_type",
+    "progress_bar_type",
+    "playback_window_position",
+    "cover_img_length",
+    "cover_img_width",
+    "cover_img_scale",
+    "playback_window_width",
+    "seek_duration_secs",
+];
+
 #[derive(Debug, Deserialize, Serialize, ConfigParse)]
 /// Application configurations
 pub struct AppConfig {
@@ -50,6 +161,7 @@ pub struct AppConfig {
     pub client_id: String,
 
     pub client_port: u16,
+    pub enable_udp_client_socket: bool,
 
     pub player_event_hook_command: Option<Command>,
 
@@ -91,6 +203,15 @@ pub struct AppConfig {
 
     pub playback_window_width: usize,
 
+    #[cfg(feature = "streaming")]
+    pub visualizer_type: VisualizerType,
+    /// show the audio visualizer on startup
+    #[cfg(feature = "streaming")]
+    pub show_visualizer: bool,
+    /// render the audio visualizer in place of the cover image
+    #[cfg(all(feature = "streaming", feature = "image"))]
+    pub visualizer_replaces_cover_image: bool,
+
     #[cfg(feature = "media-control")]
     pub enable_media_control: bool,
 
@@ -102,14 +223,31 @@ pub struct AppConfig {
 
     pub enable_cover_image_cache: bool,
 
+    #[cfg(feature = "lyric-finder")]
+    pub lyric_folder: Option<String>,
+    #[cfg(feature = "lyric-finder")]
+    pub lyric_providers: Vec<LyricProviderType>,
+
     pub default_device: String,
 
     pub device: DeviceConfig,
 
+    #[cfg(feature = "streaming")]
+    pub dsp: DspConfig,
+
     #[cfg(all(feature = "streaming", feature = "notify"))]
     pub notify_streaming_only: bool,
 
     pub seek_duration_secs: u16,
+
+    pub scrobbler: Option<ScrobblerConfig>,
+
+    #[cfg(feature = "http-api")]
+    pub http_api: Option<HttpApiConfig>,
+
+    pub profiles: Vec<ProfileConfig>,
+
+    pub schedules: Vec<ScheduleConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -136,6 +274,18 @@ pub enum ProgressBarType {
 }
 config_parser_impl!(ProgressBarType);
 
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "streaming")]
+/// The type of the audio visualizer in the playback window
+pub enum VisualizerType {
+    /// bars of the audio's frequency spectrum
+    Spectrum,
+    /// the audio's waveform
+    Waveform,
+}
+#[cfg(feature = "streaming")]
+config_parser_impl!(VisualizerType);
+
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
 pub struct Command {
     pub command: String,
@@ -152,7 +302,206 @@ pub struct DeviceConfig {
     pub bitrate: u16,
     pub audio_cache: bool,
     pub normalization: bool,
+    pub normalization_type: NormalizationType,
+    /// pregain (in dB) applied by the audio normalization
+    pub normalization_pregain: f64,
     pub autoplay: bool,
+    /// preload the next track to play it without a gap after the current one
+    pub gapless: bool,
+    /// duration (in milliseconds) over which a track is faded out before its end
+    /// and the next track is faded in, `0` to disable
+    pub crossfade_duration_ms: u32,
+    /// duration (in milliseconds) over which the playback is faded out before pausing
+    /// and faded in after resuming, `0` to disable
+    pub pause_fade_duration_ms: u32,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// The loudness data used by the audio normalization
+pub enum NormalizationType {
+    /// normalize each track to the same loudness
+    Track,
+    /// keep the relative loudness of tracks from the same album
+    Album,
+    /// use `Album` when playing an album, `Track` otherwise
+    Auto,
+}
+config_parser_impl!(NormalizationType);
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+#[cfg(feature = "streaming")]
+/// Configurations of the DSP chain applied to the integrated player's audio
+pub struct DspConfig {
+    /// name of the equalizer preset applied on startup
+    pub preset: String,
+    /// user-defined equalizer presets, in addition to the built-in presets
+    pub presets: Vec<EqPreset>,
+    /// limit the audio's peaks to prevent clipping
+    pub limiter: bool,
+    /// downmix the audio's channels into mono
+    pub mono: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[cfg(feature = "streaming")]
+/// A named equalizer preset
+pub struct EqPreset {
+    pub name: String,
+    /// gain (in dB) applied before the equalizer's bands
+    #[serde(default)]
+    pub preamp: f64,
+    #[serde(default)]
+    pub bands: Vec<EqBand>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[cfg(feature = "streaming")]
+/// A band of a parametric equalizer
+pub struct EqBand {
+    #[serde(default)]
+    pub filter: EqFilterType,
+    /// center frequency (in Hz) of a peak filter, corner frequency of a shelf filter
+    pub freq: f64,
+    /// gain (in dB)
+    pub gain: f64,
+    /// quality factor, higher values affect a narrower frequency range
+    #[serde(default = "default_eq_band_q")]
+    pub q: f64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg(feature = "streaming")]
+pub enum EqFilterType {
+    #[default]
+    Peak,
+    LowShelf,
+    HighShelf,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+/// A scrobbling service
+pub enum ScrobblerService {
+    /// [ListenBrainz](https://listenbrainz.org) or a ListenBrainz-compatible server
+    #[default]
+    ListenBrainz,
+    /// [Last.fm](https://www.last.fm)
+    LastFm,
+}
+config_parser_impl!(ScrobblerService);
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+/// Application scrobbler configurations
+pub struct ScrobblerConfig {
+    #[serde(default)]
+    pub service: ScrobblerService,
+    /// overrides the service's default API URL
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// the ListenBrainz user token
+    #[serde(default)]
+    pub token: String,
+    /// the Last.fm API account's key
+    #[serde(default)]
+    pub api_key: String,
+    /// the Last.fm API account's shared secret
+    #[serde(default)]
+    pub api_secret: String,
+    /// the Last.fm user's name
+    #[serde(default)]
+    pub username: String,
+    /// the Last.fm user's password
+    #[serde(default)]
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[cfg(feature = "lyric-finder")]
+/// A source of song lyrics
+pub enum LyricProviderType {
+    /// `.lrc` files inside the `lyric_folder` folder
+    Local,
+    /// [LRCLIB](https://lrclib.net)
+    Lrclib,
+    /// [Genius](https://genius.com)
+    Genius,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A named profile with its own credentials, caches and default device
+pub struct ProfileConfig {
+    pub name: String,
+    /// overrides the application's `client_id`
+    #[serde(default)]
+    pub client_id: Option<String>,
+    /// overrides the application's `default_device`
+    #[serde(default)]
+    pub default_device: Option<String>,
+    /// overrides the application's `scrobbler`
+    #[serde(default)]
+    pub scrobbler: Option<ScrobblerConfig>,
+    /// overrides the application's `http_api`
+    #[cfg(feature = "http-api")]
+    #[serde(default)]
+    pub http_api: Option<HttpApiConfig>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+/// Days of the week on which a scheduled action runs
+pub enum ScheduleDay {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+    /// Monday to Friday
+    Weekdays,
+    /// Saturday and Sunday
+    Weekends,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(tag = "action")]
+/// An action run by a schedule
+pub enum ScheduledAction {
+    /// start playing a context, given its URI or the name of a user's playlist,
+    /// on a device (given its name or ID) or the current device if not specified
+    StartContext {
+        context: String,
+        #[serde(default)]
+        device: Option<String>,
+        #[serde(default)]
+        shuffle: bool,
+    },
+    Pause,
+    Resume,
+    /// set a sleep timer pausing the playback after a number of minutes
+    SleepTimer {
+        minutes: u64,
+    },
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+/// A cron-like schedule running an action at a time of the day
+pub struct ScheduleConfig {
+    /// local time of the day in the `HH:MM` format
+    pub time: String,
+    /// days of the week to run the action, every day if empty
+    #[serde(default)]
+    pub days: Vec<ScheduleDay>,
+    #[serde(flatten)]
+    pub action: ScheduledAction,
+}
+
+#[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
+#[cfg(feature = "http-api")]
+/// Application HTTP API server configurations
+pub struct HttpApiConfig {
+    #[serde(default = "default_http_api_bind_address")]
+    pub bind_address: String,
+    /// the bearer token required to authenticate requests
+    pub token: String,
 }
 
 #[derive(Debug, Deserialize, Serialize, ConfigParse, Clone)]
@@ -219,6 +568,7 @@ impl Default for AppConfig {
             client_id: "65b708073fc0480ea92a077233ca87bd".to_string(),
 
             client_port: 8080,
+            enable_udp_client_socket: false,
 
             tracks_playback_limit: 50,
 
@@ -258,6 +608,13 @@ impl Default for AppConfig {
 
             playback_window_width: 6,
 
+            #[cfg(feature = "streaming")]
+            visualizer_type: VisualizerType::Spectrum,
+            #[cfg(feature = "streaming")]
+            show_visualizer: false,
+            #[cfg(all(feature = "streaming", feature = "image"))]
+            visualizer_replaces_cover_image: false,
+
             // Because of the "creating new window and stealing focus" behaviour
             // when running the media control event loop on startup,
             // media control support is disabled by default for Windows and MacOS.
@@ -277,18 +634,137 @@ impl Default for AppConfig {
 
             enable_cover_image_cache: true,
 
+            #[cfg(feature = "lyric-finder")]
+            lyric_folder: None,
+            #[cfg(feature = "lyric-finder")]
+            lyric_providers: vec![
+                LyricProviderType::Local,
+                LyricProviderType::Lrclib,
+                LyricProviderType::Genius,
+            ],
+
             default_device: "spotify-player".to_string(),
 
             device: DeviceConfig::default(),
 
+            #[cfg(feature = "streaming")]
+            dsp: DspConfig::default(),
+
             #[cfg(all(feature = "streaming", feature = "notify"))]
             notify_streaming_only: false,
 
             seek_duration_secs: 5,
+
+            scrobbler: None,
+
+            #[cfg(feature = "http-api")]
+            http_api: None,
+
+            profiles: vec![],
+
+            schedules: vec![],
+        }
+    }
+}
+
+impl ScheduleDay {
+    /// Check if a day of the week matches the schedule day
+    pub fn matches(&self, weekday: chrono::Weekday) -> bool {
+        use chrono::Weekday;
+
+        match self {
+            Self::Mon => weekday == Weekday::Mon,
+            Self::Tue => weekday == Weekday::Tue,
+            Self::Wed => weekday == Weekday::Wed,
+            Self::Thu => weekday == Weekday::Thu,
+            Self::Fri => weekday == Weekday::Fri,
+            Self::Sat => weekday == Weekday::Sat,
+            Self::Sun => weekday == Weekday::Sun,
+            Self::Weekdays => !matches!(weekday, Weekday::Sat | Weekday::Sun),
+            Self::Weekends => matches!(weekday, Weekday::Sat | Weekday::Sun),
         }
     }
 }
 
+impl ScheduleConfig {
+    /// Get the schedule's time of the day
+    pub fn time(&self) -> Result<chrono::NaiveTime> {
+        chrono::NaiveTime::parse_from_str(&self.time, "%H:%M").with_context(|| {
+            format!(
+                "invalid schedule time {:?}: a time should be in the `HH:MM` format",
+                self.time
+            )
+        })
+    }
+
+    /// Check if the schedule runs on a day of the week
+    pub fn runs_on(&self, weekday: chrono::Weekday) -> bool {
+        self.days.is_empty() || self.days.iter().any(|d| d.matches(weekday))
+    }
+
+    /// Get the next time the schedule runs after `now`
+    pub fn next_run(
+        &self,
+        now: chrono::DateTime<chrono::Local>,
+    ) -> Option<chrono::DateTime<chrono::Local>> {
+        use chrono::{Datelike, TimeZone};
+
+        let time = self.time().ok()?;
+        (0..=7)
+            .filter_map(|i| {
+                let date = now.date_naive() + chrono::Days::new(i);
+                chrono::Local
+                    .from_local_datetime(&date.and_time(time))
+                    .earliest()
+            })
+            .find(|t| *t > now && self.runs_on(t.weekday()))
+    }
+}
+
+impl Templates {
+    fn new(config: &AppConfig) -> Result<Self> {
+        Ok(Self {
+            playback: Template::parse(&config.playback_format)
+                .context("invalid `playback_format`")?,
+            #[cfg(feature = "notify")]
+            notify_summary: Template::parse(&config.notify_format.summary)
+                .context("invalid `notify_format.summary`")?,
+            #[cfg(feature = "notify")]
+            notify_body: Template::parse(&config.notify_format.body)
+                .context("invalid `notify_format.body`")?,
+        })
+    }
+}
+
+impl ScrobblerConfig {
+    /// Get the root URL of the scrobbling service's API
+    pub fn api_url(&self) -> &str {
+        match (&self.api_url, self.service) {
+            (Some(url), _) => url,
+            (None, ScrobblerService::ListenBrainz) => "https://api.listenbrainz.org",
+            (None, ScrobblerService::LastFm) => "https://ws.audioscrobbler.com/2.0",
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let required = match self.service {
+            ScrobblerService::ListenBrainz => vec![("token", &self.token)],
+            ScrobblerService::LastFm => vec![
+                ("api_key", &self.api_key),
+                ("api_secret", &self.api_secret),
+                ("username", &self.username),
+                ("password", &self.password),
+            ],
+        };
+        for (name, value) in required {
+            if value.is_empty() {
+                anyhow::bail!("`{name}` is required to scrobble to {:?}", self.service);
+            }
+        }
+        Ok(())
+    }
+}
+
 impl Default for DeviceConfig {
     fn default() -> Self {
         Self {
@@ -298,11 +774,38 @@ impl Default for DeviceConfig {
             bitrate: 320,
             audio_cache: false,
             normalization: false,
+            normalization_type: NormalizationType::Auto,
+            normalization_pregain: 0.0,
             autoplay: false,
+            gapless: true,
+            crossfade_duration_ms: 0,
+            pause_fade_duration_ms: 0,
+        }
+    }
+}
+
+#[cfg(feature = "streaming")]
+impl Default for DspConfig {
+    fn default() -> Self {
+        Self {
+            preset: "Flat".to_string(),
+            presets: vec![],
+            limiter: true,
+            mono: false,
         }
     }
 }
 
+#[cfg(feature = "streaming")]
+fn default_eq_band_q() -> f64 {
+    std::f64::consts::FRAC_1_SQRT_2
+}
+
+#[cfg(feature = "http-api")]
+fn default_http_api_bind_address() -> String {
+    "127.0.0.1:8081".to_string()
+}
+
 impl AppConfig {
     pub fn new(path: &Path) -> Result<Self> {
         let mut config = Self::default();
@@ -310,6 +813,37 @@ impl AppConfig {
             config.write_config_file(path)?
         }
 
+        // profile names are used as cache folder names
+        let mut names = std::collections::HashSet::new();
+        for profile in &config.profiles {
+            if profile.name.is_empty()
+                || !profile
+                    .name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            {
+                anyhow::bail!(
+                    "invalid profile name {:?}: a profile name can only contain alphanumeric characters, `-` and `_`",
+                    profile.name
+                );
+            }
+            if !names.insert(profile.name.as_str()) {
+                anyhow::bail!("duplicate profile name {:?}", profile.name);
+            }
+        }
+
+        for schedule in &config.schedules {
+            schedule.time()?;
+        }
+
+        let scrobbler_configs = config
+            .scrobbler
+            .iter()
+            .chain(config.profiles.iter().filter_map(|p| p.scrobbler.as_ref()));
+        for scrobbler in scrobbler_configs {
+            scrobbler.validate()?;
+        }
+
         Ok(config)
     }
 
@@ -335,6 +869,44 @@ impl AppConfig {
             })
     }
 
+    /// Construct the configurations after reloading `new` configurations.
+    ///
+    /// Only reloadable options are taken from `new`, other options are kept unchanged.
+    /// Return the constructed configurations and the names of changed options that require
+    /// restarting the application to be applied.
+    fn reload(&self, new: &AppConfig) -> Result<(AppConfig, Vec<String>)> {
+        let (toml::Value::Table(mut options), toml::Value::Table(new_options)) =
+            (toml::Value::try_from(self)?, toml::Value::try_from(new)?)
+        else {
+            anyhow::bail!("application configurations should be serialized into a table");
+        };
+
+        // unset options (e.g. `None` values) are not serialized
+        let names = options
+            .keys()
+            .chain(new_options.keys())
+            .cloned()
+            .collect::<std::collections::BTreeSet<_>>();
+
+        let mut restart_options = vec![];
+        for name in names {
+            let value = new_options.get(&name);
+            if options.get(&name) == value {
+                continue;
+            }
+            if RELOADABLE_APP_CONFIG_OPTIONS.contains(&name.as_str()) {
+                match value {
+                    Some(value) => options.insert(name, value.clone()),
+                    None => options.remove(&name),
+                };
+            } else {
+                restart_options.push(name);
+            }
+        }
+
+        Ok((toml::Value::Table(options).try_into()?, restart_options))
+    }
+
     pub fn session_config(&self) -> SessionConfig {
         let proxy = self
             .proxy
@@ -371,11 +943,61 @@ pub fn get_cache_folder_path() -> Result<PathBuf> {
 }
 
 #[inline(always)]
-pub fn get_config() -> &'static Configs {
-    CONFIGS.get().expect("configs is already initialized")
+pub fn get_config() -> Arc<Configs> {
+    CONFIGS
+        .read()
+        .clone()
+        .expect("configs is already initialized")
+}
+/// Get the name of the active profile, `None` for the default profile
+pub fn active_profile_name() -> Option<String> {
+    ACTIVE_PROFILE.read().clone()
 }
+
+/// Set the active profile, `None` for the default profile.
+///
+/// The profile must be specified in the application's configurations.
+pub fn set_active_profile(name: Option<&str>) -> Result<()> {
+    if let Some(name) = name {
+        let configs = get_config();
+        if !configs.app_config.profiles.iter().any(|p| p.name == name) {
+            anyhow::bail!("profile {name} is not specified in the application's configurations");
+        }
+        let folder = configs.cache_folder.join(PROFILES_CACHE_FOLDER).join(name);
+        if !folder.exists() {
+            std::fs::create_dir_all(&folder)?;
+        }
+    }
+    *ACTIVE_PROFILE.write() = name.map(|n| n.to_string());
+    Ok(())
+}
+
 pub fn set_config(configs: Configs) {
-    CONFIGS
-        .set(configs)
-        .expect("configs should be initialized only once")
+    let mut current = CONFIGS.write();
+    assert!(current.is_none(), "configs should be initialized only once");
+    *current = Some(Arc::new(configs));
+}
+
+/// Reload the configurations from the configuration folder.
+///
+/// Keymap, theme and smart playlist configurations are replaced, while only reloadable
+/// application config options are applied. Return the names of changed options that require
+/// restarting the application to be applied.
+pub fn reload_config() -> Result<Vec<String>> {
+    let configs = get_config();
+    let mut new = Configs::new(&configs.config_folder, &configs.cache_folder)?;
+    new.theme_override.clone_from(&configs.theme_override);
+    if let Some(theme) = &new.theme_override {
+        theme.clone_into(&mut new.app_config.theme);
+    }
+
+    let (app_config, restart_options) = configs.app_config.reload(&new.app_config)?;
+    // the previous configurations are dropped once they're no longer held by any caller of `get_config`
+    *CONFIGS.write() = Some(Arc::new(Configs {
+        templates: Templates::new(&app_config)?,
+        app_config,
+        ..new
+    }));
+
+    Ok(restart_options)
 }
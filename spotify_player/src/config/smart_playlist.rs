@@ -0,0 +1,57 @@
+use anyhow::{Context as _, Result};
+use rspotify::model::PlaylistId;
+use serde::Deserialize;
+
+use crate::smart_playlist::Filter;
+
+#[derive(Debug, Default, Deserialize)]
+/// Smart playlists configurations
+pub struct SmartPlaylistsConfig {
+    /// the duration (in minutes) between two consecutive materializations of smart playlists
+    /// in a running application. If not specified, smart playlists are only materialized
+    /// via the `playlist materialize` CLI command.
+    pub refresh_interval_in_mins: Option<u64>,
+    #[serde(default)]
+    pub smart_playlists: Vec<SmartPlaylist>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+/// A playlist whose tracks are the tracks matching a filter expression
+pub struct SmartPlaylist {
+    pub name: String,
+    /// the ID or URI of the target playlist storing the smart playlist's tracks
+    pub playlist_id: String,
+    /// the filter expression, see `Filter::parse` for the syntax
+    pub filter: String,
+}
+
+impl SmartPlaylistsConfig {
+    pub fn new(path: &std::path::Path) -> Result<Self> {
+        let file_path = path.join(super::SMART_PLAYLISTS_CONFIG_FILE);
+        let config = match std::fs::read_to_string(&file_path) {
+            // smart playlists are optional, so there is no need to warn about a missing config file
+            Err(_) => Self::default(),
+            Ok(content) => toml::from_str::<Self>(&content)?,
+        };
+
+        // validate smart playlists upon parsing the config file
+        for p in &config.smart_playlists {
+            p.playlist_id()?;
+            Filter::parse(&p.filter)
+                .context(format!("invalid filter of smart playlist '{}'", p.name))?;
+        }
+
+        Ok(config)
+    }
+}
+
+impl SmartPlaylist {
+    pub fn playlist_id(&self) -> Result<PlaylistId<'static>> {
+        PlaylistId::from_id_or_uri(&self.playlist_id)
+            .map(|id| id.into_static())
+            .context(format!(
+                "invalid playlist ID of smart playlist '{}'",
+                self.name
+            ))
+    }
+}
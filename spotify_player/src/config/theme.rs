@@ -70,12 +70,15 @@ pub struct ComponentStyle {
     pub playback_album: Option<Style>,
     pub playback_metadata: Option<Style>,
     pub playback_progress_bar: Option<Style>,
+    #[cfg(feature = "streaming")]
+    pub playback_visualizer: Option<Style>,
     pub current_playing: Option<Style>,
     pub page_desc: Option<Style>,
     pub playlist_desc: Option<Style>,
     pub table_header: Option<Style>,
     pub selection: Option<Style>,
     pub secondary_row: Option<Style>,
+    pub multi_selection: Option<Style>,
 }
 
 #[derive(Default, Clone, Debug, Deserialize)]
@@ -260,6 +263,14 @@ impl Theme {
         }
     }
 
+    #[cfg(feature = "streaming")]
+    pub fn playback_visualizer(&self) -> tui::style::Style {
+        match &self.component_style.playback_visualizer {
+            None => Style::default().fg(StyleColor::Green).style(&self.palette),
+            Some(s) => s.style(&self.palette),
+        }
+    }
+
     pub fn current_playing(&self) -> tui::style::Style {
         match &self.component_style.current_playing {
             None => Style::default()
@@ -302,6 +313,44 @@ impl Theme {
             Some(s) => s.style(&self.palette),
         }
     }
+
+    /// Get a style by its name, which is either a component style's name (e.g. `playback_track`)
+    /// or a palette color's name (e.g. `BrightBlack`)
+    pub fn style_by_name(&self, name: &str) -> Option<tui::style::Style> {
+        let style = match name {
+            "block_title" => self.block_title(),
+            "border" => self.border(),
+            "playback_status" => self.playback_status(),
+            "playback_track" => self.playback_track(),
+            "playback_artists" => self.playback_artists(),
+            "playback_album" => self.playback_album(),
+            "playback_metadata" => self.playback_metadata(),
+            "playback_progress_bar" => self.playback_progress_bar(),
+            #[cfg(feature = "streaming")]
+            "playback_visualizer" => self.playback_visualizer(),
+            "current_playing" => self.current_playing(),
+            "page_desc" => self.page_desc(),
+            "playlist_desc" => self.playlist_desc(),
+            "table_header" => self.table_header(),
+            "selection" => self.selection(true),
+            "secondary_row" => self.secondary_row(),
+            "multi_selection" => self.multi_selection(),
+            name => Style::default()
+                .fg(StyleColor::from_name(name)?)
+                .style(&self.palette),
+        };
+        Some(style)
+    }
+
+    pub fn multi_selection(&self) -> tui::style::Style {
+        match &self.component_style.multi_selection {
+            None => Style::default()
+                .fg(StyleColor::Yellow)
+                .modifiers(vec![StyleModifier::Bold])
+                .style(&self.palette),
+            Some(s) => s.style(&self.palette),
+        }
+    }
 }
 
 impl Style {
@@ -353,7 +402,24 @@ impl<'de> serde::de::Deserialize<'de> for StyleColor {
         }
 
         let str = String::deserialize(deserializer)?;
-        Ok(match str.as_str() {
+        Ok(match StyleColor::from_name(&str) {
+            Some(color) => color,
+            None => match rgb_from_hex(&str) {
+                Some((r, g, b)) => StyleColor::Rgb { r, g, b },
+                None => {
+                    return Err(serde::de::Error::custom(format!(
+                        "invalid hex color: {str}"
+                    )))
+                }
+            },
+        })
+    }
+}
+
+impl StyleColor {
+    /// Get a named color, which doesn't include RGB colors
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
             "Black" => StyleColor::Black,
             "Blue" => StyleColor::Blue,
             "Cyan" => StyleColor::Cyan,
@@ -370,15 +436,10 @@ impl<'de> serde::de::Deserialize<'de> for StyleColor {
             "BrightCyan" => StyleColor::BrightCyan,
             "BrightBlue" => StyleColor::BrightBlue,
             "BrightYellow" => StyleColor::BrightYellow,
-            s => match rgb_from_hex(s) {
-                Some((r, g, b)) => StyleColor::Rgb { r, g, b },
-                None => return Err(serde::de::Error::custom(format!("invalid hex color: {s}"))),
-            },
+            _ => return None,
         })
     }
-}
 
-impl StyleColor {
     pub fn color(&self, palette: &Palette) -> style::Color {
         match *self {
             Self::Black => palette.black.color,
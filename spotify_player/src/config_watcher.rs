@@ -0,0 +1,77 @@
+//! A watcher reloading the application's configurations when the configuration files change
+
+use crate::{
+    config,
+    state::{SharedState, StatusMessage, UIState},
+};
+
+/// the duration between two consecutive checks of the configuration files
+const POLL_DURATION: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Get the last modification times of the configuration files
+fn config_files_modified_times() -> Vec<Option<std::time::SystemTime>> {
+    config::get_config()
+        .config_files()
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+pub async fn start_config_watcher(state: SharedState) {
+    let mut interval = tokio::time::interval(POLL_DURATION);
+    let mut modified_times = config_files_modified_times();
+
+    loop {
+        interval.tick().await;
+
+        let new_modified_times = config_files_modified_times();
+        if new_modified_times == modified_times {
+            continue;
+        }
+        tracing::info!("Configuration files changed, reloading the configurations...");
+        reload_config(&mut state.ui.lock());
+        state.render.notify();
+
+        // reloading may re-create a missing configuration file with the default configurations
+        modified_times = config_files_modified_times();
+    }
+}
+
+/// Reload the application's configurations and apply them to the UI.
+///
+/// The reload's result is reported in the UI as a status message.
+pub fn reload_config(ui: &mut UIState) {
+    let message = match config::reload_config() {
+        Ok(restart_options) => {
+            let configs = config::get_config();
+            if let Some(theme) = configs.theme_config.find_theme(&configs.app_config.theme) {
+                ui.theme = theme;
+            }
+            #[cfg(feature = "image")]
+            {
+                // redraw the cover image as its layout may change
+                ui.last_cover_image_render_info = Default::default();
+            }
+
+            if restart_options.is_empty() {
+                tracing::info!("Reloaded the configurations");
+                StatusMessage::new("Reloaded the configurations".to_string(), false)
+            } else {
+                let text = format!(
+                    "Reloaded the configurations, restart the application to apply changes to: {}",
+                    restart_options.join(", ")
+                );
+                tracing::info!("{text}");
+                StatusMessage::new(text, false)
+            }
+        }
+        Err(err) => {
+            tracing::error!("Failed to reload the configurations: {err:#}");
+            StatusMessage::new(
+                format!("Failed to reload the configurations: {err:#}"),
+                true,
+            )
+        }
+    };
+    ui.status_message = Some(message);
+}
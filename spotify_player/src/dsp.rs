@@ -0,0 +1,476 @@
+//! An audio DSP chain (equalizer, limiter and mono downmix) applied to the integrated player's output
+
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use librespot_playback::{
+    audio_backend::{Sink, SinkResult},
+    convert::Converter,
+    decoder::AudioPacket,
+    NUM_CHANNELS, SAMPLE_RATE,
+};
+
+use crate::{
+    config::{self, DspConfig, EqBand, EqFilterType, EqPreset},
+    state::Mutex,
+};
+
+/// name of the preset without any equalization
+pub const FLAT_PRESET: &str = "Flat";
+
+/// the peak level (-1 dBFS) above which the limiter reduces the audio's gain
+const LIMITER_THRESHOLD: f64 = 0.891;
+/// the limiter's release time, i.e. how fast the audio's gain recovers after a peak
+const LIMITER_RELEASE_SECS: f64 = 0.1;
+
+fn builtin_presets() -> Vec<EqPreset> {
+    let band = |filter, freq, gain, q| EqBand {
+        filter,
+        freq,
+        gain,
+        q,
+    };
+    let shelf_q = std::f64::consts::FRAC_1_SQRT_2;
+
+    vec![
+        EqPreset {
+            name: FLAT_PRESET.to_string(),
+            preamp: 0.0,
+            bands: vec![],
+        },
+        EqPreset {
+            name: "Bass Boost".to_string(),
+            preamp: -4.0,
+            bands: vec![band(EqFilterType::LowShelf, 120.0, 6.0, shelf_q)],
+        },
+        EqPreset {
+            name: "Treble Boost".to_string(),
+            preamp: -4.0,
+            bands: vec![band(EqFilterType::HighShelf, 6000.0, 6.0, shelf_q)],
+        },
+        EqPreset {
+            name: "Vocal".to_string(),
+            preamp: -2.0,
+            bands: vec![
+                band(EqFilterType::Peak, 250.0, -2.0, 1.0),
+                band(EqFilterType::Peak, 2500.0, 3.0, 1.0),
+                band(EqFilterType::Peak, 5000.0, 2.0, 1.4),
+            ],
+        },
+        EqPreset {
+            name: "Loudness".to_string(),
+            preamp: -4.0,
+            bands: vec![
+                band(EqFilterType::LowShelf, 100.0, 5.0, shelf_q),
+                band(EqFilterType::HighShelf, 10000.0, 4.0, shelf_q),
+            ],
+        },
+    ]
+}
+
+/// Get the available equalizer presets: the built-in presets followed by the user-defined presets.
+/// A user-defined preset overrides a built-in preset with the same name.
+pub fn presets() -> Vec<EqPreset> {
+    let mut presets = builtin_presets();
+    for preset in &config::get_config().app_config.dsp.presets {
+        match presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(p) => *p = preset.clone(),
+            None => presets.push(preset.clone()),
+        }
+    }
+    presets
+}
+
+#[derive(Debug, Clone)]
+struct DspSettings {
+    /// a number incremented whenever the settings change
+    revision: u64,
+    preset: EqPreset,
+    limiter: bool,
+    mono: bool,
+}
+
+/// The DSP chain's settings, shared between the application and the integrated player,
+/// which can be changed while the audio is playing
+#[derive(Debug)]
+pub struct Dsp {
+    settings: Mutex<DspSettings>,
+}
+
+impl Dsp {
+    pub fn new(config: &DspConfig) -> Self {
+        let presets = presets();
+        let preset = match presets.iter().find(|p| p.name == config.preset) {
+            Some(preset) => preset.clone(),
+            None => {
+                tracing::warn!(
+                    "No equalizer preset with name={} found, using the {FLAT_PRESET} preset",
+                    config.preset
+                );
+                presets[0].clone()
+            }
+        };
+
+        Self {
+            settings: Mutex::new(DspSettings {
+                revision: 0,
+                preset,
+                limiter: config.limiter,
+                mono: config.mono,
+            }),
+        }
+    }
+
+    /// Switch to an equalizer preset
+    pub fn set_preset(&self, name: &str) -> Result<()> {
+        let preset = presets()
+            .into_iter()
+            .find(|p| p.name == name)
+            .with_context(|| format!("no equalizer preset with name={name} found"))?;
+        tracing::info!("Switch to the equalizer preset {name}");
+
+        let mut settings = self.settings.lock();
+        settings.preset = preset;
+        settings.revision += 1;
+        Ok(())
+    }
+
+    /// Wrap an audio sink, so the DSP chain is applied to the audio before being written to the sink
+    pub fn wrap_sink(self: &Arc<Self>, sink: Box<dyn Sink>) -> Box<dyn Sink> {
+        let settings = self.settings.lock();
+        Box::new(DspSink {
+            sink,
+            dsp: self.clone(),
+            revision: settings.revision,
+            chain: Chain::new(&settings),
+        })
+    }
+}
+
+/// An audio sink applying the DSP chain to the audio written into the underlying sink
+struct DspSink {
+    sink: Box<dyn Sink>,
+    dsp: Arc<Dsp>,
+    /// revision of the settings used to build the chain
+    revision: u64,
+    chain: Chain,
+}
+
+impl Sink for DspSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, mut packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        if let AudioPacket::Samples(ref mut samples) = packet {
+            {
+                let settings = self.dsp.settings.lock();
+                if settings.revision != self.revision {
+                    self.revision = settings.revision;
+                    self.chain = Chain::new(&settings);
+                }
+            }
+            self.chain.process(samples);
+        }
+        self.sink.write(packet, converter)
+    }
+}
+
+/// A chain of audio processors: preamp, equalizer's filters, mono downmix and limiter
+struct Chain {
+    preamp: f64,
+    filters: Vec<Biquad>,
+    mono: bool,
+    /// the limiter's current gain, `None` if the limiter is disabled
+    limiter_gain: Option<f64>,
+}
+
+impl Chain {
+    fn new(settings: &DspSettings) -> Self {
+        Self {
+            preamp: db_to_ratio(settings.preset.preamp),
+            filters: settings.preset.bands.iter().map(Biquad::new).collect(),
+            mono: settings.mono,
+            limiter_gain: settings.limiter.then_some(1.0),
+        }
+    }
+
+    /// Process interleaved audio samples in place
+    fn process(&mut self, samples: &mut [f64]) {
+        if self.filters.is_empty()
+            && self.preamp == 1.0
+            && !self.mono
+            && self.limiter_gain.is_none()
+        {
+            return;
+        }
+
+        let release = 1.0 - (-1.0 / (LIMITER_RELEASE_SECS * f64::from(SAMPLE_RATE))).exp();
+        for frame in samples.chunks_exact_mut(NUM_CHANNELS.into()) {
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample *= self.preamp;
+                for filter in &mut self.filters {
+                    *sample = filter.process(channel, *sample);
+                }
+            }
+
+            if self.mono {
+                let mean = frame.iter().sum::<f64>() / frame.len() as f64;
+                frame.fill(mean);
+            }
+
+            if let Some(gain) = self.limiter_gain.as_mut() {
+                let peak = frame.iter().fold(0.0_f64, |m, s| m.max(s.abs()));
+                let target = if peak > LIMITER_THRESHOLD {
+                    LIMITER_THRESHOLD / peak
+                } else {
+                    1.0
+                };
+                // reduce the gain instantly on a peak, then recover it gradually
+                *gain = if target < *gain {
+                    target
+                } else {
+                    *gain + (target - *gain) * release
+                };
+                frame.iter_mut().for_each(|s| *s *= *gain);
+            }
+        }
+    }
+}
+
+/// A biquad filter of an equalizer's band, whose coefficients are computed based on
+/// the [Audio EQ Cookbook](https://www.w3.org/TR/audio-eq-cookbook/)
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    /// the filter's state for each channel
+    z: [[f64; 2]; NUM_CHANNELS as usize],
+}
+
+impl Biquad {
+    fn new(band: &EqBand) -> Self {
+        let fs = f64::from(SAMPLE_RATE);
+        let freq = band.freq.clamp(10.0, fs / 2.0 - 1.0);
+        let a = 10_f64.powf(band.gain / 40.0);
+        let w0 = 2.0 * std::f64::consts::PI * freq / fs;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * band.q.max(0.01));
+        let sqrt_a_alpha = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match band.filter {
+            EqFilterType::Peak => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            EqFilterType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha),
+                (a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha,
+            ),
+            EqFilterType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a_alpha),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a_alpha),
+                (a + 1.0) - (a - 1.0) * cos + sqrt_a_alpha,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - sqrt_a_alpha,
+            ),
+        };
+
+        Self {
+            b0: b0 / a0,
+            b1: b1 / a0,
+            b2: b2 / a0,
+            a1: a1 / a0,
+            a2: a2 / a0,
+            z: Default::default(),
+        }
+    }
+
+    /// Filter a sample of a channel (transposed direct form II)
+    fn process(&mut self, channel: usize, x: f64) -> f64 {
+        let z = &mut self.z[channel];
+        let y = self.b0 * x + z[0];
+        z[0] = self.b1 * x - self.a1 * y + z[1];
+        z[1] = self.b2 * x - self.a2 * y;
+        y
+    }
+}
+
+fn db_to_ratio(db: f64) -> f64 {
+    10_f64.powf(db / 20.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(bands: Vec<EqBand>, preamp: f64, limiter: bool, mono: bool) -> Chain {
+        Chain::new(&DspSettings {
+            revision: 0,
+            preset: EqPreset {
+                name: "Test".to_string(),
+                preamp,
+                bands,
+            },
+            limiter,
+            mono,
+        })
+    }
+
+    fn shelf(filter: EqFilterType, gain: f64) -> EqBand {
+        EqBand {
+            filter,
+            freq: 1000.0,
+            gain,
+            q: std::f64::consts::FRAC_1_SQRT_2,
+        }
+    }
+
+    /// Get the steady-state amplitude of a biquad filter's output for a constant (DC) or an alternating (Nyquist) input
+    fn steady_amplitude(band: &EqBand, nyquist: bool) -> f64 {
+        let mut filter = Biquad::new(band);
+        let mut y = 0.0;
+        for i in 0..SAMPLE_RATE {
+            let x = if nyquist && i % 2 == 1 { -1.0 } else { 1.0 };
+            y = filter.process(0, x);
+        }
+        y.abs()
+    }
+
+    fn assert_close(x: f64, y: f64) {
+        assert!((x - y).abs() < 1e-6, "{x} != {y}");
+    }
+
+    /// A sink capturing the samples written into it
+    struct CaptureSink(Arc<Mutex<Vec<f64>>>);
+
+    impl Sink for CaptureSink {
+        fn write(&mut self, packet: AudioPacket, _: &mut Converter) -> SinkResult<()> {
+            if let AudioPacket::Samples(samples) = packet {
+                self.0.lock().extend(samples);
+            }
+            Ok(())
+        }
+    }
+
+    fn sine(n: u32, amplitude: f64) -> Vec<f64> {
+        (0..n)
+            .map(|i| amplitude * (f64::from(i) * 0.05).sin())
+            .collect()
+    }
+
+    #[test]
+    fn flat_chain_is_passthrough() {
+        let samples = sine(1024, 1.0);
+        let mut processed = samples.clone();
+        chain(vec![], 0.0, false, false).process(&mut processed);
+        assert_eq!(processed, samples);
+
+        // the limiter doesn't change audio below its threshold
+        let samples = sine(1024, LIMITER_THRESHOLD);
+        let mut processed = samples.clone();
+        chain(vec![], 0.0, true, false).process(&mut processed);
+        assert_eq!(processed, samples);
+    }
+
+    #[test]
+    fn flat_chain_limits_peaks() {
+        // full-scale audio peaks above the limiter's threshold without any boost
+        let mut samples = sine(1024, 1.0);
+        chain(vec![], 0.0, true, false).process(&mut samples);
+        let peak = samples.iter().fold(0.0_f64, |m, s| m.max(s.abs()));
+        assert!(
+            peak <= LIMITER_THRESHOLD + 1e-9,
+            "peak {peak} exceeds the ceiling"
+        );
+    }
+
+    #[test]
+    fn sink_applies_chain() {
+        let dsp = Arc::new(Dsp {
+            settings: Mutex::new(DspSettings {
+                revision: 0,
+                preset: builtin_presets().swap_remove(0),
+                limiter: false,
+                mono: false,
+            }),
+        });
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let mut sink = dsp.wrap_sink(Box::new(CaptureSink(captured.clone())));
+        let mut converter = Converter::new(None);
+
+        let samples = vec![0.2, 0.4, -0.6, 0.2];
+        sink.write(AudioPacket::Samples(samples.clone()), &mut converter)
+            .unwrap();
+        assert_eq!(*captured.lock(), samples);
+
+        // the chain is rebuilt when the settings change
+        {
+            let mut settings = dsp.settings.lock();
+            settings.mono = true;
+            settings.revision += 1;
+        }
+        captured.lock().clear();
+        sink.write(AudioPacket::Samples(samples), &mut converter)
+            .unwrap();
+        for (x, y) in captured.lock().iter().zip([0.3, 0.3, -0.2, -0.2]) {
+            assert_close(*x, y);
+        }
+    }
+
+    #[test]
+    fn shelf_gains() {
+        let gain = db_to_ratio(6.0);
+
+        let low_shelf = shelf(EqFilterType::LowShelf, 6.0);
+        assert_close(steady_amplitude(&low_shelf, false), gain);
+        assert_close(steady_amplitude(&low_shelf, true), 1.0);
+
+        let high_shelf = shelf(EqFilterType::HighShelf, 6.0);
+        assert_close(steady_amplitude(&high_shelf, false), 1.0);
+        assert_close(steady_amplitude(&high_shelf, true), gain);
+    }
+
+    #[test]
+    fn mono_downmix() {
+        let mut samples = vec![0.2, 0.4, -0.6, 0.2];
+        chain(vec![], 0.0, false, true).process(&mut samples);
+        for (x, y) in samples.into_iter().zip([0.3, 0.3, -0.2, -0.2]) {
+            assert_close(x, y);
+        }
+    }
+
+    #[test]
+    fn limiter_ceiling() {
+        // +12 dB boost of a full-scale sine wave
+        let mut samples = (0..SAMPLE_RATE)
+            .flat_map(|i| {
+                let x = (f64::from(i) * 0.05).sin();
+                [x, x]
+            })
+            .collect::<Vec<_>>();
+        chain(vec![], 12.0, true, false).process(&mut samples);
+        let peak = samples.iter().fold(0.0_f64, |m, s| m.max(s.abs()));
+        assert!(
+            peak <= LIMITER_THRESHOLD + 1e-9,
+            "peak {peak} exceeds the ceiling"
+        );
+        // the limiter reduces the gain without silencing the audio
+        assert!(peak > LIMITER_THRESHOLD * 0.9);
+    }
+}
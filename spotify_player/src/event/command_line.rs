@@ -0,0 +1,518 @@
+//! A vim-style command line for running commands, e.g. `:play playlist "Focus"` or `:vol +10`.
+//!
+//! Besides the built-in commands below, every [`Command`] and [`Action`] can be run by its name
+//! (case-insensitive, with optional `-`/`_` separators), e.g. `:NextTrack` or `:add-to-queue`.
+
+use crossterm::event::KeyCode;
+use strum::IntoEnumIterator;
+
+use super::*;
+use command::CommandOrAction;
+
+/// names of the command line's built-in commands
+const BUILTIN_COMMANDS: [&str; 11] = [
+    "play", "pause", "next", "prev", "vol", "seek", "sort", "queue", "theme", "sleep", "quit",
+];
+/// types of contexts that can be played by the `play` command
+const PLAY_TARGETS: [&str; 3] = ["playlist", "album", "artist"];
+/// fields that tracks can be sorted by with the `sort` command
+const SORT_FIELDS: [&str; 6] = ["title", "artist", "album", "duration", "added", "reverse"];
+/// arguments suggested for the `sleep` command
+const SLEEP_ARGS: [&str; 7] = ["15", "30", "45", "60", "track", "context", "off"];
+
+/// A parsed command line
+#[derive(Debug)]
+enum LineCommand {
+    Command(Command),
+    Action(Action),
+    Player(PlayerRequest),
+    Volume {
+        value: i64,
+        relative: bool,
+    },
+    Seek {
+        position_ms: i64,
+        relative: bool,
+    },
+    Theme(Box<config::Theme>),
+    /// set the sleep timer, `None` to cancel the timer
+    SleepTimer(Option<SleepTimerSpec>),
+}
+
+/// Handle a key sequence for the command line popup
+pub fn handle_key_sequence_for_command_line_popup(
+    key_sequence: &KeySequence,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let Some(PopupState::CommandLine {
+        ref mut line_input,
+        ref mut error,
+        ref mut completion,
+    }) = ui.popup
+    else {
+        return Ok(false);
+    };
+    if key_sequence.keys.len() != 1 {
+        return Ok(false);
+    }
+
+    match &key_sequence.keys[0] {
+        Key::None(KeyCode::Enter) => {
+            let line = line_input.get_text();
+            ui.popup = None;
+            if let Err(err) = run_command_line(&line, client_pub, state, ui) {
+                // re-open the command line to show the error
+                let mut line_input = LineInput::default();
+                line_input.set_text(&line);
+                ui.popup = Some(PopupState::CommandLine {
+                    line_input,
+                    error: Some(format!("{err:#}")),
+                    completion: None,
+                });
+            }
+        }
+        Key::None(k @ (KeyCode::Tab | KeyCode::BackTab)) => {
+            match completion {
+                Some(completion) => {
+                    let n = completion.candidates.len();
+                    completion.id = match k {
+                        KeyCode::Tab => (completion.id + 1) % n,
+                        _ => (completion.id + n - 1) % n,
+                    };
+                }
+                None => {
+                    *completion = complete(&line_input.get_text(), &state.data.read());
+                }
+            }
+            if let Some(completion) = completion {
+                line_input.set_text(&format!(
+                    "{}{}",
+                    completion.head,
+                    quote(&completion.candidates[completion.id])
+                ));
+            }
+            *error = None;
+        }
+        Key::None(KeyCode::Backspace) if line_input.is_empty() => {
+            // close the command line when user presses backspace on an empty input
+            ui.popup = None;
+        }
+        k => {
+            if line_input.input(k).is_none() {
+                return Ok(false);
+            }
+            *error = None;
+            *completion = None;
+        }
+    }
+    Ok(true)
+}
+
+/// Parse and run a command line
+fn run_command_line(
+    line: &str,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    let command = parse(line, &state.data.read())?;
+    tracing::info!("Running command line {line:?}: {command:?}");
+
+    match command {
+        LineCommand::Command(command) => {
+            if !page::handle_command_or_action_for_page(
+                CommandOrAction::Command(command),
+                client_pub,
+                state,
+                ui,
+            )? && !handle_global_command(command, client_pub, state, ui)?
+            {
+                anyhow::bail!("command {} is not available here", <&str>::from(command));
+            }
+        }
+        LineCommand::Action(action) => {
+            if !page::handle_command_or_action_for_page(
+                CommandOrAction::Action(action),
+                client_pub,
+                state,
+                ui,
+            )? {
+                anyhow::bail!("action {} is not available here", <&str>::from(action));
+            }
+        }
+        LineCommand::Player(request) => {
+            client_pub.send(ClientRequest::Player(request))?;
+        }
+        LineCommand::Volume { value, relative } => {
+            let volume = if relative {
+                let current = state
+                    .player
+                    .read()
+                    .buffered_playback
+                    .as_ref()
+                    .and_then(|p| p.volume)
+                    .context("no playback volume found")?;
+                i64::from(current).saturating_add(value)
+            } else {
+                value
+            };
+            client_pub.send(ClientRequest::Player(PlayerRequest::Volume(
+                volume.clamp(0, 100) as u8,
+            )))?;
+        }
+        LineCommand::Seek {
+            position_ms,
+            relative,
+        } => {
+            let position_ms = if relative {
+                let progress = state
+                    .player
+                    .read()
+                    .playback_progress()
+                    .context("no playback found")?;
+                progress.num_milliseconds().saturating_add(position_ms)
+            } else {
+                position_ms
+            };
+            client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                chrono::Duration::milliseconds(position_ms.max(0)),
+            )))?;
+        }
+        LineCommand::Theme(theme) => {
+            ui.theme = *theme;
+        }
+        LineCommand::SleepTimer(spec) => {
+            if let Some(volume) = state.set_sleep_timer(spec)? {
+                client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a command line
+fn parse(line: &str, data: &DataReadGuard) -> Result<LineCommand> {
+    let args = split_args(line)?;
+    let Some((name, args)) = args.split_first() else {
+        anyhow::bail!("empty command");
+    };
+
+    let command = match (name.to_lowercase().as_str(), args) {
+        ("play", []) => LineCommand::Player(PlayerRequest::Resume),
+        ("play", [target, name @ ..]) if !name.is_empty() => {
+            let id = find_context(target, &name.join(" "), data)?;
+            LineCommand::Player(PlayerRequest::StartPlayback(
+                Playback::Context(id, None),
+                None,
+            ))
+        }
+        ("play", _) => anyhow::bail!("usage: play [playlist|album|artist <name>]"),
+        ("pause", []) => LineCommand::Player(PlayerRequest::Pause),
+        ("next", []) => LineCommand::Command(Command::NextTrack),
+        ("prev" | "previous", []) => LineCommand::Command(Command::PreviousTrack),
+        ("q" | "quit", []) => LineCommand::Command(Command::Quit),
+        ("vol" | "volume", [value]) => {
+            // a volume (or a volume change) is a percentage
+            let (value, relative) = parse_signed(value, |v| {
+                v.parse::<i64>().ok().filter(|v| (0..=100).contains(v))
+            })
+            .with_context(|| format!("invalid volume {value:?}"))?;
+            LineCommand::Volume { value, relative }
+        }
+        ("vol" | "volume", _) => anyhow::bail!("usage: vol [+|-]<volume>"),
+        ("seek", [position]) => {
+            let (position_ms, relative) = parse_signed(position, parse_position_ms)
+                .with_context(|| format!("invalid position {position:?}"))?;
+            LineCommand::Seek {
+                position_ms,
+                relative,
+            }
+        }
+        ("seek", _) => anyhow::bail!("usage: seek [+|-]<[mm:]ss>"),
+        ("sort", [field]) => LineCommand::Command(match field.to_lowercase().as_str() {
+            "title" => Command::SortTrackByTitle,
+            "artist" | "artists" => Command::SortTrackByArtists,
+            "album" => Command::SortTrackByAlbum,
+            "duration" => Command::SortTrackByDuration,
+            "added" => Command::SortTrackByAddedDate,
+            "reverse" => Command::ReverseTrackOrder,
+            _ => anyhow::bail!("unknown sort field {field:?}"),
+        }),
+        ("sort", _) => anyhow::bail!("usage: sort <{}>", SORT_FIELDS.join("|")),
+        ("queue", []) => LineCommand::Command(Command::Queue),
+        ("queue", [arg]) if arg == "add" => LineCommand::Action(Action::AddToQueue),
+        ("queue", _) => anyhow::bail!("usage: queue [add]"),
+        ("theme", name) if !name.is_empty() => {
+            let name = name.join(" ");
+            LineCommand::Theme(Box::new(
+                config::get_config()
+                    .theme_config
+                    .find_theme(&name)
+                    .with_context(|| format!("theme {name:?} not found"))?,
+            ))
+        }
+        ("theme", _) => anyhow::bail!("usage: theme <name>"),
+        ("sleep", [arg]) if arg == "off" => LineCommand::SleepTimer(None),
+        ("sleep", [arg]) => LineCommand::SleepTimer(Some(arg.parse()?)),
+        ("sleep", _) => anyhow::bail!("usage: sleep <minutes|duration|track|context|off>"),
+        _ => {
+            let found = find_command_or_action(name)
+                .with_context(|| format!("unknown command {name:?}"))?;
+            if !args.is_empty() {
+                anyhow::bail!("command {name:?} doesn't take any argument");
+            }
+            match found {
+                CommandOrAction::Command(command) => LineCommand::Command(command),
+                CommandOrAction::Action(action) => LineCommand::Action(action),
+            }
+        }
+    };
+
+    Ok(command)
+}
+
+/// Find a command or an action by its name, ignoring cases and `-`/`_` separators
+fn find_command_or_action(name: &str) -> Option<CommandOrAction> {
+    let name = normalize_name(name);
+    Command::iter()
+        .filter(|c| *c != Command::None)
+        .find(|c| normalize_name((*c).into()) == name)
+        .map(CommandOrAction::Command)
+        .or_else(|| {
+            Action::iter()
+                .find(|a| normalize_name((*a).into()) == name)
+                .map(CommandOrAction::Action)
+        })
+}
+
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Find a context in the user's library by its type (`target`) and its name.
+///
+/// A context whose name matches exactly (ignoring cases) is preferred over
+/// a context whose name contains the given name.
+fn find_context(target: &str, name: &str, data: &DataReadGuard) -> Result<ContextId> {
+    fn find<'a, T>(items: &'a [T], name: &str, f: impl Fn(&T) -> &str) -> Option<&'a T> {
+        let name = name.to_lowercase();
+        items
+            .iter()
+            .find(|x| f(x).to_lowercase() == name)
+            .or_else(|| items.iter().find(|x| f(x).to_lowercase().contains(&name)))
+    }
+
+    let user_data = &data.user_data;
+    let id =
+        match target.to_lowercase().as_str() {
+            "playlist" => find(&user_data.playlists, name, |p| &p.name)
+                .map(|p| ContextId::Playlist(p.id.clone())),
+            "album" => find(&user_data.saved_albums, name, |a| &a.name)
+                .map(|a| ContextId::Album(a.id.clone())),
+            "artist" => find(&user_data.followed_artists, name, |a| &a.name)
+                .map(|a| ContextId::Artist(a.id.clone())),
+            _ => anyhow::bail!(
+                "unknown context type {target:?}, expected one of {}",
+                PLAY_TARGETS.join(", ")
+            ),
+        };
+    id.with_context(|| format!("{target} {name:?} not found in your library"))
+}
+
+/// Complete the last argument of a command line
+fn complete(line: &str, data: &DataReadGuard) -> Option<CommandLineCompletion> {
+    let (args, last_arg_start) = split_args_lenient(line);
+    let (args, last_arg) = match args.split_last() {
+        // the line ends with a whitespace, so the last argument is empty
+        Some(_) if last_arg_start == line.len() => (args.as_slice(), String::new()),
+        Some((last, args)) => (args, last.clone()),
+        None => (args.as_slice(), String::new()),
+    };
+    let mut head = line[..last_arg_start].to_string();
+
+    let candidates: Vec<String> = match args {
+        [] => BUILTIN_COMMANDS
+            .into_iter()
+            .chain(
+                Command::iter()
+                    .filter(|c| *c != Command::None)
+                    .map(<&str>::from),
+            )
+            .chain(Action::iter().map(<&str>::from))
+            .map(String::from)
+            .collect(),
+        [cmd] if cmd == "play" => PLAY_TARGETS.map(String::from).to_vec(),
+        [cmd] if cmd == "sort" => SORT_FIELDS.map(String::from).to_vec(),
+        [cmd] if cmd == "queue" => vec!["add".to_string()],
+        [cmd] if cmd == "sleep" => SLEEP_ARGS.map(String::from).to_vec(),
+        [cmd, ..] if cmd == "theme" => config::get_config()
+            .theme_config
+            .themes
+            .iter()
+            .map(|t| t.name.clone())
+            .collect(),
+        [cmd, target, ..] if cmd == "play" => match target.as_str() {
+            "playlist" => data
+                .user_data
+                .playlists
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            "album" => data
+                .user_data
+                .saved_albums
+                .iter()
+                .map(|a| a.name.clone())
+                .collect(),
+            "artist" => data
+                .user_data
+                .followed_artists
+                .iter()
+                .map(|a| a.name.clone())
+                .collect(),
+            _ => vec![],
+        },
+        _ => vec![],
+    };
+
+    // a context's or a theme's name can be specified without quotes,
+    // so the completed argument includes every argument after the command's fixed arguments
+    let n_fixed_args = match args.first().map(String::as_str) {
+        Some("play") => 2,
+        Some("theme") => 1,
+        _ => args.len(),
+    };
+    let prefix = if args.len() > n_fixed_args {
+        head = args[..n_fixed_args]
+            .iter()
+            .map(|a| format!("{} ", quote(a)))
+            .collect();
+        let mut words = args[n_fixed_args..].to_vec();
+        words.push(last_arg);
+        words.join(" ")
+    } else {
+        last_arg
+    };
+
+    let prefix = prefix.to_lowercase();
+    let mut candidates = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&prefix))
+        .collect::<Vec<_>>();
+    candidates.dedup();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    Some(CommandLineCompletion {
+        head,
+        candidates,
+        id: 0,
+    })
+}
+
+/// Split a command line into arguments, in which quoted arguments can include whitespaces
+fn split_args(line: &str) -> Result<Vec<String>> {
+    if line.chars().filter(|c| *c == '"').count() % 2 == 1 {
+        anyhow::bail!("unclosed quote");
+    }
+    Ok(split_args_lenient(line).0)
+}
+
+/// Split a command line into arguments, allowing an unclosed quote in the last argument.
+///
+/// Return the arguments and the start position of the last argument
+/// (or the line's length if the line ends with a whitespace).
+fn split_args_lenient(line: &str) -> (Vec<String>, usize) {
+    let mut args = Vec::new();
+    let mut arg = None::<String>;
+    let mut arg_start = 0;
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                arg.get_or_insert_with(|| {
+                    arg_start = i;
+                    String::new()
+                });
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if let Some(arg) = arg.take() {
+                    args.push(arg);
+                }
+            }
+            c => arg
+                .get_or_insert_with(|| {
+                    arg_start = i;
+                    String::new()
+                })
+                .push(c),
+        }
+    }
+    match arg {
+        Some(arg) => args.push(arg),
+        None => arg_start = line.len(),
+    }
+    (args, arg_start)
+}
+
+/// Quote an argument if it contains whitespaces
+fn quote(arg: &str) -> String {
+    if arg.contains(char::is_whitespace) {
+        format!("\"{arg}\"")
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Parse a value optionally prefixed by `+` or `-`, which makes the value relative.
+/// Return the parsed value (negated if prefixed by `-`) and whether the value is relative.
+fn parse_signed(value: &str, parse: impl Fn(&str) -> Option<i64>) -> Option<(i64, bool)> {
+    if let Some(v) = value.strip_prefix('+') {
+        Some((parse(v)?, true))
+    } else if let Some(v) = value.strip_prefix('-') {
+        Some((-parse(v)?, true))
+    } else {
+        Some((parse(value)?, false))
+    }
+}
+
+/// Parse a playback position in the `[[hh:]mm:]ss` format into milliseconds,
+/// `None` if the position is invalid or too large
+fn parse_position_ms(position: &str) -> Option<i64> {
+    let mut secs: i64 = 0;
+    for part in position.split(':') {
+        secs = secs
+            .checked_mul(60)?
+            .checked_add(part.parse::<u32>().ok()?.into())?;
+    }
+    secs.checked_mul(1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_position() {
+        assert_eq!(parse_position_ms("1:30"), Some(90_000));
+        assert_eq!(parse_position_ms("1:00:05"), Some(3_605_000));
+        assert_eq!(parse_position_ms("1:x"), None);
+        // too large positions are invalid instead of overflowing
+        assert_eq!(
+            parse_position_ms("4294967295:4294967295:4294967295:4294967295:4294967295"),
+            None
+        );
+        assert_eq!(
+            parse_signed("-1:30", parse_position_ms),
+            Some((-90_000, true))
+        );
+    }
+}
@@ -15,16 +15,23 @@ use anyhow::{Context as _, Result};
 use clipboard::{execute_copy_command, get_clipboard_content};
 
 mod clipboard;
+mod command_line;
+mod mouse;
 mod page;
 mod popup;
 mod window;
 
 /// Start a terminal event handler (key pressed, mouse clicked, etc)
 pub fn start_event_handler(state: SharedState, client_pub: flume::Sender<ClientRequest>) {
+    // the last mouse click, which is used to detect double clicks
+    let mut last_click = None;
+
     while let Ok(event) = crossterm::event::read() {
         let _enter = tracing::info_span!("terminal_event", event = ?event).entered();
         if let Err(err) = match event {
-            crossterm::event::Event::Mouse(event) => handle_mouse_event(event, &client_pub, &state),
+            crossterm::event::Event::Mouse(event) => {
+                mouse::handle_mouse_event(event, &mut last_click, &client_pub, &state)
+            }
             crossterm::event::Event::Key(event) => {
                 if event.kind == crossterm::event::KeyEventKind::Press {
                     // only handle key press event to avoid handling a key event multiple times
@@ -40,38 +47,10 @@ pub fn start_event_handler(state: SharedState, client_pub: flume::Sender<ClientR
         } {
             tracing::error!("Failed to handle event: {err:#}");
         }
-    }
-}
 
-// Handle a terminal mouse event
-fn handle_mouse_event(
-    event: crossterm::event::MouseEvent,
-    client_pub: &flume::Sender<ClientRequest>,
-    state: &SharedState,
-) -> Result<()> {
-    // a left click event
-    if let crossterm::event::MouseEventKind::Down(crossterm::event::MouseButton::Left) = event.kind
-    {
-        tracing::debug!("Handling mouse event: {event:?}");
-        let rect = state.ui.lock().playback_progress_bar_rect;
-        if event.row == rect.y {
-            // calculate the seek position (in ms) based on the mouse click position,
-            // the progress bar's width and the track's duration (in ms)
-            let duration = state
-                .player
-                .read()
-                .current_playing_track()
-                .map(|t| t.duration);
-            if let Some(duration) = duration {
-                let position_ms =
-                    (duration.num_milliseconds()) * (event.column as i64) / (rect.width as i64);
-                client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
-                    chrono::Duration::try_milliseconds(position_ms).unwrap(),
-                )))?;
-            }
-        }
+        // any terminal event (including a terminal resize) may require a new frame
+        state.render.notify();
     }
-    Ok(())
 }
 
 // Handle a terminal key pressed event
@@ -150,6 +129,13 @@ pub fn handle_action_in_context(
                 client_pub.send(ClientRequest::AddTrackToQueue(track.id))?;
                 ui.popup = None;
             }
+            Action::PlayNext | Action::PlayLater => {
+                client_pub.send(ClientRequest::AddTracksToLocalQueue {
+                    tracks: vec![track],
+                    play_next: matches!(action, Action::PlayNext),
+                })?;
+                ui.popup = None;
+            }
             Action::CopyLink => {
                 let track_url = format!("https://open.spotify.com/track/{}", track.id.id());
                 execute_copy_command(track_url)?;
@@ -158,7 +144,7 @@ pub fn handle_action_in_context(
             Action::AddToPlaylist => {
                 client_pub.send(ClientRequest::GetUserPlaylists)?;
                 ui.popup = Some(PopupState::UserPlaylistList(
-                    PlaylistPopupAction::AddTrack(track.id),
+                    PlaylistPopupAction::AddTracks(vec![track.id]),
                     new_list_state(),
                 ));
             }
@@ -303,11 +289,110 @@ pub fn handle_action_in_context(
             }
             _ => {}
         },
+        ActionContext::Show(show) => match action {
+            Action::AddToLibrary => {
+                client_pub.send(ClientRequest::AddToLibrary(Item::Show(show)))?;
+                ui.popup = None;
+            }
+            Action::DeleteFromLibrary => {
+                client_pub.send(ClientRequest::DeleteFromLibrary(ItemId::Show(show.id)))?;
+                ui.popup = None;
+            }
+            Action::CopyLink => {
+                let show_url = format!("https://open.spotify.com/show/{}", show.id.id());
+                execute_copy_command(show_url)?;
+                ui.popup = None;
+            }
+            _ => {}
+        },
+        ActionContext::Episode(episode) => match action {
+            Action::GoToShow => {
+                ui.new_page(PageState::Context {
+                    id: None,
+                    context_page_type: ContextPageType::Browsing(ContextId::Show(episode.show.id)),
+                    state: None,
+                });
+            }
+            Action::AddToQueue => {
+                client_pub.send(ClientRequest::AddEpisodeToQueue(episode.id))?;
+                ui.popup = None;
+            }
+            Action::MarkAsPlayed | Action::MarkAsUnplayed => {
+                client_pub.send(ClientRequest::MarkEpisodePlayed(
+                    episode.id,
+                    matches!(action, Action::MarkAsPlayed),
+                ))?;
+                ui.popup = None;
+            }
+            Action::CopyLink => {
+                let episode_url = format!("https://open.spotify.com/episode/{}", episode.id.id());
+                execute_copy_command(episode_url)?;
+                ui.popup = None;
+            }
+            _ => {}
+        },
     }
 
     Ok(())
 }
 
+/// Handle an action on multiple tracks selected in a track table.
+///
+/// Return `false` if the action cannot be run on multiple tracks.
+pub fn handle_action_on_tracks(
+    action: Action,
+    tracks: Vec<Track>,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let track_ids = tracks.iter().map(|t| t.id.clone()).collect::<Vec<_>>();
+    match action {
+        Action::AddToPlaylist => {
+            client_pub.send(ClientRequest::GetUserPlaylists)?;
+            ui.popup = Some(PopupState::UserPlaylistList(
+                PlaylistPopupAction::AddTracks(track_ids),
+                new_list_state(),
+            ));
+        }
+        Action::AddToQueue => {
+            client_pub.send(ClientRequest::AddTracksToQueue(track_ids))?;
+            ui.popup = None;
+        }
+        Action::PlayNext | Action::PlayLater => {
+            client_pub.send(ClientRequest::AddTracksToLocalQueue {
+                tracks,
+                play_next: matches!(action, Action::PlayNext),
+            })?;
+            ui.popup = None;
+        }
+        Action::AddToLiked => {
+            client_pub.send(ClientRequest::AddTracksToLiked(tracks))?;
+            ui.popup = None;
+        }
+        Action::DeleteFromLiked => {
+            client_pub.send(ClientRequest::DeleteTracksFromLiked(track_ids))?;
+            ui.popup = None;
+        }
+        Action::DeleteFromPlaylist => {
+            if let PageState::Context {
+                id: Some(ContextId::Playlist(playlist_id)),
+                ..
+            } = ui.current_page()
+            {
+                client_pub.send(ClientRequest::DeleteTracksFromPlaylist(
+                    playlist_id.clone_static(),
+                    track_ids,
+                ))?;
+            }
+            ui.popup = None;
+        }
+        _ => return Ok(false),
+    }
+
+    ui.track_selection = TrackSelection::default();
+    Ok(true)
+}
+
 fn handle_go_to_artist(artists: Vec<Artist>, ui: &mut UIStateGuard) {
     if artists.len() == 1 {
         let context_id = ContextId::Artist(artists[0].id.clone());
@@ -505,12 +590,21 @@ fn handle_global_command(
             });
             client_pub.send(ClientRequest::GetBrowseCategories)?;
         }
-        Command::PreviousPage => {
-            if ui.history.len() > 1 {
-                ui.history.pop();
-                ui.popup = None;
+        Command::HistoryPage => {
+            // cycle the statistics period if the history page is already opened
+            if let PageState::History { period, state } = ui.current_page_mut() {
+                *period = period.next();
+                *state = HistoryPageUIState::new();
+            } else {
+                ui.new_page(PageState::History {
+                    period: HistoryPeriod::Week,
+                    state: HistoryPageUIState::new(),
+                });
             }
         }
+        Command::PreviousPage => {
+            ui.previous_page();
+        }
         Command::OpenSpotifyLinkFromClipboard => {
             let content = get_clipboard_content().context("get clipboard's content")?;
             let re = regex::Regex::new(
@@ -567,11 +661,13 @@ fn handle_global_command(
                     track: track.name.clone(),
                     artists: artists.clone(),
                     scroll_offset: 0,
+                    offset_ms: 0,
                 });
 
                 client_pub.send(ClientRequest::GetLyric {
                     track: track.name.clone(),
                     artists,
+                    duration: track.duration.to_std().unwrap_or_default(),
                 })?;
             }
         }
@@ -579,6 +675,9 @@ fn handle_global_command(
             ui.popup = Some(PopupState::DeviceList(new_list_state()));
             client_pub.send(ClientRequest::GetDevices)?;
         }
+        Command::SwitchProfile => {
+            ui.popup = Some(PopupState::ProfileList(new_list_state()));
+        }
         Command::SwitchTheme => {
             // get the available themes with the current theme moved to the first position
             let mut themes = config::get_config().theme_config.themes.clone();
@@ -590,10 +689,29 @@ fn handle_global_command(
 
             ui.popup = Some(PopupState::ThemeList(themes, new_list_state()));
         }
+        Command::ReloadConfig => crate::config_watcher::reload_config(ui),
         #[cfg(feature = "streaming")]
         Command::RestartIntegratedClient => {
             client_pub.send(ClientRequest::RestartIntegratedClient)?;
         }
+        #[cfg(feature = "streaming")]
+        Command::SwitchEqPreset => {
+            // get the available presets with the current preset moved to the first position
+            let mut presets = crate::dsp::presets()
+                .into_iter()
+                .map(|p| p.name)
+                .collect::<Vec<_>>();
+            if let Some(id) = presets.iter().position(|name| *name == ui.eq_preset) {
+                let name = presets.remove(id);
+                presets.insert(0, name);
+            }
+
+            ui.popup = Some(PopupState::EqPresetList(presets, new_list_state()));
+        }
+        #[cfg(feature = "streaming")]
+        Command::ToggleVisualizer => {
+            ui.is_visualizer_shown = !ui.is_visualizer_shown;
+        }
         Command::FocusNextWindow => {
             if !ui.has_focused_popup() {
                 ui.current_page_mut().next()
@@ -605,7 +723,9 @@ fn handle_global_command(
             }
         }
         Command::Queue => {
-            ui.new_page(PageState::Queue { scroll_offset: 0 });
+            ui.new_page(PageState::Queue {
+                state: QueuePageUIState::new(),
+            });
             client_pub.send(ClientRequest::GetCurrentUserQueue)?;
         }
         Command::CreatePlaylist => {
@@ -613,6 +733,7 @@ fn handle_global_command(
                 name: LineInput::default(),
                 desc: LineInput::default(),
                 current_field: PlaylistCreateCurrentField::Name,
+                track_ids: vec![],
             });
         }
         Command::JumpToCurrentTrackInContext => {
@@ -645,6 +766,35 @@ fn handle_global_command(
         Command::ClosePopup => {
             ui.popup = None;
         }
+        Command::ClearTrackSelection => {
+            ui.track_selection = TrackSelection::default();
+        }
+        Command::Undo => {
+            client_pub.send(ClientRequest::Undo)?;
+        }
+        Command::Redo => {
+            client_pub.send(ClientRequest::Redo)?;
+        }
+        Command::CycleSleepTimer => {
+            let volume = match state.set_sleep_timer(state.next_sleep_timer_preset()) {
+                Ok(volume) => volume,
+                Err(err) => {
+                    // the next timer can't be set (e.g. no playing context), so cancel the timer instead
+                    tracing::warn!("Failed to set the sleep timer: {err:#}");
+                    state.set_sleep_timer(None)?
+                }
+            };
+            if let Some(volume) = volume {
+                client_pub.send(ClientRequest::Player(PlayerRequest::Volume(volume)))?;
+            }
+        }
+        Command::OpenCommandLine => {
+            ui.popup = Some(PopupState::CommandLine {
+                line_input: LineInput::default(),
+                error: None,
+                completion: None,
+            });
+        }
         _ => return Ok(false),
     }
     Ok(true)
@@ -0,0 +1,205 @@
+use super::*;
+use command::CommandOrAction;
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use tui::layout::Position;
+
+/// the maximum duration between two clicks to be considered as a double click
+const DOUBLE_CLICK_DURATION: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// A mouse click, which is used to detect double clicks
+#[derive(Clone, Copy)]
+pub struct MouseClick {
+    time: std::time::Instant,
+    position: Position,
+}
+
+/// Handle a terminal mouse event
+pub fn handle_mouse_event(
+    event: MouseEvent,
+    last_click: &mut Option<MouseClick>,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+) -> Result<()> {
+    tracing::debug!("Handling mouse event: {event:?}");
+    let position = Position {
+        x: event.column,
+        y: event.row,
+    };
+    let mut ui = state.ui.lock();
+
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            // start dragging the playback progress bar, the playback is seeked on release
+            if ui.playback_progress_bar_rect.contains(position) {
+                ui.progress_bar_drag_column = Some(event.column);
+                return Ok(());
+            }
+
+            let click = MouseClick {
+                time: std::time::Instant::now(),
+                position,
+            };
+            let is_double_click = last_click.is_some_and(|c| {
+                c.position == click.position
+                    && click.time.duration_since(c.time) <= DOUBLE_CLICK_DURATION
+            });
+            // reset the last click after a double click, so that a triple click
+            // isn't handled as two double clicks
+            *last_click = if is_double_click { None } else { Some(click) };
+
+            handle_mouse_click(position, is_double_click, client_pub, state, &mut ui)
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            if ui.progress_bar_drag_column.is_some() {
+                ui.progress_bar_drag_column = Some(event.column);
+            }
+            Ok(())
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            if let Some(column) = ui.progress_bar_drag_column.take() {
+                let duration = {
+                    let player = state.player.read();
+                    player
+                        .current_playing_track()
+                        .map(|t| t.duration)
+                        .or_else(|| player.current_playing_episode().map(|e| e.duration))
+                };
+                if let Some(duration) = duration {
+                    client_pub.send(ClientRequest::Player(PlayerRequest::SeekTrack(
+                        ui.progress_bar_position(column, duration),
+                    )))?;
+                }
+            }
+            Ok(())
+        }
+        MouseEventKind::ScrollDown => handle_mouse_scroll(
+            Command::SelectNextOrScrollDown,
+            position,
+            client_pub,
+            state,
+            &mut ui,
+        ),
+        MouseEventKind::ScrollUp => handle_mouse_scroll(
+            Command::SelectPreviousOrScrollUp,
+            position,
+            client_pub,
+            state,
+            &mut ui,
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Handle a left click at a given position.
+///
+/// Clicking a popup's entry chooses the entry. Clicking a page's window focuses the window
+/// and selects the clicked item, which is chosen upon a double click.
+fn handle_mouse_click(
+    position: Position,
+    is_double_click: bool,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    if let Some(rect) = ui.popup_list_rect {
+        if rect.contains(position) {
+            return handle_popup_entry_click((position.y - rect.y) as usize, client_pub, state, ui);
+        }
+    }
+    if ui.has_focused_popup() {
+        return Ok(());
+    }
+
+    let Some(window) = ui.window_area_at(position) else {
+        return Ok(());
+    };
+    let page = ui.current_page_mut();
+    if let Some(focus) = window.focus {
+        page.focus_window(focus);
+    }
+    match page.focus_window_state_mut() {
+        Some(mut state @ (MutableWindowState::List(_) | MutableWindowState::Table(_))) => {
+            let id = state.offset() + (position.y - window.rect.y) as usize;
+            if id >= window.n_items {
+                return Ok(());
+            }
+            state.select(id);
+        }
+        _ => return Ok(()),
+    }
+
+    if is_double_click {
+        page::handle_command_or_action_for_page(
+            CommandOrAction::Command(Command::ChooseSelected),
+            client_pub,
+            state,
+            ui,
+        )?;
+    }
+    Ok(())
+}
+
+/// Handle a left click on the `row`-th rendered entry of a list popup
+fn handle_popup_entry_click(
+    row: usize,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    let Some(list_state) = ui.popup.as_ref().and_then(|p| p.list_state()) else {
+        return Ok(());
+    };
+    let id = list_state.offset() + row;
+    let current_id = list_state.selected().unwrap_or_default();
+
+    // move the selection one entry at a time, so that popups reacting to
+    // the selection (e.g. the theme popup previewing the selected theme) are updated
+    let command = if id > current_id {
+        Command::SelectNextOrScrollDown
+    } else {
+        Command::SelectPreviousOrScrollUp
+    };
+    for _ in 0..id.abs_diff(current_id) {
+        popup::handle_command_for_popup(command, client_pub, state, ui)?;
+    }
+
+    // clicking below the popup's last entry doesn't choose anything
+    if ui.popup.as_ref().and_then(|p| p.list_selected()) == Some(id) {
+        popup::handle_command_for_popup(Command::ChooseSelected, client_pub, state, ui)?;
+    }
+    Ok(())
+}
+
+/// Handle a mouse scroll at a given position by scrolling the window under the mouse cursor
+fn handle_mouse_scroll(
+    command: Command,
+    position: Position,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<()> {
+    if ui
+        .popup_list_rect
+        .is_some_and(|rect| rect.contains(position))
+    {
+        popup::handle_command_for_popup(command, client_pub, state, ui)?;
+        return Ok(());
+    }
+    if ui.has_focused_popup() {
+        return Ok(());
+    }
+
+    if let Some(WindowArea {
+        focus: Some(focus), ..
+    }) = ui.window_area_at(position)
+    {
+        ui.current_page_mut().focus_window(focus);
+    }
+    page::handle_command_or_action_for_page(
+        CommandOrAction::Command(command),
+        client_pub,
+        state,
+        ui,
+    )?;
+    Ok(())
+}
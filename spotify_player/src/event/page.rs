@@ -3,6 +3,10 @@ use command::CommandOrAction;
 
 use super::*;
 
+/// the step (in milliseconds) to adjust a synced lyric's offset
+#[cfg(feature = "lyric-finder")]
+const LYRIC_OFFSET_STEP_MS: i64 = 250;
+
 pub fn handle_key_sequence_for_page(
     key_sequence: &KeySequence,
     client_pub: &flume::Sender<ClientRequest>,
@@ -20,26 +24,45 @@ pub fn handle_key_sequence_for_page(
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
     {
-        Some(CommandOrAction::Command(command)) => match page_type {
+        Some(found) => handle_command_or_action_for_page(found, client_pub, state, ui),
+        None => Ok(false),
+    }
+}
+
+/// Handle a command or an action for the current page
+pub fn handle_command_or_action_for_page(
+    found: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let page_type = ui.current_page().page_type();
+    if page_type == PageType::Search {
+        return handle_command_or_action_for_search_page(found, client_pub, state, ui);
+    }
+
+    match found {
+        CommandOrAction::Command(command) => match page_type {
             PageType::Search => anyhow::bail!("page search type should already be handled!"),
             PageType::Library => handle_command_for_library_page(command, client_pub, ui, state),
             PageType::Context => handle_command_for_context_page(command, client_pub, ui, state),
             PageType::Browse => handle_command_for_browse_page(command, client_pub, ui, state),
             #[cfg(feature = "lyric-finder")]
             PageType::Lyric => handle_command_for_lyric_page(command, ui),
-            PageType::Queue => handle_command_for_queue_page(command, ui),
+            PageType::Queue => handle_command_for_queue_page(command, ui, state),
+            PageType::History => handle_command_for_history_page(command, ui, state),
             PageType::CommandHelp => handle_command_for_command_help_page(command, ui),
         },
-        Some(CommandOrAction::Action(action)) => match page_type {
+        CommandOrAction::Action(action) => match page_type {
             PageType::Search => anyhow::bail!("page search type should already be handled!"),
             PageType::Library => handle_action_for_library_page(action, client_pub, ui, state),
             PageType::Context => {
                 window::handle_action_for_focused_context_page(action, client_pub, ui, state)
             }
             PageType::Browse => handle_action_for_browse_page(action, client_pub, ui, state),
+            PageType::Queue => handle_action_for_queue_page(action, client_pub, ui, state),
             _ => Ok(false),
         },
-        None => Ok(false),
     }
 }
 
@@ -76,6 +99,13 @@ fn handle_action_for_library_page(
             ui,
             client_pub,
         ),
+        LibraryFocusState::SavedShows => window::handle_action_for_selected_item(
+            action,
+            ui.search_filtered_items(&data.user_data.saved_shows),
+            &data,
+            ui,
+            client_pub,
+        ),
     }
 }
 
@@ -118,6 +148,12 @@ fn handle_command_for_library_page(
                         ui,
                     )
                 }
+                LibraryFocusState::SavedShows => window::handle_command_for_show_list_window(
+                    command,
+                    ui.search_filtered_items(&data.user_data.saved_shows),
+                    &data,
+                    ui,
+                ),
             }
         }
     }
@@ -157,19 +193,35 @@ fn handle_key_sequence_for_search_page(
         }
     }
 
-    let found_keymap = match config::get_config()
+    match config::get_config()
         .keymap_config
         .find_command_or_action_from_key_sequence(key_sequence)
     {
-        Some(found) => found,
-        None => return Ok(false),
+        Some(found) => handle_command_or_action_for_search_page(found, client_pub, state, ui),
+        None => Ok(false),
+    }
+}
+
+fn handle_command_or_action_for_search_page(
+    found_keymap: CommandOrAction,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let (focus_state, current_query) = match ui.current_page() {
+        PageState::Search {
+            state,
+            current_query,
+            ..
+        } => (state.focus, current_query.clone()),
+        _ => anyhow::bail!("expect a search page"),
     };
 
     let data = state.data.read();
-    let search_results = data.caches.search.get(current_query);
+    let search_results = data.caches.search.get(&current_query);
 
     match focus_state {
-        SearchFocusState::Input => anyhow::bail!("user's search input should be handled before"),
+        SearchFocusState::Input => Ok(false),
         SearchFocusState::Tracks => {
             let tracks = match search_results {
                 Some(s) => s.tracks.iter().collect(),
@@ -227,6 +279,36 @@ fn handle_key_sequence_for_search_page(
                 ),
             }
         }
+        SearchFocusState::Shows => {
+            let shows = search_results
+                .map(|s| s.shows.iter().collect())
+                .unwrap_or_default();
+
+            match found_keymap {
+                CommandOrAction::Command(command) => {
+                    window::handle_command_for_show_list_window(command, shows, &data, ui)
+                }
+                CommandOrAction::Action(action) => {
+                    window::handle_action_for_selected_item(action, shows, &data, ui, client_pub)
+                }
+            }
+        }
+        SearchFocusState::Episodes => {
+            let episodes = search_results
+                .map(|s| s.episodes.iter().collect())
+                .unwrap_or_default();
+
+            match found_keymap {
+                CommandOrAction::Command(command) => {
+                    window::handle_command_for_episode_list_window(
+                        command, client_pub, episodes, &data, ui,
+                    )
+                }
+                CommandOrAction::Action(action) => {
+                    window::handle_action_for_selected_item(action, episodes, &data, ui, client_pub)
+                }
+            }
+        }
     }
 }
 
@@ -362,10 +444,25 @@ fn handle_command_for_browse_page(
 
 #[cfg(feature = "lyric-finder")]
 fn handle_command_for_lyric_page(command: Command, ui: &mut UIStateGuard) -> Result<bool> {
-    let scroll_offset = match ui.current_page() {
-        PageState::Lyric { scroll_offset, .. } => *scroll_offset,
+    let (scroll_offset, offset_ms) = match ui.current_page_mut() {
+        PageState::Lyric {
+            scroll_offset,
+            offset_ms,
+            ..
+        } => (*scroll_offset, offset_ms),
         _ => return Ok(false),
     };
+    match command {
+        Command::IncreaseLyricOffset => {
+            *offset_ms += LYRIC_OFFSET_STEP_MS;
+            return Ok(true);
+        }
+        Command::DecreaseLyricOffset => {
+            *offset_ms -= LYRIC_OFFSET_STEP_MS;
+            return Ok(true);
+        }
+        _ => {}
+    }
     Ok(handle_navigation_command(
         command,
         ui.current_page_mut(),
@@ -377,16 +474,151 @@ fn handle_command_for_lyric_page(command: Command, ui: &mut UIStateGuard) -> Res
 fn handle_command_for_queue_page(
     command: Command,
     ui: &mut UIStateGuard,
-) -> Result<bool, anyhow::Error> {
-    let scroll_offset = match ui.current_page() {
-        PageState::Queue { scroll_offset } => *scroll_offset,
+    state: &SharedState,
+) -> Result<bool> {
+    let focus_state = match ui.current_page() {
+        PageState::Queue { state } => state.focus,
+        _ => return Ok(false),
+    };
+
+    match command {
+        Command::ClearQueue => {
+            state.player.write().local_queue.clear();
+            return Ok(true);
+        }
+        Command::SaveQueueAsPlaylist => {
+            // the current queue consists of the application's queue followed by Spotify's queue
+            let player = state.player.read();
+            let spotify_queue_track_ids = player.queue.iter().flat_map(|q| {
+                q.queue.iter().filter_map(|item| match item {
+                    rspotify_model::PlayableItem::Track(t) => t.id.clone(),
+                    rspotify_model::PlayableItem::Episode(_) => None,
+                })
+            });
+            let track_ids = player
+                .local_queue
+                .iter()
+                .map(|t| t.id.clone())
+                .chain(spotify_queue_track_ids)
+                .collect::<Vec<_>>();
+
+            ui.popup = Some(PopupState::PlaylistCreate {
+                name: LineInput::default(),
+                desc: LineInput::default(),
+                current_field: PlaylistCreateCurrentField::Name,
+                track_ids,
+            });
+            return Ok(true);
+        }
+        _ => {}
+    }
+
+    let page_state = ui.current_page_mut();
+    match focus_state {
+        QueueFocusState::LocalQueue => {
+            let mut player = state.player.write();
+            let queue = &mut player.local_queue;
+            let id = page_state.selected().unwrap_or_default();
+            if id >= queue.len() {
+                return Ok(false);
+            }
+
+            match command {
+                Command::MovePlaylistItemUp => {
+                    if id > 0 {
+                        queue.swap(id, id - 1);
+                        page_state.select(id - 1);
+                    }
+                }
+                Command::MovePlaylistItemDown => {
+                    if id + 1 < queue.len() {
+                        queue.swap(id, id + 1);
+                        page_state.select(id + 1);
+                    }
+                }
+                Command::DeleteQueueItem => {
+                    queue.remove(id);
+                    if id >= queue.len() && id > 0 {
+                        page_state.select(id - 1);
+                    }
+                }
+                Command::ShowActionsOnSelectedItem => {
+                    let track = queue[id].clone();
+                    drop(player);
+                    let actions = command::construct_track_actions(&track, &state.data.read());
+                    ui.popup = Some(PopupState::ActionList(
+                        Box::new(ActionListItem::Track(track, actions)),
+                        new_list_state(),
+                    ));
+                }
+                _ => {
+                    return Ok(handle_navigation_command(
+                        command,
+                        page_state,
+                        id,
+                        queue.len(),
+                    ))
+                }
+            }
+            Ok(true)
+        }
+        QueueFocusState::SpotifyQueue => {
+            let scroll_offset = page_state.selected().unwrap_or_default();
+            Ok(handle_navigation_command(
+                command,
+                page_state,
+                scroll_offset,
+                10000,
+            ))
+        }
+    }
+}
+
+fn handle_action_for_queue_page(
+    action: Action,
+    client_pub: &flume::Sender<ClientRequest>,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    match ui.current_page() {
+        PageState::Queue { state } if state.focus == QueueFocusState::LocalQueue => {}
         _ => return Ok(false),
+    }
+
+    let local_queue = state.player.read().local_queue.clone();
+    window::handle_action_for_selected_item(
+        action,
+        local_queue.iter().collect(),
+        &state.data.read(),
+        ui,
+        client_pub,
+    )
+}
+
+fn handle_command_for_history_page(
+    command: Command,
+    ui: &mut UIStateGuard,
+    state: &SharedState,
+) -> Result<bool> {
+    let (period, focus_state) = match ui.current_page() {
+        PageState::History { period, state } => (*period, state.focus),
+        _ => return Ok(false),
+    };
+    let stats = state
+        .data
+        .read()
+        .play_history
+        .stats(period, HISTORY_PAGE_STATS_LIMIT);
+    let len = match focus_state {
+        HistoryFocusState::Tracks => stats.top_tracks.len(),
+        HistoryFocusState::Artists => stats.top_artists.len(),
+        HistoryFocusState::Albums => stats.top_albums.len(),
     };
+
+    let page_state = ui.current_page_mut();
+    let selected = page_state.selected().unwrap_or_default();
     Ok(handle_navigation_command(
-        command,
-        ui.current_page_mut(),
-        scroll_offset,
-        10000,
+        command, page_state, selected, len,
     ))
 }
 
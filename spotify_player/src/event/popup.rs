@@ -18,6 +18,14 @@ pub fn handle_key_sequence_for_popup(
         PopupState::PlaylistCreate { .. } => {
             return handle_key_sequence_for_create_playlist_popup(key_sequence, client_pub, ui);
         }
+        PopupState::CommandLine { .. } => {
+            return command_line::handle_key_sequence_for_command_line_popup(
+                key_sequence,
+                client_pub,
+                state,
+                ui,
+            );
+        }
         PopupState::ActionList(item, ..) => {
             return handle_key_sequence_for_action_list_popup(
                 item.n_actions(),
@@ -38,13 +46,41 @@ pub fn handle_key_sequence_for_popup(
         None => return Ok(false),
     };
 
+    handle_command_for_popup(command, client_pub, state, ui)
+}
+
+/// Handle a command for a list popup
+pub fn handle_command_for_popup(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let popup = ui.popup.as_ref().context("empty popup")?;
+
     match popup {
         PopupState::Search { .. } => anyhow::bail!("search popup should be handled before"),
         PopupState::PlaylistCreate { .. } => {
             anyhow::bail!("create playlist popup should be handled before")
         }
-        PopupState::ActionList(..) => {
-            anyhow::bail!("action list popup should be handled before")
+        PopupState::CommandLine { .. } => {
+            anyhow::bail!("command line popup should be handled before")
+        }
+        PopupState::ActionList(item, ..) => {
+            let n_actions = item.n_actions();
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_actions,
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    handle_item_action(id, client_pub, state, ui)
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
         }
         PopupState::ArtistList(_, artists, _) => {
             let n_items = artists.len();
@@ -106,8 +142,8 @@ pub fn handle_key_sequence_for_popup(
                     rspotify_model::Type::Playlist,
                 )
             }
-            PlaylistPopupAction::AddTrack(track_id) => {
-                let track_id = track_id.clone();
+            PlaylistPopupAction::AddTracks(track_ids) => {
+                let track_ids = track_ids.clone();
                 let playlist_ids = state
                     .data
                     .read()
@@ -123,10 +159,13 @@ pub fn handle_key_sequence_for_popup(
                     playlist_ids.len(),
                     |_, _| {},
                     |ui: &mut UIStateGuard, id: usize| -> Result<()> {
-                        client_pub.send(ClientRequest::AddTrackToPlaylist(
-                            playlist_ids[id].clone(),
-                            track_id.clone(),
-                        ))?;
+                        let playlist_id = playlist_ids[id].clone();
+                        client_pub.send(match &track_ids[..] {
+                            [track_id] => {
+                                ClientRequest::AddTrackToPlaylist(playlist_id, track_id.clone())
+                            }
+                            _ => ClientRequest::AddTracksToPlaylist(playlist_id, track_ids.clone()),
+                        })?;
                         ui.popup = None;
                         Ok(())
                     },
@@ -196,6 +235,61 @@ pub fn handle_key_sequence_for_popup(
                 },
             )
         }
+        #[cfg(feature = "streaming")]
+        PopupState::EqPresetList(presets, _) => {
+            let n_items = presets.len();
+
+            // the selected preset is applied right away, so it can be previewed
+            let switch_preset = |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                let name = match ui.popup {
+                    Some(PopupState::EqPresetList(ref presets, _)) => presets[id].clone(),
+                    _ => return Ok(()),
+                };
+                client_pub.send(ClientRequest::SetEqPreset(name.clone()))?;
+                ui.eq_preset = name;
+                Ok(())
+            };
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                n_items,
+                |ui: &mut UIStateGuard, id: usize| {
+                    if let Err(err) = switch_preset(ui, id) {
+                        tracing::error!("Failed to switch the equalizer preset: {err:#}");
+                    }
+                },
+                |ui: &mut UIStateGuard, _| -> Result<()> {
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    if let Err(err) = switch_preset(ui, 0) {
+                        tracing::error!("Failed to switch the equalizer preset: {err:#}");
+                    }
+                    ui.popup = None;
+                },
+            )
+        }
+        PopupState::ProfileList(_) => {
+            let profiles = &config::get_config().app_config.profiles;
+
+            handle_command_for_list_popup(
+                command,
+                ui,
+                profiles.len() + 1, // the first item is the default profile
+                |_, _| {},
+                |ui: &mut UIStateGuard, id: usize| -> Result<()> {
+                    let name = id.checked_sub(1).map(|i| profiles[i].name.clone());
+                    client_pub.send(ClientRequest::SwitchProfile(name))?;
+                    ui.popup = None;
+                    Ok(())
+                },
+                |ui: &mut UIStateGuard| {
+                    ui.popup = None;
+                },
+            )
+        }
         PopupState::DeviceList(_) => {
             let player = state.player.read();
 
@@ -230,12 +324,13 @@ fn handle_key_sequence_for_create_playlist_popup(
     client_pub: &flume::Sender<ClientRequest>,
     ui: &mut UIStateGuard,
 ) -> Result<bool> {
-    let (name, desc, current_field) = match ui.popup {
+    let (name, desc, current_field, track_ids) = match ui.popup {
         Some(PopupState::PlaylistCreate {
             ref mut name,
             ref mut desc,
             ref mut current_field,
-        }) => (name, desc, current_field),
+            ref track_ids,
+        }) => (name, desc, current_field, track_ids),
         _ => return Ok(false),
     };
     if key_sequence.keys.len() == 1 {
@@ -246,6 +341,7 @@ fn handle_key_sequence_for_create_playlist_popup(
                     public: false,
                     collab: false,
                     desc: desc.get_text(),
+                    track_ids: track_ids.clone(),
                 })?;
                 ui.popup = None;
                 return Ok(true);
@@ -430,18 +526,7 @@ fn handle_key_sequence_for_action_list_popup(
         }
     };
 
-    handle_command_for_list_popup(
-        command,
-        ui,
-        n_actions,
-        |_, _| {},
-        |ui: &mut UIStateGuard, id: usize| -> Result<()> {
-            handle_item_action(id, client_pub, state, ui)
-        },
-        |ui: &mut UIStateGuard| {
-            ui.popup = None;
-        },
-    )
+    handle_command_for_popup(command, client_pub, state, ui)
 }
 
 /// Handle the `n`-th action in an action list popup
@@ -471,5 +556,15 @@ pub fn handle_item_action(
         ActionListItem::Playlist(playlist, actions) => {
             handle_action_in_context(actions[n], playlist.into(), client_pub, &data, ui)
         }
+        ActionListItem::Show(show, actions) => {
+            handle_action_in_context(actions[n], show.into(), client_pub, &data, ui)
+        }
+        ActionListItem::Episode(episode, actions) => {
+            handle_action_in_context(actions[n], episode.into(), client_pub, &data, ui)
+        }
+        ActionListItem::Tracks(tracks, actions) => {
+            handle_action_on_tracks(actions[n], tracks, client_pub, ui)?;
+            Ok(())
+        }
     }
 }
@@ -1,7 +1,10 @@
 use super::page::handle_navigation_command;
 use super::*;
 use crate::{
-    command::{construct_album_actions, construct_artist_actions, construct_playlist_actions},
+    command::{
+        construct_album_actions, construct_artist_actions, construct_episode_actions,
+        construct_playlist_actions, construct_show_actions,
+    },
     state::UIStateGuard,
 };
 use command::Action;
@@ -49,32 +52,23 @@ pub fn handle_action_for_focused_context_page(
                     ui,
                     client_pub,
                 ),
-                ArtistFocusState::TopTracks => handle_action_for_selected_item(
-                    action,
-                    ui.search_filtered_items(top_tracks),
-                    &data,
-                    ui,
-                    client_pub,
-                ),
+                ArtistFocusState::TopTracks => {
+                    handle_action_for_track_table(action, top_tracks, &data, ui, client_pub)
+                }
             }
         }
-        Some(Context::Album { tracks, .. }) => handle_action_for_selected_item(
-            action,
-            ui.search_filtered_items(tracks),
-            &data,
-            ui,
-            client_pub,
-        ),
-        Some(Context::Tracks { tracks, .. }) => handle_action_for_selected_item(
-            action,
-            ui.search_filtered_items(tracks),
-            &data,
-            ui,
-            client_pub,
-        ),
-        Some(Context::Playlist { tracks, .. }) => handle_action_for_selected_item(
+        Some(Context::Album { tracks, .. }) => {
+            handle_action_for_track_table(action, tracks, &data, ui, client_pub)
+        }
+        Some(Context::Tracks { tracks, .. }) => {
+            handle_action_for_track_table(action, tracks, &data, ui, client_pub)
+        }
+        Some(Context::Playlist { tracks, .. }) => {
+            handle_action_for_track_table(action, tracks, &data, ui, client_pub)
+        }
+        Some(Context::Show { episodes, .. }) => handle_action_for_selected_item(
             action,
-            ui.search_filtered_items(tracks),
+            ui.search_filtered_items(episodes),
             &data,
             ui,
             client_pub,
@@ -83,6 +77,29 @@ pub fn handle_action_for_focused_context_page(
     }
 }
 
+/// Handle an action for a track table, which runs on the table's selected tracks (if any)
+fn handle_action_for_track_table(
+    action: Action,
+    tracks: &[Track],
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+    client_pub: &flume::Sender<ClientRequest>,
+) -> Result<bool> {
+    if !ui.track_selection.is_empty() {
+        let tracks = ui.track_selection.selected_tracks(tracks);
+        if handle_action_on_tracks(action, tracks, client_pub, ui)? {
+            return Ok(true);
+        }
+    }
+    handle_action_for_selected_item(
+        action,
+        ui.search_filtered_items(tracks),
+        data,
+        ui,
+        client_pub,
+    )
+}
+
 pub fn handle_action_for_selected_item<T: Into<ActionContext> + Clone>(
     action: Action,
     items: Vec<&T>,
@@ -203,6 +220,13 @@ pub fn handle_command_for_focused_context_window(
             Context::Tracks { tracks, .. } => {
                 handle_command_for_track_table_window(command, client_pub, None, tracks, &data, ui)
             }
+            Context::Show { episodes, .. } => handle_command_for_episode_list_window(
+                command,
+                client_pub,
+                ui.search_filtered_items(episodes),
+                &data,
+                ui,
+            ),
         },
         None => Ok(false),
     }
@@ -225,7 +249,6 @@ fn handle_playlist_modify_command(
                     playlist_id: playlist_id.clone_static(),
                     insert_index: id - 1,
                     range_start: id,
-                    range_length: None,
                     snapshot_id: None,
                 })?;
                 ui.current_page_mut().select(id - 1);
@@ -238,7 +261,6 @@ fn handle_playlist_modify_command(
                     playlist_id: playlist_id.clone_static(),
                     insert_index: id + 1,
                     range_start: id,
-                    range_length: None,
                     snapshot_id: None,
                 })?;
                 ui.current_page_mut().select(id + 1);
@@ -274,23 +296,82 @@ fn handle_command_for_track_table_window(
         return Ok(false);
     }
 
-    if let Some(ContextId::Playlist(ref playlist_id)) = context_id {
-        let modifiable = data
-            .user_data
-            .modifiable_playlists()
-            .iter()
-            .any(|p| p.id.eq(playlist_id));
-        if modifiable
-            && handle_playlist_modify_command(
-                id,
-                playlist_id,
-                command,
-                client_pub,
-                &filtered_tracks,
-                data,
-                ui,
-            )?
+    let modifiable_playlist_id = match context_id {
+        Some(ContextId::Playlist(ref playlist_id))
+            if data
+                .user_data
+                .modifiable_playlists()
+                .iter()
+                .any(|p| p.id.eq(playlist_id)) =>
         {
+            Some(playlist_id)
+        }
+        _ => None,
+    };
+
+    // handle commands on the table's track selection
+    match command {
+        Command::ToggleTrackSelection => {
+            ui.track_selection
+                .toggle(filtered_tracks[id].id.clone(), id);
+            // move to the next track to allow toggling consecutive tracks quickly
+            handle_navigation_command(
+                Command::SelectNextOrScrollDown,
+                ui.current_page_mut(),
+                id,
+                filtered_tracks.len(),
+            );
+            return Ok(true);
+        }
+        Command::SelectTrackRange => {
+            let anchor = ui
+                .track_selection
+                .anchor
+                .unwrap_or(id)
+                .min(filtered_tracks.len() - 1);
+            ui.track_selection.track_ids.extend(
+                filtered_tracks[anchor.min(id)..=anchor.max(id)]
+                    .iter()
+                    .map(|t| t.id.clone()),
+            );
+            ui.track_selection.anchor = Some(id);
+            return Ok(true);
+        }
+        Command::SelectAllTracks => {
+            ui.track_selection
+                .track_ids
+                .extend(filtered_tracks.iter().map(|t| t.id.clone()));
+            return Ok(true);
+        }
+        Command::ShowActionsOnSelectedItem if !ui.track_selection.is_empty() => {
+            let actions = command::construct_bulk_track_actions(modifiable_playlist_id.is_some());
+            ui.popup = Some(PopupState::ActionList(
+                Box::new(ActionListItem::Tracks(
+                    ui.track_selection.selected_tracks(tracks),
+                    actions,
+                )),
+                new_list_state(),
+            ));
+            return Ok(true);
+        }
+        Command::AddSelectedItemToQueue if !ui.track_selection.is_empty() => {
+            let tracks = ui.track_selection.selected_tracks(tracks);
+            handle_action_on_tracks(Action::AddToQueue, tracks, client_pub, ui)?;
+            return Ok(true);
+        }
+        _ => {}
+    }
+
+    if let Some(playlist_id) = modifiable_playlist_id {
+        if handle_playlist_modify_command(
+            id,
+            playlist_id,
+            command,
+            client_pub,
+            &filtered_tracks,
+            data,
+            ui,
+        )? {
             return Ok(true);
         }
     }
@@ -488,3 +569,80 @@ pub fn handle_command_for_playlist_list_window(
     }
     Ok(true)
 }
+
+pub fn handle_command_for_show_list_window(
+    command: Command,
+    shows: Vec<&Show>,
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let id = ui.current_page_mut().selected().unwrap_or_default();
+    if id >= shows.len() {
+        return Ok(false);
+    }
+
+    if handle_navigation_command(command, ui.current_page_mut(), id, shows.len()) {
+        return Ok(true);
+    }
+    match command {
+        Command::ChooseSelected => {
+            let context_id = ContextId::Show(shows[id].id.clone());
+            ui.new_page(PageState::Context {
+                id: None,
+                context_page_type: ContextPageType::Browsing(context_id),
+                state: None,
+            });
+        }
+        Command::ShowActionsOnSelectedItem => {
+            let actions = construct_show_actions(shows[id], data);
+            ui.popup = Some(PopupState::ActionList(
+                Box::new(ActionListItem::Show(shows[id].clone(), actions)),
+                new_list_state(),
+            ));
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+pub fn handle_command_for_episode_list_window(
+    command: Command,
+    client_pub: &flume::Sender<ClientRequest>,
+    episodes: Vec<&Episode>,
+    data: &DataReadGuard,
+    ui: &mut UIStateGuard,
+) -> Result<bool> {
+    let id = ui.current_page_mut().selected().unwrap_or_default();
+    if id >= episodes.len() {
+        return Ok(false);
+    }
+
+    if handle_navigation_command(command, ui.current_page_mut(), id, episodes.len()) {
+        return Ok(true);
+    }
+    match command {
+        Command::ChooseSelected => {
+            // an episode is played in the context of its show, so the show's next episodes
+            // can be played after the episode ends
+            client_pub.send(ClientRequest::Player(PlayerRequest::StartPlayback(
+                Playback::Context(
+                    ContextId::Show(episodes[id].show.id.clone()),
+                    Some(rspotify_model::Offset::Uri(episodes[id].id.uri())),
+                ),
+                None,
+            )))?;
+        }
+        Command::ShowActionsOnSelectedItem => {
+            let actions = construct_episode_actions(episodes[id], data);
+            ui.popup = Some(PopupState::ActionList(
+                Box::new(ActionListItem::Episode(episodes[id].clone(), actions)),
+                new_list_state(),
+            ));
+        }
+        Command::AddSelectedItemToQueue => {
+            client_pub.send(ClientRequest::AddEpisodeToQueue(episodes[id].id.clone()))?;
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
@@ -3,16 +3,25 @@ mod cli;
 mod client;
 mod command;
 mod config;
+mod config_watcher;
+#[cfg(feature = "streaming")]
+mod dsp;
 mod event;
 mod key;
 #[cfg(feature = "media-control")]
 mod media_control;
+mod scheduler;
+mod scrobbler;
+mod smart_playlist;
 mod state;
 #[cfg(feature = "streaming")]
 mod streaming;
+mod template;
 mod token;
 mod ui;
 mod utils;
+#[cfg(feature = "streaming")]
+mod visualizer;
 
 use anyhow::{Context, Result};
 use rspotify::clients::BaseClient;
@@ -113,11 +122,11 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
     }
 
     // create a librespot session
-    let auth_config = auth::AuthConfig::new(configs)?;
+    let auth_config = auth::AuthConfig::new(&configs)?;
     let session = auth::new_session(&auth_config, !state.is_daemon).await?;
 
     // create a Spotify API client
-    let client = client::Client::new(session, auth_config, configs.app_config.client_id.clone());
+    let client = client::Client::new(session, auth_config, configs.client_id());
     client.refresh_token().await?;
 
     // initialize Spotify-related stuff
@@ -129,22 +138,26 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
     let mut tasks = Vec::new();
 
     // client socket task (for handling CLI commands)
-    tasks.push(tokio::task::spawn({
-        let client = client.clone();
-        let state = state.clone();
-        async move {
-            let port = configs.app_config.client_port;
-            tracing::info!("Starting a client socket at 127.0.0.1:{port}");
-            match tokio::net::UdpSocket::bind(("127.0.0.1", port)).await {
-                Ok(socket) => cli::start_socket(client, socket, Some(state)).await,
-                Err(err) => {
-                    tracing::warn!(
-                        "Failed to create a client socket for handling CLI commands: {err:#}"
-                    )
-                }
-            }
-        }
-    }));
+    tasks.push(tokio::task::spawn(cli::start_client_sockets(
+        client.clone(),
+        state.clone(),
+    )));
+
+    // smart playlist task
+    tasks.push(tokio::task::spawn(
+        smart_playlist::start_smart_playlist_task(client.clone(), state.clone()),
+    ));
+
+    // scheduler task (sleep timer and scheduled actions)
+    tasks.push(tokio::task::spawn(scheduler::start_scheduler(
+        client.clone(),
+        state.clone(),
+    )));
+
+    // config watcher task (reloading the configurations upon changes)
+    tasks.push(tokio::task::spawn(config_watcher::start_config_watcher(
+        state.clone(),
+    )));
 
     // client event handler task
     tasks.push(tokio::task::spawn({
@@ -154,16 +167,47 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
         }
     }));
 
+    // scrobbler task, started if any profile enables scrobbling
+    let scrobbler_pub = if configs.app_config.scrobbler.is_some()
+        || configs
+            .app_config
+            .profiles
+            .iter()
+            .any(|p| p.scrobbler.is_some())
+    {
+        let (scrobbler_pub, scrobbler_sub) = flume::unbounded::<scrobbler::ScrobblerRequest>();
+        tasks.push(tokio::task::spawn(scrobbler::start_scrobbler(
+            scrobbler_sub,
+        )));
+        Some(scrobbler_pub)
+    } else {
+        None
+    };
+
     // player event watcher task
     tasks.push(tokio::task::spawn({
         let state = state.clone();
         let client_pub = client_pub.clone();
         async move {
-            client::start_player_event_watchers(state, client_pub).await;
+            client::start_player_event_watchers(state, client_pub, scrobbler_pub).await;
         }
     }));
 
-    if !state.is_daemon {
+    if state.is_daemon {
+        // shutdown signal handler task
+        tokio::task::spawn({
+            let state = state.clone();
+            async move {
+                if let Err(err) = wait_for_shutdown_signal().await {
+                    tracing::error!("Failed to listen for shutdown signals: {err:#}");
+                    return;
+                }
+                tracing::info!("Received a shutdown signal, shutting down the application...");
+                state.flush_play_history();
+                std::process::exit(0);
+            }
+        });
+    } else {
         // spawn tasks needed for running the application UI
 
         // terminal event handler task
@@ -217,6 +261,23 @@ async fn start_app(state: &state::SharedState) -> Result<()> {
     Ok(())
 }
 
+/// Wait until the application receives a `SIGINT` or `SIGTERM` (`Ctrl-C` on Windows) signal
+async fn wait_for_shutdown_signal() -> Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result?,
+            _ = terminate.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await?;
+
+    Ok(())
+}
+
 fn main() -> Result<()> {
     // parse command line arguments
     let args = cli::init_cli()?.get_matches();
@@ -249,9 +310,11 @@ fn main() -> Result<()> {
         if let Some(theme) = args.get_one::<String>("theme") {
             // override the theme config if user specifies a `theme` cli argument
             theme.clone_into(&mut configs.app_config.theme);
+            configs.theme_override = Some(theme.clone());
         }
         config::set_config(configs);
     }
+    config::set_active_profile(args.get_one::<String>("profile").map(String::as_str))?;
 
     match args.subcommand() {
         None => {
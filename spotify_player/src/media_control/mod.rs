@@ -1,14 +1,25 @@
-#![allow(unused_imports)]
+// On Linux, the application runs its own MPRIS server, which implements the
+// full specification instead of the subset provided by `souvlaki`.
+#[cfg(target_os = "linux")]
+mod mpris;
+#[cfg(target_os = "linux")]
+pub use mpris::start_event_watcher;
+
+#[cfg(not(target_os = "linux"))]
 use souvlaki::MediaPosition;
+#[cfg(not(target_os = "linux"))]
 use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, PlatformConfig};
 
+#[cfg(not(target_os = "linux"))]
 use crate::utils;
+#[cfg(not(target_os = "linux"))]
 use crate::{
     client::{ClientRequest, PlayerRequest},
     state::SharedState,
     utils::map_join,
 };
 
+#[cfg(not(target_os = "linux"))]
 fn update_control_metadata(
     state: &SharedState,
     controls: &mut MediaControls,
@@ -51,6 +62,7 @@ fn update_control_metadata(
 }
 
 /// Start the application's media control event watcher
+#[cfg(not(target_os = "linux"))]
 pub fn start_event_watcher(
     state: SharedState,
     client_pub: flume::Sender<ClientRequest>,
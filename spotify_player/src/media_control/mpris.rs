@@ -0,0 +1,854 @@
+use anyhow::Result;
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    blocking::{stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged, Connection},
+    channel::{MatchingReceiver, Sender},
+    message::{MatchRule, SignalArgs},
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder};
+use rspotify::{
+    model::{PlayableItem, RepeatState},
+    prelude::Id,
+};
+
+use crate::{
+    client::{ClientRequest, PlayerRequest},
+    state::{AlbumId, ArtistId, ContextId, Playback, PlaylistId, SharedState, TrackId},
+    utils,
+};
+
+const BUS_NAME: &str = "org.mpris.MediaPlayer2.spotify_player";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const TRACKLIST_INTERFACE: &str = "org.mpris.MediaPlayer2.TrackList";
+const PLAYLISTS_INTERFACE: &str = "org.mpris.MediaPlayer2.Playlists";
+/// the track ID representing the absence of a track, defined by the MPRIS specification
+const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
+/// the duration between two consecutive checks for changes of the player's properties
+const REFRESH_DURATION: std::time::Duration = std::time::Duration::from_millis(200);
+/// the minimum difference (in microseconds) between the actual and the expected playback position
+/// for the playback to be considered seeked
+const SEEK_THRESHOLD_US: i64 = 2_000_000;
+
+/// Data shared by the MPRIS interfaces' handlers
+struct MprisData {
+    state: SharedState,
+    client_pub: flume::Sender<ClientRequest>,
+}
+
+#[derive(Debug, PartialEq)]
+/// A snapshot of the MPRIS properties used to compute changes to be signaled
+struct Snapshot {
+    playback_status: &'static str,
+    loop_status: &'static str,
+    shuffle: bool,
+    volume: f64,
+    position_us: i64,
+    tracks: Vec<Path<'static>>,
+    playlist_count: u32,
+    active_playlist: (bool, (Path<'static>, String, String)),
+}
+
+impl MprisData {
+    fn send_player_request(&self, request: PlayerRequest) -> Result<(), MethodErr> {
+        self.client_pub
+            .send(ClientRequest::Player(request))
+            .map_err(|err| MethodErr::failed(&err))
+    }
+
+    fn playback_status(&self) -> &'static str {
+        match self.state.player.read().current_playback() {
+            None => "Stopped",
+            Some(playback) if playback.is_playing => "Playing",
+            Some(_) => "Paused",
+        }
+    }
+
+    fn loop_status(&self) -> &'static str {
+        match self.state.player.read().current_playback() {
+            Some(playback) => match playback.repeat_state {
+                RepeatState::Off => "None",
+                RepeatState::Track => "Track",
+                RepeatState::Context => "Playlist",
+            },
+            None => "None",
+        }
+    }
+
+    fn shuffle(&self) -> bool {
+        self.state
+            .player
+            .read()
+            .current_playback()
+            .map(|playback| playback.shuffle_state)
+            .unwrap_or_default()
+    }
+
+    fn volume(&self) -> f64 {
+        self.state
+            .player
+            .read()
+            .current_playback()
+            .and_then(|playback| playback.device.volume_percent)
+            .map(|volume| volume as f64 / 100.0)
+            .unwrap_or_default()
+    }
+
+    fn position_us(&self) -> i64 {
+        self.state
+            .player
+            .read()
+            .playback_progress()
+            .and_then(|progress| progress.num_microseconds())
+            .unwrap_or_default()
+    }
+
+    /// Get the items in the track list, which consists of the currently playing item
+    /// followed by items in the playback's queue
+    fn tracklist_items(&self) -> Vec<PlayableItem> {
+        let player = self.state.player.read();
+        let current_item = match player.playback.as_ref().and_then(|p| p.item.as_ref()) {
+            Some(item) => item.clone(),
+            None => return Vec::new(),
+        };
+
+        let mut items = vec![current_item];
+        if let Some(queue) = player.queue.as_ref() {
+            items.extend(queue.queue.iter().cloned());
+        }
+        items
+    }
+
+    fn tracks(&self) -> Vec<Path<'static>> {
+        self.tracklist_items()
+            .iter()
+            .enumerate()
+            .map(|(index, item)| track_path(index, item))
+            .collect()
+    }
+
+    fn metadata(&self) -> PropMap {
+        match self.tracklist_items().first() {
+            Some(item) => item_metadata(0, item),
+            None => {
+                let mut metadata = PropMap::new();
+                metadata.insert(
+                    "mpris:trackid".to_string(),
+                    Variant(Box::new(Path::from(NO_TRACK))),
+                );
+                metadata
+            }
+        }
+    }
+
+    fn playlists(&self) -> Vec<(Path<'static>, String, String)> {
+        self.state
+            .data
+            .read()
+            .user_data
+            .playlists
+            .iter()
+            .map(|playlist| {
+                (
+                    playlist_path(&playlist.id),
+                    playlist.name.clone(),
+                    String::new(),
+                )
+            })
+            .collect()
+    }
+
+    fn active_playlist(&self) -> (bool, (Path<'static>, String, String)) {
+        let context_id = self.state.player.read().playing_context_id();
+        if let Some(ContextId::Playlist(id)) = context_id {
+            let path = playlist_path(&id);
+            if let Some(playlist) = self.playlists().into_iter().find(|p| p.0 == path) {
+                return (true, playlist);
+            }
+        }
+        (false, (Path::from("/"), String::new(), String::new()))
+    }
+
+    /// Start a new playback from a Spotify URI
+    fn open_uri(&self, uri: &str) -> Result<(), MethodErr> {
+        let uri = utils::parse_uri(uri);
+        let playback = if let Ok(id) = TrackId::from_uri(&uri) {
+            Playback::URIs(vec![id.into_static()], None)
+        } else if let Ok(id) = AlbumId::from_uri(&uri) {
+            Playback::Context(ContextId::Album(id.into_static()), None)
+        } else if let Ok(id) = ArtistId::from_uri(&uri) {
+            Playback::Context(ContextId::Artist(id.into_static()), None)
+        } else if let Ok(id) = PlaylistId::from_uri(&uri) {
+            Playback::Context(ContextId::Playlist(id.into_static()), None)
+        } else {
+            return Err(MethodErr::invalid_arg(&uri));
+        };
+        self.send_player_request(PlayerRequest::StartPlayback(playback, None))
+    }
+}
+
+impl Snapshot {
+    fn new(data: &MprisData) -> Self {
+        Self {
+            playback_status: data.playback_status(),
+            loop_status: data.loop_status(),
+            shuffle: data.shuffle(),
+            volume: data.volume(),
+            position_us: data.position_us(),
+            tracks: data.tracks(),
+            playlist_count: data.playlists().len() as u32,
+            active_playlist: data.active_playlist(),
+        }
+    }
+
+    /// Construct messages signaling changes between the `prev` snapshot and the current snapshot
+    fn changed_messages(
+        &self,
+        prev: &Self,
+        data: &MprisData,
+        elapsed: std::time::Duration,
+    ) -> Vec<dbus::Message> {
+        let mut messages = vec![];
+        let path = Path::from(OBJECT_PATH);
+
+        let mut player_changes = PropMap::new();
+        let insert = |changes: &mut PropMap, name: &str, value: Box<dyn RefArg>| {
+            changes.insert(name.to_string(), Variant(value));
+        };
+        if self.playback_status != prev.playback_status {
+            insert(
+                &mut player_changes,
+                "PlaybackStatus",
+                Box::new(self.playback_status.to_string()),
+            );
+        }
+        if self.loop_status != prev.loop_status {
+            insert(
+                &mut player_changes,
+                "LoopStatus",
+                Box::new(self.loop_status.to_string()),
+            );
+        }
+        if self.shuffle != prev.shuffle {
+            insert(&mut player_changes, "Shuffle", Box::new(self.shuffle));
+        }
+        if self.volume != prev.volume {
+            insert(&mut player_changes, "Volume", Box::new(self.volume));
+        }
+
+        let is_track_changed = self.tracks.first() != prev.tracks.first();
+        if is_track_changed {
+            insert(&mut player_changes, "Metadata", Box::new(data.metadata()));
+        }
+        if !player_changes.is_empty() {
+            messages.push(properties_changed_message(
+                &path,
+                PLAYER_INTERFACE,
+                player_changes,
+            ));
+        }
+
+        if !is_track_changed {
+            let expected_position_us = prev.position_us
+                + if prev.playback_status == "Playing" {
+                    elapsed.as_micros() as i64
+                } else {
+                    0
+                };
+            if (self.position_us - expected_position_us).abs() > SEEK_THRESHOLD_US {
+                if let Ok(msg) = dbus::Message::new_signal(OBJECT_PATH, PLAYER_INTERFACE, "Seeked")
+                {
+                    messages.push(msg.append1(self.position_us));
+                }
+            }
+        }
+
+        if self.tracks != prev.tracks {
+            let current_track = self
+                .tracks
+                .first()
+                .cloned()
+                .unwrap_or_else(|| Path::from(NO_TRACK));
+            if let Ok(msg) =
+                dbus::Message::new_signal(OBJECT_PATH, TRACKLIST_INTERFACE, "TrackListReplaced")
+            {
+                messages.push(msg.append2(self.tracks.clone(), current_track));
+            }
+        }
+
+        let mut playlists_changes = PropMap::new();
+        if self.playlist_count != prev.playlist_count {
+            insert(
+                &mut playlists_changes,
+                "PlaylistCount",
+                Box::new(self.playlist_count),
+            );
+        }
+        if self.active_playlist != prev.active_playlist {
+            insert(
+                &mut playlists_changes,
+                "ActivePlaylist",
+                Box::new(self.active_playlist.clone()),
+            );
+        }
+        if !playlists_changes.is_empty() {
+            messages.push(properties_changed_message(
+                &path,
+                PLAYLISTS_INTERFACE,
+                playlists_changes,
+            ));
+        }
+
+        messages
+    }
+}
+
+fn properties_changed_message(
+    path: &Path<'static>,
+    interface: &str,
+    changed_properties: PropMap,
+) -> dbus::Message {
+    PropertiesPropertiesChanged {
+        interface_name: interface.to_string(),
+        changed_properties,
+        invalidated_properties: Vec::new(),
+    }
+    .to_emit_message(path)
+}
+
+/// Get the object path of an item in the track list.
+///
+/// The item's position is included in the path because the same track can appear
+/// multiple times in the track list.
+fn track_path(index: usize, item: &PlayableItem) -> Path<'static> {
+    let id = match item {
+        PlayableItem::Track(track) => track
+            .id
+            .as_ref()
+            .map(|id| id.id().to_string())
+            .unwrap_or_else(|| "local".to_string()),
+        PlayableItem::Episode(episode) => episode.id.id().to_string(),
+    };
+    Path::from(format!("/spotify_player/track/{index}_{id}"))
+}
+
+fn playlist_path(id: &PlaylistId) -> Path<'static> {
+    Path::from(format!("/spotify_player/playlist/{}", id.id()))
+}
+
+fn item_metadata(index: usize, item: &PlayableItem) -> PropMap {
+    let mut metadata = PropMap::new();
+    let mut insert = |key: &str, value: Box<dyn RefArg>| {
+        metadata.insert(key.to_string(), Variant(value));
+    };
+
+    insert("mpris:trackid", Box::new(track_path(index, item)));
+    match item {
+        PlayableItem::Track(track) => {
+            insert(
+                "mpris:length",
+                Box::new(track.duration.num_microseconds().unwrap_or_default()),
+            );
+            insert("xesam:title", Box::new(track.name.clone()));
+            insert("xesam:album", Box::new(track.album.name.clone()));
+            insert(
+                "xesam:artist",
+                Box::new(
+                    track
+                        .artists
+                        .iter()
+                        .map(|a| a.name.clone())
+                        .collect::<Vec<_>>(),
+                ),
+            );
+            if let Some(url) = utils::get_track_album_image_url(track) {
+                insert("mpris:artUrl", Box::new(url.to_string()));
+            }
+            if let Some(id) = track.id.as_ref() {
+                insert(
+                    "xesam:url",
+                    Box::new(format!("https://open.spotify.com/track/{}", id.id())),
+                );
+            }
+        }
+        PlayableItem::Episode(episode) => {
+            insert(
+                "mpris:length",
+                Box::new(episode.duration.num_microseconds().unwrap_or_default()),
+            );
+            insert("xesam:title", Box::new(episode.name.clone()));
+            insert("xesam:album", Box::new(episode.show.name.clone()));
+            if let Some(image) = episode.images.first() {
+                insert("mpris:artUrl", Box::new(image.url.clone()));
+            }
+            insert(
+                "xesam:url",
+                Box::new(format!(
+                    "https://open.spotify.com/episode/{}",
+                    episode.id.id()
+                )),
+            );
+        }
+    }
+
+    metadata
+}
+
+fn register_root_interface(b: &mut IfaceBuilder<MprisData>) {
+    b.method("Raise", (), (), |_, _, _: ()| Ok(()));
+    b.method("Quit", (), (), |_, _, _: ()| Ok(()));
+
+    b.property("CanQuit").get(|_, _| Ok(false));
+    b.property("CanRaise").get(|_, _| Ok(false));
+    b.property("HasTrackList").get(|_, _| Ok(true));
+    b.property("Identity")
+        .get(|_, _| Ok("Spotify Player".to_string()));
+    b.property("SupportedUriSchemes")
+        .get(|_, _| Ok(vec!["spotify".to_string()]));
+    b.property("SupportedMimeTypes")
+        .get(|_, _| Ok(Vec::<String>::new()));
+}
+
+fn register_player_interface(b: &mut IfaceBuilder<MprisData>) {
+    b.method("Next", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::NextTrack)
+    });
+    b.method("Previous", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::PreviousTrack)
+    });
+    b.method("Pause", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::Pause)
+    });
+    b.method("PlayPause", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::ResumePause)
+    });
+    b.method("Stop", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::Pause)
+    });
+    b.method("Play", (), (), |_, data: &mut MprisData, _: ()| {
+        data.send_player_request(PlayerRequest::Resume)
+    });
+    b.method(
+        "Seek",
+        ("Offset",),
+        (),
+        |_, data: &mut MprisData, (offset,): (i64,)| {
+            let position_us = std::cmp::max(data.position_us() + offset, 0);
+            data.send_player_request(PlayerRequest::SeekTrack(chrono::Duration::microseconds(
+                position_us,
+            )))
+        },
+    );
+    b.method(
+        "SetPosition",
+        ("TrackId", "Position"),
+        (),
+        |_, data: &mut MprisData, (track_id, position_us): (Path<'static>, i64)| {
+            // the request is ignored if it's stale or the position is invalid
+            if data.tracks().first() != Some(&track_id) || position_us < 0 {
+                return Ok(());
+            }
+            data.send_player_request(PlayerRequest::SeekTrack(chrono::Duration::microseconds(
+                position_us,
+            )))
+        },
+    );
+    b.method(
+        "OpenUri",
+        ("Uri",),
+        (),
+        |_, data: &mut MprisData, (uri,): (String,)| data.open_uri(&uri),
+    );
+    b.signal::<(i64,), _>("Seeked", ("Position",));
+
+    b.property("PlaybackStatus")
+        .get(|_, data: &mut MprisData| Ok(data.playback_status().to_string()));
+    b.property("LoopStatus")
+        .get(|_, data: &mut MprisData| Ok(data.loop_status().to_string()))
+        .set(|_, data: &mut MprisData, loop_status: String| {
+            let repeat_state = match loop_status.as_str() {
+                "None" => RepeatState::Off,
+                "Track" => RepeatState::Track,
+                "Playlist" => RepeatState::Context,
+                _ => return Err(MethodErr::invalid_arg(&loop_status)),
+            };
+            data.send_player_request(PlayerRequest::SetRepeat(repeat_state))?;
+            Ok(None)
+        });
+    b.property("Shuffle")
+        .get(|_, data: &mut MprisData| Ok(data.shuffle()))
+        .set(|_, data: &mut MprisData, shuffle: bool| {
+            data.send_player_request(PlayerRequest::SetShuffle(shuffle))?;
+            Ok(None)
+        });
+    b.property("Volume")
+        .get(|_, data: &mut MprisData| Ok(data.volume()))
+        .set(|_, data: &mut MprisData, volume: f64| {
+            let volume = (volume.clamp(0.0, 1.0) * 100.0).round() as u8;
+            data.send_player_request(PlayerRequest::Volume(volume))?;
+            Ok(None)
+        });
+    // Unlike the MPRIS specification, which defines `Position` as a read-only property,
+    // setting the property seeks the current track to the given position.
+    b.property("Position")
+        .get(|_, data: &mut MprisData| Ok(data.position_us()))
+        .set(|_, data: &mut MprisData, position_us: i64| {
+            data.send_player_request(PlayerRequest::SeekTrack(chrono::Duration::microseconds(
+                std::cmp::max(position_us, 0),
+            )))?;
+            Ok(None)
+        })
+        .emits_changed_false();
+    b.property("Metadata")
+        .get(|_, data: &mut MprisData| Ok(data.metadata()));
+    b.property("Rate").get(|_, _| Ok(1.0));
+    b.property("MinimumRate").get(|_, _| Ok(1.0));
+    b.property("MaximumRate").get(|_, _| Ok(1.0));
+    b.property("CanGoNext").get(|_, _| Ok(true));
+    b.property("CanGoPrevious").get(|_, _| Ok(true));
+    b.property("CanPlay").get(|_, _| Ok(true));
+    b.property("CanPause").get(|_, _| Ok(true));
+    b.property("CanSeek").get(|_, _| Ok(true));
+    b.property("CanControl").get(|_, _| Ok(true));
+}
+
+fn register_tracklist_interface(b: &mut IfaceBuilder<MprisData>) {
+    b.method(
+        "GetTracksMetadata",
+        ("TrackIds",),
+        ("Metadata",),
+        |_, data: &mut MprisData, (track_ids,): (Vec<Path<'static>>,)| {
+            let items = data.tracklist_items();
+            let metadata = track_ids
+                .iter()
+                .filter_map(|track_id| {
+                    items
+                        .iter()
+                        .enumerate()
+                        .find(|(index, item)| track_path(*index, item) == *track_id)
+                        .map(|(index, item)| item_metadata(index, item))
+                })
+                .collect::<Vec<_>>();
+            Ok((metadata,))
+        },
+    );
+    b.method(
+        "AddTrack",
+        ("Uri", "AfterTrack", "SetAsCurrent"),
+        (),
+        // Spotify only supports adding tracks to the end of the queue,
+        // so the `AfterTrack` argument is ignored
+        |_,
+         data: &mut MprisData,
+         (uri, _after_track, set_as_current): (String, Path<'static>, bool)| {
+            let id = TrackId::from_uri(&utils::parse_uri(&uri))
+                .map_err(|_| MethodErr::invalid_arg(&uri))?
+                .into_static();
+            if set_as_current {
+                data.send_player_request(PlayerRequest::StartPlayback(
+                    Playback::URIs(vec![id], None),
+                    None,
+                ))
+            } else {
+                data.client_pub
+                    .send(ClientRequest::AddTrackToQueue(id))
+                    .map_err(|err| MethodErr::failed(&err))
+            }
+        },
+    );
+    b.method(
+        "RemoveTrack",
+        ("TrackId",),
+        (),
+        |_, _: &mut MprisData, (_track_id,): (Path<'static>,)| -> Result<(), MethodErr> {
+            Err(MethodErr::failed(
+                "removing a track from the playback's queue is not supported",
+            ))
+        },
+    );
+    b.method(
+        "GoTo",
+        ("TrackId",),
+        (),
+        |_, data: &mut MprisData, (track_id,): (Path<'static>,)| {
+            let items = data.tracklist_items();
+            let index = match items
+                .iter()
+                .enumerate()
+                .position(|(index, item)| track_path(index, item) == track_id)
+            {
+                // the current track is already playing
+                Some(0) => return Ok(()),
+                Some(index) => index,
+                None => return Err(MethodErr::invalid_arg(&track_id)),
+            };
+
+            // start a new playback from the given track followed by the remaining tracks in the queue
+            let ids = items[index..]
+                .iter()
+                .filter_map(|item| match item {
+                    PlayableItem::Track(track) => track.id.clone(),
+                    PlayableItem::Episode(_) => None,
+                })
+                .collect::<Vec<_>>();
+            data.send_player_request(PlayerRequest::StartPlayback(
+                Playback::URIs(ids, None),
+                None,
+            ))
+        },
+    );
+    b.signal::<(Vec<Path<'static>>, Path<'static>), _>(
+        "TrackListReplaced",
+        ("Tracks", "CurrentTrack"),
+    );
+
+    b.property("Tracks")
+        .get(|_, data: &mut MprisData| Ok(data.tracks()))
+        .emits_changed_invalidates();
+    // tracks can be added but not removed, which isn't editable by the specification's definition
+    b.property("CanEditTracks").get(|_, _| Ok(false));
+}
+
+fn register_playlists_interface(b: &mut IfaceBuilder<MprisData>) {
+    b.method(
+        "ActivatePlaylist",
+        ("PlaylistId",),
+        (),
+        |_, data: &mut MprisData, (playlist_id,): (Path<'static>,)| {
+            let playlist = data
+                .state
+                .data
+                .read()
+                .user_data
+                .playlists
+                .iter()
+                .find(|p| playlist_path(&p.id) == playlist_id)
+                .map(|p| p.id.clone());
+            match playlist {
+                Some(id) => data.send_player_request(PlayerRequest::StartPlayback(
+                    Playback::Context(ContextId::Playlist(id), None),
+                    None,
+                )),
+                None => Err(MethodErr::invalid_arg(&playlist_id)),
+            }
+        },
+    );
+    b.method(
+        "GetPlaylists",
+        ("Index", "MaxCount", "Order", "ReverseOrder"),
+        ("Playlists",),
+        |_, data: &mut MprisData, (index, max_count, order, reverse): (u32, u32, String, bool)| {
+            let mut playlists = data.playlists();
+            // playlists are stored in the user-defined order, which is used for unsupported orderings
+            if order == "Alphabetical" {
+                playlists.sort_by_key(|p| p.1.to_lowercase());
+            }
+            if reverse {
+                playlists.reverse();
+            }
+            let playlists = playlists
+                .into_iter()
+                .skip(index as usize)
+                .take(max_count as usize)
+                .collect::<Vec<_>>();
+            Ok((playlists,))
+        },
+    );
+
+    b.property("PlaylistCount")
+        .get(|_, data: &mut MprisData| Ok(data.playlists().len() as u32));
+    b.property("Orderings")
+        .get(|_, _| Ok(vec!["Alphabetical".to_string(), "User".to_string()]));
+    b.property("ActivePlaylist")
+        .get(|_, data: &mut MprisData| Ok(data.active_playlist()));
+}
+
+/// Start the application's MPRIS server, which handles media control requests
+/// and notifies changes of the player's properties via D-Bus.
+pub fn start_event_watcher(
+    state: SharedState,
+    client_pub: flume::Sender<ClientRequest>,
+) -> Result<()> {
+    tracing::info!("Initializing application's MPRIS server...");
+
+    serve(Connection::new_session()?, state, client_pub)
+}
+
+/// Run the MPRIS server on a D-Bus connection
+fn serve(
+    conn: Connection,
+    state: SharedState,
+    client_pub: flume::Sender<ClientRequest>,
+) -> Result<()> {
+    conn.request_name(BUS_NAME, false, true, false)?;
+
+    let mut cr = Crossroads::new();
+    let root = cr.register(ROOT_INTERFACE, register_root_interface);
+    let player = cr.register(PLAYER_INTERFACE, register_player_interface);
+    let tracklist = cr.register(TRACKLIST_INTERFACE, register_tracklist_interface);
+    let playlists = cr.register(PLAYLISTS_INTERFACE, register_playlists_interface);
+    cr.insert(
+        OBJECT_PATH,
+        &[root, player, tracklist, playlists],
+        MprisData {
+            state: state.clone(),
+            client_pub: client_pub.clone(),
+        },
+    );
+
+    conn.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |msg, conn| {
+            cr.handle_message(msg, conn).unwrap_or_default();
+            true
+        }),
+    );
+
+    // a separate handle to the player's data for computing property changes,
+    // as the data inserted into `Crossroads` is owned by the method call handler
+    let data = MprisData { state, client_pub };
+    let mut prev = Snapshot::new(&data);
+    let mut timer = std::time::Instant::now();
+    loop {
+        conn.process(REFRESH_DURATION)?;
+
+        let elapsed = timer.elapsed();
+        if elapsed < REFRESH_DURATION {
+            continue;
+        }
+        let snapshot = Snapshot::new(&data);
+        for msg in snapshot.changed_messages(&prev, &data, elapsed) {
+            if conn.send(msg).is_err() {
+                tracing::warn!("Failed to send a MPRIS signal");
+            }
+        }
+        prev = snapshot;
+        timer = std::time::Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+    use std::io::BufRead;
+
+    /// A private D-Bus daemon killed when dropped
+    struct DbusDaemon {
+        process: std::process::Child,
+        address: String,
+    }
+
+    impl DbusDaemon {
+        /// Start a private session bus, `None` if `dbus-daemon` is not installed
+        fn start() -> Option<Self> {
+            let mut process = std::process::Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(std::process::Stdio::piped())
+                .stderr(std::process::Stdio::null())
+                .spawn()
+                .ok()?;
+            let mut address = String::new();
+            std::io::BufReader::new(process.stdout.take()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some(Self {
+                process,
+                address: address.trim().to_string(),
+            })
+        }
+
+        fn connect(&self) -> Connection {
+            let mut channel = dbus::channel::Channel::open_private(&self.address).unwrap();
+            channel.register().unwrap();
+            Connection::from(channel)
+        }
+    }
+
+    impl Drop for DbusDaemon {
+        fn drop(&mut self) {
+            self.process.kill().unwrap_or_default();
+        }
+    }
+
+    fn new_state() -> SharedState {
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            let folder =
+                std::env::temp_dir().join(format!("spotify_player-mpris-{}", std::process::id()));
+            std::fs::create_dir_all(&folder).unwrap();
+            crate::config::set_config(crate::config::Configs::new(&folder, &folder).unwrap());
+        });
+        std::sync::Arc::new(crate::state::State::new(false))
+    }
+
+    #[test]
+    fn mpris_server() {
+        let Some(daemon) = DbusDaemon::start() else {
+            eprintln!("skipped: `dbus-daemon` is not installed");
+            return;
+        };
+
+        let (client_pub, client_sub) = flume::unbounded();
+        let server = daemon.connect();
+        let state = new_state();
+        std::thread::spawn(move || serve(server, state, client_pub));
+
+        let conn = daemon.connect();
+        let proxy = conn.with_proxy(BUS_NAME, OBJECT_PATH, std::time::Duration::from_secs(5));
+        // wait for the server to own its bus name
+        let identity = (0..50)
+            .find_map(|_| {
+                let identity = proxy.get::<String>(ROOT_INTERFACE, "Identity").ok();
+                if identity.is_none() {
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+                identity
+            })
+            .expect("the MPRIS server should be running");
+        assert_eq!(identity, "Spotify Player");
+
+        assert_eq!(
+            proxy
+                .get::<String>(PLAYER_INTERFACE, "PlaybackStatus")
+                .unwrap(),
+            "Stopped"
+        );
+        assert!(!proxy
+            .get::<bool>(TRACKLIST_INTERFACE, "CanEditTracks")
+            .unwrap());
+
+        proxy
+            .method_call::<(), _, _, _>(PLAYER_INTERFACE, "PlayPause", ())
+            .unwrap();
+        assert!(matches!(
+            client_sub.recv().unwrap(),
+            ClientRequest::Player(PlayerRequest::ResumePause)
+        ));
+
+        proxy
+            .set(PLAYER_INTERFACE, "LoopStatus", "Track".to_string())
+            .unwrap();
+        assert!(matches!(
+            client_sub.recv().unwrap(),
+            ClientRequest::Player(PlayerRequest::SetRepeat(RepeatState::Track))
+        ));
+        assert!(proxy
+            .set(PLAYER_INTERFACE, "LoopStatus", "Invalid".to_string())
+            .is_err());
+
+        proxy.set(PLAYER_INTERFACE, "Shuffle", true).unwrap();
+        assert!(matches!(
+            client_sub.recv().unwrap(),
+            ClientRequest::Player(PlayerRequest::SetShuffle(true))
+        ));
+
+        assert!(proxy
+            .method_call::<(), _, _, _>(TRACKLIST_INTERFACE, "RemoveTrack", (Path::from(NO_TRACK),))
+            .is_err());
+        assert!(client_sub.is_empty());
+    }
+}
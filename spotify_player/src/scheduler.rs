@@ -0,0 +1,208 @@
+//! A scheduler running the sleep timer and the scheduled actions configured in `app.toml`
+
+use anyhow::{Context as _, Result};
+use rspotify::prelude::*;
+
+use crate::{
+    client::{Client, ClientRequest, PlayerRequest},
+    config::{self, ScheduleConfig, ScheduledAction},
+    state::{
+        AlbumId, ArtistId, ContextId, Playback, PlaylistId, SharedState, SleepTimerMode,
+        SleepTimerSpec,
+    },
+};
+
+/// the duration between two consecutive checks of the sleep timer and the schedules
+const TICK_DURATION: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// the duration over which the playback's volume is faded out before the sleep timer expires
+const FADE_OUT_DURATION_IN_SECS: i64 = 60;
+
+/// the minimum volume change (in percent) when fading out the playback's volume
+const FADE_OUT_VOLUME_STEP: u8 = 5;
+
+pub async fn start_scheduler(client: Client, state: SharedState) {
+    let schedules = &config::get_config().app_config.schedules;
+    let mut interval = tokio::time::interval(TICK_DURATION);
+    // the minute in which schedules were last checked, so each schedule runs at most once a minute
+    let mut last_checked_minute = None;
+
+    loop {
+        interval.tick().await;
+
+        if let Err(err) = update_sleep_timer(&client, &state).await {
+            tracing::warn!("Failed to update the sleep timer: {err:#}");
+        }
+
+        let now = chrono::Local::now();
+        let minute = now.format("%Y-%m-%d %H:%M").to_string();
+        if last_checked_minute.as_ref() == Some(&minute) {
+            continue;
+        }
+        last_checked_minute = Some(minute);
+
+        for schedule in schedules.iter().filter(|s| is_due(s, now)) {
+            tracing::info!("Running the scheduled action: {schedule:?}");
+            if let Err(err) = run_scheduled_action(&client, &state, &schedule.action).await {
+                tracing::warn!("Failed to run the scheduled action {schedule:?}: {err:#}");
+            }
+        }
+    }
+}
+
+/// Check if a schedule is due in the minute of `now`
+fn is_due(schedule: &ScheduleConfig, now: chrono::DateTime<chrono::Local>) -> bool {
+    use chrono::{Datelike, Timelike};
+
+    match schedule.time() {
+        Ok(time) => {
+            time.hour() == now.hour()
+                && time.minute() == now.minute()
+                && schedule.runs_on(now.weekday())
+        }
+        Err(_) => false,
+    }
+}
+
+/// Fade out the playback's volume over the last minute of the sleep timer,
+/// then pause the playback and restore its volume when the timer expires
+async fn update_sleep_timer(client: &Client, state: &SharedState) -> Result<()> {
+    let Some(timer) = state.player.read().sleep_timer.clone() else {
+        return Ok(());
+    };
+
+    // the timer's remaining time depends on its context's tracks, which may not be retrieved yet
+    if let SleepTimerMode::EndOfContext(ref id) = timer.mode {
+        if state.data.read().context_tracks(id).is_none() {
+            client
+                .handle_request(state, ClientRequest::GetContext(id.clone()))
+                .await?;
+        }
+    }
+    let Some(remaining) = state.sleep_timer_remaining() else {
+        return Ok(());
+    };
+
+    // the playback is paused within the last tick, so the next track isn't started
+    if remaining.num_milliseconds() < TICK_DURATION.as_millis() as i64 {
+        tracing::info!("The sleep timer expired, pausing the playback");
+        state.player.write().sleep_timer = None;
+        state.render.notify();
+        client
+            .handle_request(state, ClientRequest::Player(PlayerRequest::Pause))
+            .await?;
+        if let Some(volume) = timer.volume_before_fade {
+            client
+                .handle_request(state, ClientRequest::Player(PlayerRequest::Volume(volume)))
+                .await?;
+        }
+        return Ok(());
+    }
+
+    if remaining.num_seconds() >= FADE_OUT_DURATION_IN_SECS {
+        return Ok(());
+    }
+    let Some(volume) = state
+        .player
+        .read()
+        .buffered_playback
+        .as_ref()
+        .and_then(|p| p.volume)
+    else {
+        return Ok(());
+    };
+    let volume = volume.min(100) as u8;
+    let volume_before_fade = timer.volume_before_fade.unwrap_or(volume);
+    if let Some(timer) = state.player.write().sleep_timer.as_mut() {
+        timer.volume_before_fade = Some(volume_before_fade);
+    }
+
+    let target_volume =
+        (i64::from(volume_before_fade) * remaining.num_seconds() / FADE_OUT_DURATION_IN_SECS) as u8;
+    if volume >= target_volume + FADE_OUT_VOLUME_STEP {
+        client
+            .handle_request(
+                state,
+                ClientRequest::Player(PlayerRequest::Volume(target_volume)),
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+async fn run_scheduled_action(
+    client: &Client,
+    state: &SharedState,
+    action: &ScheduledAction,
+) -> Result<()> {
+    match action {
+        ScheduledAction::StartContext {
+            context,
+            device,
+            shuffle,
+        } => {
+            let context_id = resolve_context(state, context)?;
+            let device_id = match device {
+                None => None,
+                Some(device) => Some(
+                    client
+                        .device()
+                        .await?
+                        .into_iter()
+                        .find(|d| d.name == *device || d.id.as_ref() == Some(device))
+                        .and_then(|d| d.id)
+                        .with_context(|| format!("no device with name or id={device} found"))?,
+                ),
+            };
+
+            client
+                .start_playback(Playback::Context(context_id, None), device_id.as_deref())
+                .await?;
+            client.shuffle(*shuffle, device_id.as_deref()).await?;
+            client.update_playback(state);
+        }
+        ScheduledAction::Pause => {
+            client
+                .handle_request(state, ClientRequest::Player(PlayerRequest::Pause))
+                .await?;
+        }
+        ScheduledAction::Resume => {
+            client
+                .handle_request(state, ClientRequest::Player(PlayerRequest::Resume))
+                .await?;
+        }
+        ScheduledAction::SleepTimer { minutes } => {
+            let volume = state
+                .set_sleep_timer(Some(SleepTimerSpec::Duration(minutes.saturating_mul(60))))?;
+            if let Some(volume) = volume {
+                client
+                    .handle_request(state, ClientRequest::Player(PlayerRequest::Volume(volume)))
+                    .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolve a context from its URI or the name of a user's playlist
+fn resolve_context(state: &SharedState, context: &str) -> Result<ContextId> {
+    if let Ok(id) = PlaylistId::from_uri(context) {
+        return Ok(ContextId::Playlist(id.into_static()));
+    }
+    if let Ok(id) = AlbumId::from_uri(context) {
+        return Ok(ContextId::Album(id.into_static()));
+    }
+    if let Ok(id) = ArtistId::from_uri(context) {
+        return Ok(ContextId::Artist(id.into_static()));
+    }
+
+    state
+        .data
+        .read()
+        .user_data
+        .playlists
+        .iter()
+        .find(|p| p.name.eq_ignore_ascii_case(context))
+        .map(|p| ContextId::Playlist(p.id.clone()))
+        .with_context(|| format!("no playlist with name={context} found"))
+}
@@ -0,0 +1,690 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{self, ScrobblerService},
+    state::{load_data_from_file_cache, store_data_into_file_cache, FileCacheKey, PlayRecord},
+};
+
+/// the minimum duration of a track to be scrobbled
+const MIN_TRACK_DURATION_MS: u64 = 30_000;
+/// a track is scrobbled once it has been played for half of its duration or for this amount of time
+const MAX_SCROBBLE_THRESHOLD_MS: u64 = 4 * 60 * 1000;
+/// the duration between two consecutive attempts to submit queued scrobbles
+const RETRY_DURATION: std::time::Duration = std::time::Duration::from_secs(60);
+/// the maximum number of listens submitted to ListenBrainz in a single request
+const MAX_LISTENS_PER_REQUEST: usize = 100;
+/// the maximum number of scrobbles submitted to Last.fm in a single request
+const MAX_LASTFM_SCROBBLES_PER_REQUEST: usize = 50;
+/// the timeout of a request to the scrobbling server
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+/// the timeout of connecting to the scrobbling server
+const CONNECT_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+/// [Last.fm error codes](https://www.last.fm/api/errorcodes) after which a request can be retried:
+/// temporary errors (8, 11, 16, 29) and authentication errors (4, 9, 10, 13, 26),
+/// which are resolved by re-authenticating or updating the scrobbler configurations
+const LASTFM_RETRYABLE_ERRORS: [u64; 9] = [4, 8, 9, 10, 11, 13, 16, 26, 29];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A listen of a track
+pub struct Listen {
+    /// the time (in UNIX timestamp) that the track started playing
+    pub listened_at: i64,
+    pub track: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    pub track_uri: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug)]
+/// An error of a submission permanently rejected by the scrobbling server (e.g. invalid listens),
+/// which shouldn't be retried
+struct RejectedError(String);
+
+impl std::fmt::Display for RejectedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for RejectedError {}
+
+/// Create an error of a failed request, which is a `RejectedError` if the request shouldn't be retried
+fn request_error(msg: String, is_retryable: bool) -> anyhow::Error {
+    if is_retryable {
+        anyhow::anyhow!(msg)
+    } else {
+        RejectedError(msg).into()
+    }
+}
+
+/// Check if a request failed with a status can be retried, i.e. a server error,
+/// a rate limit, a timeout or an authentication error (e.g. an invalid token to be updated)
+fn is_retryable_status(status: reqwest::StatusCode) -> bool {
+    use reqwest::StatusCode;
+    status.is_server_error()
+        || matches!(
+            status,
+            StatusCode::UNAUTHORIZED
+                | StatusCode::FORBIDDEN
+                | StatusCode::REQUEST_TIMEOUT
+                | StatusCode::TOO_MANY_REQUESTS
+        )
+}
+
+#[derive(Debug)]
+pub enum ScrobblerRequest {
+    NowPlaying(Listen),
+    Scrobble(Listen),
+    /// the active profile has been switched
+    SwitchProfile,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ListenType {
+    Single,
+    PlayingNow,
+    Import,
+}
+
+struct Scrobbler {
+    http: reqwest::Client,
+    config: config::ScrobblerConfig,
+    /// the cache folder of the profile the scrobbler is created for, which stores the queue
+    cache_folder: PathBuf,
+    /// listens that haven't been successfully submitted yet
+    queue: Vec<Listen>,
+    /// the Last.fm session key, authenticated on the first submission
+    lastfm_session_key: Option<String>,
+}
+
+impl From<&PlayRecord> for Listen {
+    fn from(record: &PlayRecord) -> Self {
+        Self {
+            listened_at: record.started_at.timestamp(),
+            track: record.track.clone(),
+            artists: record.artists.clone(),
+            album: record.album.clone(),
+            track_uri: record.track_uri.clone(),
+            duration_ms: record.duration_ms,
+        }
+    }
+}
+
+impl Listen {
+    /// the listen's payload based on the ListenBrainz's API
+    fn payload(&self, ty: ListenType) -> serde_json::Value {
+        let mut additional_info = serde_json::json!({
+            "duration_ms": self.duration_ms,
+            "artist_names": self.artists,
+            "media_player": "spotify_player",
+            "submission_client": "spotify_player",
+            "submission_client_version": env!("CARGO_PKG_VERSION"),
+            "music_service": "spotify.com",
+        });
+        if let Some(id) = self.track_uri.strip_prefix("spotify:track:") {
+            additional_info["spotify_id"] = format!("https://open.spotify.com/track/{id}").into();
+        }
+
+        let mut payload = serde_json::json!({
+            "track_metadata": {
+                "artist_name": self.artists.join(", "),
+                "track_name": self.track,
+                "release_name": self.album,
+                "additional_info": additional_info,
+            }
+        });
+        // a "playing now" listen must not have a `listened_at` field
+        if !matches!(ty, ListenType::PlayingNow) {
+            payload["listened_at"] = self.listened_at.into();
+        }
+        payload
+    }
+
+    /// the listen's parameters based on the Last.fm's API.
+    ///
+    /// A scrobble's parameters are indexed by its position `i` in the submitted batch,
+    /// while a "now playing" update's parameters (`i` is `None`) have no index nor timestamp.
+    fn lastfm_params(&self, i: Option<usize>) -> Vec<(String, String)> {
+        let key = |name: &str| match i {
+            Some(i) => format!("{name}[{i}]"),
+            None => name.to_string(),
+        };
+
+        // Last.fm matches tracks by their main artist
+        let mut params = vec![
+            (
+                key("artist"),
+                self.artists.first().cloned().unwrap_or_default(),
+            ),
+            (key("track"), self.track.clone()),
+            (key("duration"), (self.duration_ms / 1000).to_string()),
+        ];
+        if !self.album.is_empty() {
+            params.push((key("album"), self.album.clone()));
+        }
+        if i.is_some() {
+            params.push((key("timestamp"), self.listened_at.to_string()));
+        }
+        params
+    }
+}
+
+impl ListenType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Single => "single",
+            Self::PlayingNow => "playing_now",
+            Self::Import => "import",
+        }
+    }
+}
+
+/// Check if a play record satisfies the requirements to be scrobbled, which is
+/// the track has been played for at least half of its duration or for 4 minutes.
+pub fn should_scrobble(record: &PlayRecord) -> bool {
+    record.duration_ms >= MIN_TRACK_DURATION_MS
+        && (record.played_ms * 2 >= record.duration_ms
+            || record.played_ms >= MAX_SCROBBLE_THRESHOLD_MS)
+}
+
+impl Scrobbler {
+    fn new(config: config::ScrobblerConfig, cache_folder: PathBuf) -> Self {
+        let queue = load_data_from_file_cache(FileCacheKey::ScrobbleQueue, &cache_folder)
+            .unwrap_or_default();
+
+        Self {
+            // like `reqwest::Client::new`, building the client only fails if the TLS backend cannot be initialized
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .connect_timeout(CONNECT_TIMEOUT)
+                .build()
+                .expect("build the scrobbler's HTTP client"),
+            config,
+            cache_folder,
+            queue,
+            lastfm_session_key: None,
+        }
+    }
+
+    /// Create a scrobbler for the active profile, `None` if the profile doesn't enable scrobbling
+    fn from_active_profile() -> Option<Self> {
+        let configs = config::get_config();
+        let config = configs.scrobbler_config()?.clone();
+        tracing::info!(
+            "Starting the application's scrobbler (service={:?}, api_url={})",
+            config.service,
+            config.api_url()
+        );
+        Some(Self::new(config, configs.profile_cache_folder()))
+    }
+
+    fn max_listens_per_request(&self) -> usize {
+        match self.config.service {
+            ScrobblerService::ListenBrainz => MAX_LISTENS_PER_REQUEST,
+            ScrobblerService::LastFm => MAX_LASTFM_SCROBBLES_PER_REQUEST,
+        }
+    }
+
+    /// Submit listens to the scrobbling server
+    async fn submit(&mut self, ty: ListenType, listens: &[Listen]) -> Result<()> {
+        match self.config.service {
+            ScrobblerService::ListenBrainz => self.submit_listenbrainz(ty, listens).await,
+            ScrobblerService::LastFm => {
+                let result = self.submit_lastfm(ty, listens).await;
+                if result.is_err() {
+                    // re-authenticate on the next submission in case the session key is no longer valid
+                    self.lastfm_session_key = None;
+                }
+                result
+            }
+        }
+    }
+
+    async fn submit_listenbrainz(&self, ty: ListenType, listens: &[Listen]) -> Result<()> {
+        let body = serde_json::json!({
+            "listen_type": ty.as_str(),
+            "payload": listens.iter().map(|l| l.payload(ty)).collect::<Vec<_>>(),
+        });
+
+        let response = self
+            .http
+            .post(format!(
+                "{}/1/submit-listens",
+                self.config.api_url().trim_end_matches('/')
+            ))
+            .header(
+                reqwest::header::AUTHORIZATION,
+                format!("Token {}", self.config.token),
+            )
+            .json(&body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(request_error(
+                format!(
+                    "failed to submit listens, status={status}, response={}",
+                    response.text().await.unwrap_or_default()
+                ),
+                is_retryable_status(status),
+            ));
+        }
+        Ok(())
+    }
+
+    async fn submit_lastfm(&mut self, ty: ListenType, listens: &[Listen]) -> Result<()> {
+        let session_key = match self.lastfm_session_key.clone() {
+            Some(key) => key,
+            None => {
+                let key = self.authenticate_lastfm().await?;
+                self.lastfm_session_key = Some(key.clone());
+                key
+            }
+        };
+
+        let mut params = match ty {
+            ListenType::PlayingNow => {
+                let mut params = vec![("method".to_string(), "track.updateNowPlaying".to_string())];
+                params.extend(listens.iter().flat_map(|l| l.lastfm_params(None)));
+                params
+            }
+            ListenType::Single | ListenType::Import => {
+                let mut params = vec![("method".to_string(), "track.scrobble".to_string())];
+                params.extend(
+                    listens
+                        .iter()
+                        .enumerate()
+                        .flat_map(|(i, l)| l.lastfm_params(Some(i))),
+                );
+                params
+            }
+        };
+        params.push(("sk".to_string(), session_key));
+
+        self.call_lastfm(params).await?;
+        Ok(())
+    }
+
+    /// Authenticate the Last.fm user, return the session key
+    async fn authenticate_lastfm(&self) -> Result<String> {
+        let response = self
+            .call_lastfm(vec![
+                ("method".to_string(), "auth.getMobileSession".to_string()),
+                ("username".to_string(), self.config.username.clone()),
+                ("password".to_string(), self.config.password.clone()),
+            ])
+            .await?;
+        match response["session"]["key"].as_str() {
+            Some(key) => Ok(key.to_string()),
+            None => anyhow::bail!("failed to authenticate the Last.fm user, response={response}"),
+        }
+    }
+
+    /// Call a method of the Last.fm API, signing its parameters with the API secret
+    async fn call_lastfm(&self, mut params: Vec<(String, String)>) -> Result<serde_json::Value> {
+        params.push(("api_key".to_string(), self.config.api_key.clone()));
+        params.sort();
+        let mut signature = params.iter().fold(String::new(), |mut acc, (k, v)| {
+            acc.push_str(k);
+            acc.push_str(v);
+            acc
+        });
+        signature.push_str(&self.config.api_secret);
+        params.push((
+            "api_sig".to_string(),
+            format!("{:x}", md5::compute(signature)),
+        ));
+        params.push(("format".to_string(), "json".to_string()));
+
+        let response = self
+            .http
+            .post(format!("{}/", self.config.api_url().trim_end_matches('/')))
+            .form(&params)
+            .send()
+            .await?;
+
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        let body = serde_json::from_str::<serde_json::Value>(&text).unwrap_or_default();
+        if !status.is_success() || body.get("error").is_some() {
+            let is_retryable = match body["error"].as_u64() {
+                Some(code) => LASTFM_RETRYABLE_ERRORS.contains(&code),
+                None => is_retryable_status(status),
+            };
+            return Err(request_error(
+                format!("failed to call the Last.fm API, status={status}, response={text}"),
+                is_retryable,
+            ));
+        }
+        Ok(body)
+    }
+
+    /// Submit queued listens to the scrobbling server.
+    ///
+    /// Listens that fail to be submitted are kept in the queue to be retried later,
+    /// unless they are permanently rejected by the server.
+    async fn submit_queued_listens(&mut self) {
+        while !self.queue.is_empty() {
+            let n = std::cmp::min(self.queue.len(), self.max_listens_per_request());
+            let ty = if n == 1 {
+                ListenType::Single
+            } else {
+                ListenType::Import
+            };
+            let listens = self.queue[..n].to_vec();
+            match self.submit(ty, &listens).await {
+                Ok(()) => tracing::info!("Successfully scrobbled {n} listens"),
+                // rejected listens are dropped, so they don't block the following listens
+                Err(err) if err.is::<RejectedError>() => {
+                    tracing::error!(
+                        "Dropped {n} listens rejected by the scrobbling server: {err:#}"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        "Failed to scrobble {} queued listens, will retry later: {err:#}",
+                        self.queue.len()
+                    );
+                    break;
+                }
+            }
+            self.queue.drain(..n);
+        }
+
+        if let Err(err) =
+            store_data_into_file_cache(FileCacheKey::ScrobbleQueue, &self.cache_folder, &self.queue)
+        {
+            tracing::error!("Failed to store the scrobble queue into the cache folder: {err:#}");
+        }
+    }
+
+    async fn handle_request(&mut self, request: ScrobblerRequest) {
+        match request {
+            ScrobblerRequest::NowPlaying(listen) => {
+                if let Err(err) = self.submit(ListenType::PlayingNow, &[listen]).await {
+                    tracing::warn!("Failed to submit a \"now playing\" listen: {err:#}");
+                }
+            }
+            ScrobblerRequest::Scrobble(listen) => {
+                self.queue.push(listen);
+                self.submit_queued_listens().await;
+            }
+            // handled by the scrobbler task, which re-creates the scrobbler
+            ScrobblerRequest::SwitchProfile => {}
+        }
+    }
+}
+
+/// Start the application's scrobbler, which submits listens to ListenBrainz or Last.fm.
+///
+/// The scrobbler is re-created whenever the active profile is switched, so that listens are
+/// submitted with the profile's scrobbler configurations and queued in the profile's cache folder.
+pub async fn start_scrobbler(scrobbler_sub: flume::Receiver<ScrobblerRequest>) {
+    let mut scrobbler = Scrobbler::from_active_profile();
+    if let Some(scrobbler) = scrobbler.as_mut() {
+        scrobbler.submit_queued_listens().await;
+    }
+
+    loop {
+        match tokio::time::timeout(RETRY_DURATION, scrobbler_sub.recv_async()).await {
+            Ok(Ok(ScrobblerRequest::SwitchProfile)) => {
+                // flush the old profile's queue before switching to the new profile
+                if let Some(scrobbler) = scrobbler.as_mut() {
+                    scrobbler.submit_queued_listens().await;
+                }
+                scrobbler = Scrobbler::from_active_profile();
+                if let Some(scrobbler) = scrobbler.as_mut() {
+                    scrobbler.submit_queued_listens().await;
+                }
+            }
+            Ok(Ok(request)) => {
+                if let Some(scrobbler) = scrobbler.as_mut() {
+                    tracing::info!("Got a scrobbler request: {request:?}");
+                    scrobbler.handle_request(request).await;
+                }
+            }
+            Ok(Err(_)) => break,
+            Err(_) => {
+                // no new request within the retry duration, retry submitting queued listens
+                if let Some(scrobbler) = scrobbler.as_mut() {
+                    if !scrobbler.queue.is_empty() {
+                        scrobbler.submit_queued_listens().await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// A request received by the mock server
+    struct MockRequest {
+        head: String,
+        body: String,
+    }
+
+    /// Start a mock HTTP server replying to consecutive requests with `responses`,
+    /// return the server's URL and a channel of the received requests
+    async fn start_mock_server(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, flume::Receiver<MockRequest>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (tx, rx) = flume::unbounded();
+
+        tokio::spawn(async move {
+            for (status, body) in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+
+                let mut buf = vec![];
+                let head_len = loop {
+                    let mut chunk = [0; 1024];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                    if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break i + 4;
+                    }
+                };
+                let head = String::from_utf8_lossy(&buf[..head_len]).to_lowercase();
+                let content_length = head
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length:"))
+                    .map_or(0, |l| l.trim().parse::<usize>().unwrap());
+                while buf.len() < head_len + content_length {
+                    let mut chunk = [0; 1024];
+                    let n = stream.read(&mut chunk).await.unwrap();
+                    buf.extend_from_slice(&chunk[..n]);
+                }
+
+                tx.send(MockRequest {
+                    head,
+                    body: String::from_utf8_lossy(&buf[head_len..]).into_owned(),
+                })
+                .unwrap();
+
+                let response = format!(
+                    "HTTP/1.1 {status} Mock\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        (url, rx)
+    }
+
+    fn test_scrobbler(config: config::ScrobblerConfig, name: &str) -> Scrobbler {
+        let cache_folder = std::env::temp_dir().join(format!(
+            "spotify_player-scrobbler-{name}-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&cache_folder).unwrap();
+        let _ = std::fs::remove_file(cache_folder.join("ScrobbleQueue_cache.json"));
+
+        let mut scrobbler = Scrobbler::new(config, cache_folder);
+        scrobbler.http = reqwest::Client::builder().no_proxy().build().unwrap();
+        scrobbler
+    }
+
+    fn test_config(service: ScrobblerService, api_url: String) -> config::ScrobblerConfig {
+        config::ScrobblerConfig {
+            service,
+            api_url: Some(api_url),
+            token: "token".to_string(),
+            api_key: "key".to_string(),
+            api_secret: "secret".to_string(),
+            username: "user".to_string(),
+            password: "password".to_string(),
+        }
+    }
+
+    fn test_listen() -> Listen {
+        Listen {
+            listened_at: 1_700_000_000,
+            track: "Track".to_string(),
+            artists: vec!["Artist".to_string(), "Featured".to_string()],
+            album: "Album".to_string(),
+            track_uri: "spotify:track:abc".to_string(),
+            duration_ms: 200_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn listenbrainz_submission_is_retried() {
+        let (url, requests) =
+            start_mock_server(vec![(500, "{}"), (200, r#"{"status":"ok"}"#)]).await;
+        let mut scrobbler = test_scrobbler(
+            test_config(ScrobblerService::ListenBrainz, url),
+            "listenbrainz",
+        );
+
+        // the first submission fails, the listen is queued and stored into the cache folder
+        scrobbler
+            .handle_request(ScrobblerRequest::Scrobble(test_listen()))
+            .await;
+        assert_eq!(scrobbler.queue.len(), 1);
+        let stored: Vec<Listen> =
+            load_data_from_file_cache(FileCacheKey::ScrobbleQueue, &scrobbler.cache_folder)
+                .unwrap();
+        assert_eq!(stored.len(), 1);
+
+        // the retry succeeds
+        scrobbler.submit_queued_listens().await;
+        assert!(scrobbler.queue.is_empty());
+
+        for _ in 0..2 {
+            let request = requests.recv_async().await.unwrap();
+            assert!(request.head.starts_with("post /1/submit-listens "));
+            assert!(request.head.contains("authorization: token token\r\n"));
+
+            let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+            assert_eq!(body["listen_type"], "single");
+            assert_eq!(body["payload"][0]["listened_at"], 1_700_000_000);
+            assert_eq!(
+                body["payload"][0]["track_metadata"]["artist_name"],
+                "Artist, Featured"
+            );
+            assert_eq!(body["payload"][0]["track_metadata"]["track_name"], "Track");
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_listens_are_dropped() {
+        let (url, requests) = start_mock_server(vec![
+            (400, r#"{"code":400,"error":"invalid listen"}"#),
+            (200, r#"{"status":"ok"}"#),
+            (200, r#"{"error":6,"message":"Invalid parameters"}"#),
+        ])
+        .await;
+        let mut scrobbler = test_scrobbler(
+            test_config(ScrobblerService::ListenBrainz, url.clone()),
+            "rejected",
+        );
+
+        // a rejected listen doesn't block the following listens
+        scrobbler
+            .handle_request(ScrobblerRequest::Scrobble(test_listen()))
+            .await;
+        assert!(scrobbler.queue.is_empty());
+        scrobbler
+            .handle_request(ScrobblerRequest::Scrobble(test_listen()))
+            .await;
+        assert!(scrobbler.queue.is_empty());
+
+        let mut scrobbler = test_scrobbler(test_config(ScrobblerService::LastFm, url), "rejected");
+        scrobbler.lastfm_session_key = Some("session".to_string());
+        scrobbler
+            .handle_request(ScrobblerRequest::Scrobble(test_listen()))
+            .await;
+        assert!(scrobbler.queue.is_empty());
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn lastfm_submission_is_signed_and_retried() {
+        let (url, requests) = start_mock_server(vec![
+            (200, r#"{"session":{"name":"user","key":"session"}}"#),
+            (200, r#"{"error":11,"message":"Service Offline"}"#),
+            (200, r#"{"session":{"name":"user","key":"session"}}"#),
+            (200, r#"{"scrobbles":{}}"#),
+        ])
+        .await;
+        let mut scrobbler = test_scrobbler(test_config(ScrobblerService::LastFm, url), "lastfm");
+
+        scrobbler
+            .handle_request(ScrobblerRequest::Scrobble(test_listen()))
+            .await;
+        assert_eq!(scrobbler.queue.len(), 1);
+        scrobbler.submit_queued_listens().await;
+        assert!(scrobbler.queue.is_empty());
+
+        let mut methods = vec![];
+        for _ in 0..4 {
+            let request = requests.recv_async().await.unwrap();
+            let url = reqwest::Url::parse(&format!("http://localhost/?{}", request.body)).unwrap();
+            let mut params = url
+                .query_pairs()
+                .into_owned()
+                .collect::<std::collections::BTreeMap<_, _>>();
+
+            // the signature is the MD5 hash of the sorted parameters followed by the secret
+            let api_sig = params.remove("api_sig").unwrap();
+            assert_eq!(params.remove("format").as_deref(), Some("json"));
+            let signature = params
+                .iter()
+                .map(|(k, v)| format!("{k}{v}"))
+                .collect::<String>();
+            assert_eq!(api_sig, format!("{:x}", md5::compute(signature + "secret")));
+            assert_eq!(params["api_key"], "key");
+
+            if params["method"] == "track.scrobble" {
+                assert_eq!(params["sk"], "session");
+                assert_eq!(params["artist[0]"], "Artist");
+                assert_eq!(params["track[0]"], "Track");
+                assert_eq!(params["album[0]"], "Album");
+                assert_eq!(params["duration[0]"], "200");
+                assert_eq!(params["timestamp[0]"], "1700000000");
+            }
+            methods.push(params["method"].clone());
+        }
+        // the user is re-authenticated after a failed submission
+        assert_eq!(
+            methods,
+            [
+                "auth.getMobileSession",
+                "track.scrobble",
+                "auth.getMobileSession",
+                "track.scrobble"
+            ]
+        );
+    }
+}
@@ -0,0 +1,594 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context as _, Result};
+use rspotify::{
+    model::{PlaylistId, TrackId},
+    prelude::*,
+};
+
+use crate::{
+    client::Client,
+    config,
+    state::{Context, Edit, LibraryIndex, SharedState, State, Track},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+/// A smart playlist's filter expression over a track's fields
+pub enum Filter {
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+    Liked,
+    Explicit,
+    /// the track is in any of the user's (non-smart) playlists
+    InPlaylist,
+    Compare(Field, Op, Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Name,
+    Artist,
+    Album,
+    /// the name of a user's (non-smart) playlist containing the track
+    Playlist,
+    Year,
+    Duration,
+    AddedAt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    NotContains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(u64),
+    /// a duration in seconds
+    Duration(u64),
+    /// a point of time, represented as a UNIX timestamp
+    Time(u64),
+    /// a point of time relative to now, represented as a duration in seconds
+    Ago(u64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    Op(Op),
+    Word(String),
+    Quoted(String),
+}
+
+/// A track being matched against a smart playlist's filter
+struct Candidate<'a> {
+    track: &'a Track,
+    liked: bool,
+    /// names of the user's (non-smart) playlists containing the track
+    playlists: Vec<&'a str>,
+}
+
+impl Filter {
+    /// Parse a filter expression, e.g. `liked and year >= 2010 and duration < 4m`.
+    ///
+    /// An expression combines predicates using `and`, `or`, `not` and parentheses. A predicate is either
+    /// a boolean field (`liked`, `explicit`, `in_playlist`) or a comparison `{field} {op} {value}`:
+    /// - `name`, `artist`, `album`, `playlist` with `==`, `!=`, `~` (contains) or `!~` (not contains)
+    ///   and a (quoted) text value
+    /// - `year` with a number
+    /// - `duration` with a duration, e.g. `3m30s`
+    /// - `added_at` with a date (e.g. `2024-01-31`) or a relative time (e.g. `30d ago`)
+    pub fn parse(expr: &str) -> Result<Self> {
+        let tokens = tokenize(expr)?;
+        let mut pos = 0;
+        let filter = parse_or(&tokens, &mut pos)?;
+        if let Some(token) = tokens.get(pos) {
+            anyhow::bail!("unexpected token {token:?} in filter `{expr}`");
+        }
+        Ok(filter)
+    }
+
+    fn matches(&self, c: &Candidate) -> bool {
+        match self {
+            Self::And(x, y) => x.matches(c) && y.matches(c),
+            Self::Or(x, y) => x.matches(c) || y.matches(c),
+            Self::Not(x) => !x.matches(c),
+            Self::Liked => c.liked,
+            Self::Explicit => c.track.explicit,
+            Self::InPlaylist => !c.playlists.is_empty(),
+            Self::Compare(field, op, value) => match field {
+                Field::Name => compare_texts(std::iter::once(c.track.name.as_str()), *op, value),
+                Field::Artist => {
+                    compare_texts(c.track.artists.iter().map(|a| a.name.as_str()), *op, value)
+                }
+                Field::Album => {
+                    compare_texts(c.track.album.iter().map(|a| a.name.as_str()), *op, value)
+                }
+                Field::Playlist => compare_texts(c.playlists.iter().copied(), *op, value),
+                Field::Year => {
+                    let year = c
+                        .track
+                        .album
+                        .as_ref()
+                        .and_then(|a| a.release_date.get(..4))
+                        .and_then(|y| y.parse::<u64>().ok());
+                    match (year, value) {
+                        (Some(year), Value::Number(n)) => compare(year, *op, *n),
+                        _ => false,
+                    }
+                }
+                Field::Duration => match value {
+                    Value::Duration(d) => compare(c.track.duration.as_secs(), *op, *d),
+                    _ => false,
+                },
+                Field::AddedAt => {
+                    // tracks whose added time is unknown never match
+                    if c.track.added_at == 0 {
+                        return false;
+                    }
+                    let time = match value {
+                        Value::Time(t) => *t,
+                        Value::Ago(d) => (chrono::Utc::now().timestamp() as u64).saturating_sub(*d),
+                        _ => return false,
+                    };
+                    compare(c.track.added_at, *op, time)
+                }
+            },
+        }
+    }
+}
+
+fn compare(x: u64, op: Op, y: u64) -> bool {
+    match op {
+        Op::Eq => x == y,
+        Op::Ne => x != y,
+        Op::Lt => x < y,
+        Op::Le => x <= y,
+        Op::Gt => x > y,
+        Op::Ge => x >= y,
+        Op::Contains | Op::NotContains => false,
+    }
+}
+
+/// Compare a list of texts with a text value (case-insensitive).
+/// A positive comparison (`==`, `~`) matches if any text matches,
+/// a negative comparison (`!=`, `!~`) matches if no text matches the positive comparison.
+fn compare_texts<'a>(texts: impl Iterator<Item = &'a str>, op: Op, value: &Value) -> bool {
+    let value = match value {
+        Value::Text(v) => v.to_lowercase(),
+        _ => return false,
+    };
+    let mut texts = texts.map(|t| t.to_lowercase());
+    match op {
+        Op::Eq => texts.any(|t| t == value),
+        Op::Ne => !texts.any(|t| t == value),
+        Op::Contains => texts.any(|t| t.contains(&value)),
+        Op::NotContains => !texts.any(|t| t.contains(&value)),
+        _ => false,
+    }
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::LParen,
+            ')' => Token::RParen,
+            '"' => {
+                let mut s = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => s.push(c),
+                        None => anyhow::bail!("unterminated quoted text in filter `{expr}`"),
+                    }
+                }
+                Token::Quoted(s)
+            }
+            '=' | '!' | '<' | '>' | '~' => {
+                let next = chars.peek().copied();
+                let op = match (c, next) {
+                    ('=', Some('=')) => Op::Eq,
+                    ('!', Some('=')) => Op::Ne,
+                    ('!', Some('~')) => Op::NotContains,
+                    ('<', Some('=')) => Op::Le,
+                    ('>', Some('=')) => Op::Ge,
+                    ('<', _) => Op::Lt,
+                    ('>', _) => Op::Gt,
+                    ('~', _) => Op::Contains,
+                    _ => anyhow::bail!("invalid operator in filter `{expr}`"),
+                };
+                if matches!(op, Op::Eq | Op::Ne | Op::NotContains | Op::Le | Op::Ge) {
+                    chars.next();
+                }
+                Token::Op(op)
+            }
+            c => {
+                let mut s = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "()\"=!<>~".contains(c) {
+                        break;
+                    }
+                    s.push(c);
+                    chars.next();
+                }
+                Token::Word(s)
+            }
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+fn parse_or(tokens: &[Token], pos: &mut usize) -> Result<Filter> {
+    let mut filter = parse_and(tokens, pos)?;
+    while matches!(tokens.get(*pos), Some(Token::Word(w)) if w == "or") {
+        *pos += 1;
+        filter = Filter::Or(Box::new(filter), Box::new(parse_and(tokens, pos)?));
+    }
+    Ok(filter)
+}
+
+fn parse_and(tokens: &[Token], pos: &mut usize) -> Result<Filter> {
+    let mut filter = parse_unary(tokens, pos)?;
+    while matches!(tokens.get(*pos), Some(Token::Word(w)) if w == "and") {
+        *pos += 1;
+        filter = Filter::And(Box::new(filter), Box::new(parse_unary(tokens, pos)?));
+    }
+    Ok(filter)
+}
+
+fn parse_unary(tokens: &[Token], pos: &mut usize) -> Result<Filter> {
+    let token = tokens
+        .get(*pos)
+        .context("unexpected end of the filter expression")?;
+    *pos += 1;
+    match token {
+        Token::LParen => {
+            let filter = parse_or(tokens, pos)?;
+            match tokens.get(*pos) {
+                Some(Token::RParen) => {
+                    *pos += 1;
+                    Ok(filter)
+                }
+                _ => anyhow::bail!("expect a closing parenthesis"),
+            }
+        }
+        Token::Word(w) => match w.as_str() {
+            "not" => Ok(Filter::Not(Box::new(parse_unary(tokens, pos)?))),
+            "liked" => Ok(Filter::Liked),
+            "explicit" => Ok(Filter::Explicit),
+            "in_playlist" => Ok(Filter::InPlaylist),
+            field => {
+                let field = match field {
+                    "name" => Field::Name,
+                    "artist" => Field::Artist,
+                    "album" => Field::Album,
+                    "playlist" => Field::Playlist,
+                    "year" => Field::Year,
+                    "duration" => Field::Duration,
+                    "added_at" => Field::AddedAt,
+                    _ => anyhow::bail!("unknown field `{field}`"),
+                };
+                let op = match tokens.get(*pos) {
+                    Some(Token::Op(op)) => *op,
+                    _ => anyhow::bail!("expect an operator after field `{w}`"),
+                };
+                *pos += 1;
+                let value = parse_value(field, tokens, pos)?;
+                Ok(Filter::Compare(field, op, value))
+            }
+        },
+        _ => anyhow::bail!("unexpected token {token:?}"),
+    }
+}
+
+fn parse_value(field: Field, tokens: &[Token], pos: &mut usize) -> Result<Value> {
+    let value = match tokens.get(*pos) {
+        Some(Token::Word(w) | Token::Quoted(w)) => w,
+        _ => anyhow::bail!("expect a value after the operator"),
+    };
+    *pos += 1;
+
+    Ok(match field {
+        Field::Name | Field::Artist | Field::Album | Field::Playlist => Value::Text(value.clone()),
+        Field::Year => Value::Number(value.parse().context(format!("invalid year `{value}`"))?),
+        Field::Duration => Value::Duration(parse_duration(value)?),
+        Field::AddedAt => {
+            if matches!(tokens.get(*pos), Some(Token::Word(w)) if w == "ago") {
+                *pos += 1;
+                Value::Ago(parse_duration(value)?)
+            } else {
+                let date = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                    .context(format!("invalid date `{value}`, expect `YYYY-MM-DD`"))?;
+                Value::Time(
+                    date.and_hms_opt(0, 0, 0)
+                        .expect("valid time")
+                        .and_utc()
+                        .timestamp() as u64,
+                )
+            }
+        }
+    })
+}
+
+/// Parse a duration (e.g. `30d`, `1h30m`, `3m30s`) into seconds
+fn parse_duration(s: &str) -> Result<u64> {
+    let mut secs = 0;
+    let mut n = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            n.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => 24 * 60 * 60,
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => anyhow::bail!("invalid duration `{s}`"),
+        };
+        secs += n
+            .parse::<u64>()
+            .context(format!("invalid duration `{s}`"))?
+            * unit;
+        n.clear();
+    }
+    if !n.is_empty() {
+        anyhow::bail!("invalid duration `{s}`, missing a unit (d, h, m, s)");
+    }
+    Ok(secs)
+}
+
+/// Materialize smart playlists defined in the smart playlists config file, or only the smart playlist
+/// named `name` if specified.
+///
+/// Each smart playlist's target playlist is updated to contain exactly the tracks matching its filter.
+/// Candidate tracks are the user's liked tracks and tracks in the local library index.
+/// Return a summary of changes made to the target playlists.
+pub async fn materialize_smart_playlists(
+    client: &Client,
+    state: Option<&SharedState>,
+    name: Option<&str>,
+) -> Result<String> {
+    let configs = config::get_config();
+    let smart_playlists = configs
+        .smart_playlists_config
+        .smart_playlists
+        .iter()
+        .filter(|p| name.map(|name| p.name == name).unwrap_or(true))
+        .collect::<Vec<_>>();
+    if smart_playlists.is_empty() {
+        match name {
+            Some(name) => anyhow::bail!("Cannot find smart playlist '{name}'"),
+            None => return Ok("No smart playlists found.".to_string()),
+        }
+    }
+
+    // without a running application, the application's data is loaded from the cache folder,
+    // so that changes are still recorded into the edit journal
+    let is_standalone = state.is_none();
+    let standalone_state;
+    let state = match state {
+        Some(state) => state,
+        None => {
+            standalone_state = std::sync::Arc::new(State::new(false));
+            &standalone_state
+        }
+    };
+
+    // targets of all smart playlists are excluded from the source playlists
+    let targets = configs
+        .smart_playlists_config
+        .smart_playlists
+        .iter()
+        .map(|p| p.playlist_id().map(|id| id.uri()))
+        .collect::<Result<HashSet<_>>>()?;
+    let playlist_names = client
+        .current_user_playlists()
+        .await?
+        .into_iter()
+        .filter(|p| !targets.contains(&p.id.uri()))
+        .map(|p| (p.id.uri(), p.name))
+        .collect::<HashMap<_, _>>();
+    index_source_playlists(client, state, &playlist_names, is_standalone).await?;
+
+    let liked_tracks = client.current_user_saved_tracks().await?;
+    let changes = match_smart_playlists(
+        &smart_playlists,
+        &liked_tracks,
+        &state.data.read().library,
+        &playlist_names,
+        &targets,
+    )?;
+
+    let mut result = String::new();
+    for (name, id, matched) in changes {
+        let current = match client.playlist_context(id.as_ref()).await? {
+            Context::Playlist { tracks, .. } => tracks.into_iter().map(|t| t.id).collect(),
+            _ => unreachable!(),
+        };
+        let (added, deleted) = diff_playlist(current, matched);
+        let (n_added, n_deleted) = (added.len(), deleted.len());
+
+        // changes are recorded into the edit journal, so they can be reverted later
+        let edits = vec![
+            Edit::DeletePlaylistTracks {
+                playlist_id: id.clone(),
+                track_ids: deleted,
+            },
+            Edit::AddPlaylistTracks {
+                playlist_id: id.clone(),
+                track_ids: added,
+            },
+        ];
+        client
+            .apply_and_record_edits(state, format!("materialize smart playlist '{name}'"), edits)
+            .await?;
+
+        // remove the cache of the target playlist to force refetching new data
+        state.data.write().caches.context.remove(&id.uri());
+        result += &format!(
+            "Materialized smart playlist '{name}' into {}: {n_added} tracks added, {n_deleted} tracks deleted\n",
+            id.id()
+        );
+    }
+
+    Ok(result.trim_end().to_string())
+}
+
+/// Index the source playlists missing from the library index, or all of them if `reindex` is true
+/// (e.g. the index is loaded from the cache folder and can be outdated),
+/// so that the `in_playlist` and `playlist` filters are evaluated against the playlists' tracks
+async fn index_source_playlists(
+    client: &Client,
+    state: &SharedState,
+    playlist_names: &HashMap<String, String>,
+    reindex: bool,
+) -> Result<()> {
+    let uris = {
+        let data = state.data.read();
+        playlist_names
+            .keys()
+            .filter(|uri| reindex || !data.library.contains_source(uri))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    let mut changed = false;
+    for uri in uris {
+        let id = PlaylistId::from_uri(&uri)?.into_static();
+        let tracks = match client
+            .playlist_context(id)
+            .await
+            .with_context(|| format!("index source playlist {uri}"))?
+        {
+            Context::Playlist { tracks, .. } => tracks,
+            _ => unreachable!(),
+        };
+        changed |= state.data.write().library.update_source(&uri, &tracks);
+    }
+    if changed {
+        state
+            .data
+            .read()
+            .library
+            .store()
+            .context("store the library index into the cache folder")?;
+    }
+    Ok(())
+}
+
+/// Find tracks matching each smart playlist's filter, ordered by their added time.
+/// Candidate tracks are the user's liked tracks and tracks in the library index,
+/// excluding the smart playlists' `targets`.
+fn match_smart_playlists(
+    smart_playlists: &[&config::SmartPlaylist],
+    liked_tracks: &[Track],
+    library: &LibraryIndex,
+    playlist_names: &HashMap<String, String>,
+    targets: &HashSet<String>,
+) -> Result<Vec<(String, PlaylistId<'static>, Vec<TrackId<'static>>)>> {
+    let mut candidates: HashMap<String, Candidate> = liked_tracks
+        .iter()
+        .map(|t| {
+            (
+                t.id.uri(),
+                Candidate {
+                    track: t,
+                    liked: true,
+                    playlists: Vec::new(),
+                },
+            )
+        })
+        .collect();
+    for (uri, tracks) in library.sources().filter(|(uri, _)| !targets.contains(*uri)) {
+        let playlist = playlist_names.get(uri).map(|name| name.as_str());
+        for track in tracks {
+            let candidate = candidates
+                .entry(track.id.uri())
+                .or_insert_with(|| Candidate {
+                    track,
+                    liked: false,
+                    playlists: Vec::new(),
+                });
+            if let Some(playlist) = playlist {
+                candidate.playlists.push(playlist);
+            }
+        }
+    }
+
+    smart_playlists
+        .iter()
+        .map(|smart_playlist| {
+            let filter = Filter::parse(&smart_playlist.filter)?;
+            let mut matched = candidates
+                .values()
+                .filter(|c| filter.matches(c))
+                .map(|c| c.track)
+                .collect::<Vec<_>>();
+            matched.sort_by(|x, y| {
+                x.added_at
+                    .cmp(&y.added_at)
+                    .then_with(|| x.name.cmp(&y.name))
+            });
+            Ok((
+                smart_playlist.name.clone(),
+                smart_playlist.playlist_id()?,
+                matched.into_iter().map(|t| t.id.clone()).collect(),
+            ))
+        })
+        .collect()
+}
+
+/// Compute the changes updating a playlist containing the `current` tracks to contain exactly the `target` tracks.
+/// Return the added and deleted tracks.
+fn diff_playlist(
+    current: HashSet<TrackId<'static>>,
+    target: Vec<TrackId<'static>>,
+) -> (Vec<TrackId<'static>>, Vec<TrackId<'static>>) {
+    let target_set = target.iter().cloned().collect::<HashSet<_>>();
+    let added = target
+        .into_iter()
+        .filter(|id| !current.contains(id))
+        .collect::<Vec<_>>();
+    let deleted = current
+        .into_iter()
+        .filter(|id| !target_set.contains(id))
+        .collect::<Vec<_>>();
+    (added, deleted)
+}
+
+/// Periodically materialize smart playlists in a running application,
+/// if `refresh_interval_in_mins` is specified in the smart playlists config file
+pub async fn start_smart_playlist_task(client: Client, state: SharedState) {
+    let config = &config::get_config().smart_playlists_config;
+    let Some(interval_in_mins) = config.refresh_interval_in_mins else {
+        return;
+    };
+    if config.smart_playlists.is_empty() {
+        return;
+    }
+
+    let period = std::time::Duration::from_secs(interval_in_mins.max(1) * 60);
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        interval.tick().await;
+        match materialize_smart_playlists(&client, Some(&state), None).await {
+            Ok(result) => tracing::info!("{result}"),
+            Err(err) => tracing::warn!("Failed to materialize smart playlists: {err:#}"),
+        }
+    }
+}
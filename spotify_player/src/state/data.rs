@@ -1,11 +1,24 @@
 use std::io::{BufReader, BufWriter};
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
 
 use once_cell::sync::Lazy;
 use serde::{de::DeserializeOwned, Serialize};
 
+use super::history::PlayHistory;
+use super::journal::Journal;
+use super::library::LibraryIndex;
 use super::model::*;
 
+/// name of the folder storing cached lyrics, relative to the application's cache folder
+#[cfg(feature = "lyric-finder")]
+const LYRICS_CACHE_FOLDER: &str = "lyrics";
+/// the maximum length of a cached lyric's file name
+#[cfg(feature = "lyric-finder")]
+const MAX_LYRIC_FILE_NAME_LEN: usize = 200;
+
 pub type DataReadGuard<'a> = parking_lot::RwLockReadGuard<'a, AppData>;
 
 #[derive(Debug)]
@@ -14,6 +27,9 @@ pub enum FileCacheKey {
     FollowedArtists,
     SavedAlbums,
     SavedTracks,
+    SavedShows,
+    PlayedEpisodes,
+    ScrobbleQueue,
 }
 
 /// default time-to-live cache duration
@@ -25,6 +41,9 @@ pub struct AppData {
     pub user_data: UserData,
     pub caches: MemoryCaches,
     pub browse: BrowseData,
+    pub play_history: PlayHistory,
+    pub library: LibraryIndex,
+    pub journal: Journal,
 }
 
 #[derive(Debug)]
@@ -35,6 +54,12 @@ pub struct UserData {
     pub followed_artists: Vec<Artist>,
     pub saved_albums: Vec<Album>,
     pub saved_tracks: HashMap<String, Track>,
+    pub saved_shows: Vec<Show>,
+    /// episodes marked as played or unplayed by the user, keyed by the episodes' URIs.
+    ///
+    /// The Web API doesn't support updating an episode's listening progress,
+    /// so the marks are stored locally and take precedence over the progress reported by Spotify.
+    pub played_episodes: HashMap<String, bool>,
 }
 
 /// the application's in-memory caches
@@ -73,29 +98,37 @@ impl AppData {
             user_data: UserData::new_from_file_caches(cache_folder),
             caches: MemoryCaches::new(),
             browse: BrowseData::default(),
+            play_history: PlayHistory::new(cache_folder),
+            library: LibraryIndex::new(cache_folder),
+            journal: Journal::new(cache_folder),
         }
     }
 
     /// Get a list of tracks inside a given context
     pub fn context_tracks_mut(&mut self, id: &ContextId) -> Option<&mut Vec<Track>> {
-        self.caches.context.get_mut(&id.uri()).map(|c| match c {
-            Context::Album { tracks, .. } => tracks,
-            Context::Playlist { tracks, .. } => tracks,
-            Context::Artist {
-                top_tracks: tracks, ..
-            } => tracks,
-            Context::Tracks { tracks, .. } => tracks,
-        })
+        self.caches
+            .context
+            .get_mut(&id.uri())
+            .and_then(|c| match c {
+                Context::Album { tracks, .. } => Some(tracks),
+                Context::Playlist { tracks, .. } => Some(tracks),
+                Context::Artist {
+                    top_tracks: tracks, ..
+                } => Some(tracks),
+                Context::Tracks { tracks, .. } => Some(tracks),
+                Context::Show { .. } => None,
+            })
     }
 
     pub fn context_tracks(&self, id: &ContextId) -> Option<&Vec<Track>> {
-        self.caches.context.get(&id.uri()).map(|c| match c {
-            Context::Album { tracks, .. } => tracks,
-            Context::Playlist { tracks, .. } => tracks,
+        self.caches.context.get(&id.uri()).and_then(|c| match c {
+            Context::Album { tracks, .. } => Some(tracks),
+            Context::Playlist { tracks, .. } => Some(tracks),
             Context::Artist {
                 top_tracks: tracks, ..
-            } => tracks,
-            Context::Tracks { tracks, .. } => tracks,
+            } => Some(tracks),
+            Context::Tracks { tracks, .. } => Some(tracks),
+            Context::Show { .. } => None,
         })
     }
 }
@@ -116,6 +149,10 @@ impl UserData {
                 .unwrap_or_default(),
             saved_tracks: load_data_from_file_cache(FileCacheKey::SavedTracks, cache_folder)
                 .unwrap_or_default(),
+            saved_shows: load_data_from_file_cache(FileCacheKey::SavedShows, cache_folder)
+                .unwrap_or_default(),
+            played_episodes: load_data_from_file_cache(FileCacheKey::PlayedEpisodes, cache_folder)
+                .unwrap_or_default(),
         }
     }
 
@@ -131,10 +168,112 @@ impl UserData {
         }
     }
 
+    /// Get URIs of the user's library sources (playlists, saved albums and liked tracks),
+    /// whose tracks are indexed into the local library index
+    pub fn library_source_uris(&self) -> HashSet<String> {
+        self.playlists
+            .iter()
+            .map(|p| p.id.uri())
+            .chain(self.saved_albums.iter().map(|a| a.id.uri()))
+            .chain(std::iter::once(super::USER_LIKED_TRACKS_ID.uri.clone()))
+            .collect()
+    }
+
     /// Check if a track is a liked track
     pub fn is_liked_track(&self, track: &Track) -> bool {
         self.saved_tracks.contains_key(&track.id.uri())
     }
+
+    /// Check if an episode is played, based on the user's marks and the episode's listening progress
+    pub fn is_played_episode(&self, episode: &Episode) -> bool {
+        self.played_episodes
+            .get(&episode.id.uri())
+            .copied()
+            .unwrap_or(episode.fully_played)
+    }
+
+    /// Get an item in the user's library (liked tracks, saved albums, followed artists, playlists or saved shows)
+    pub fn library_item(&self, id: &ItemId) -> Option<Item> {
+        match id {
+            ItemId::Track(id) => self.saved_tracks.get(&id.uri()).cloned().map(Item::Track),
+            ItemId::Album(id) => self
+                .saved_albums
+                .iter()
+                .find(|a| a.id == *id)
+                .cloned()
+                .map(Item::Album),
+            ItemId::Artist(id) => self
+                .followed_artists
+                .iter()
+                .find(|a| a.id == *id)
+                .cloned()
+                .map(Item::Artist),
+            ItemId::Playlist(id) => self
+                .playlists
+                .iter()
+                .find(|p| p.id == *id)
+                .cloned()
+                .map(Item::Playlist),
+            ItemId::Show(id) => self
+                .saved_shows
+                .iter()
+                .find(|s| s.id == *id)
+                .cloned()
+                .map(Item::Show),
+        }
+    }
+}
+
+/// Get the path of the file caching a lyric, whose name is derived from the lyric's `query`
+#[cfg(feature = "lyric-finder")]
+fn lyric_file_cache_path(query: &str, cache_folder: &Path) -> std::path::PathBuf {
+    let name = query
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("_");
+    let name = name
+        .chars()
+        .take(MAX_LYRIC_FILE_NAME_LEN)
+        .collect::<String>();
+    cache_folder
+        .join(LYRICS_CACHE_FOLDER)
+        .join(format!("{name}.json"))
+}
+
+#[cfg(feature = "lyric-finder")]
+pub fn store_lyric_into_file_cache(
+    query: &str,
+    cache_folder: &Path,
+    lyric: &lyric_finder::LyricResult,
+) -> std::io::Result<()> {
+    let path = lyric_file_cache_path(query, cache_folder);
+    if let Some(folder) = path.parent() {
+        std::fs::create_dir_all(folder)?;
+    }
+    let f = BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer(f, lyric)?;
+    Ok(())
+}
+
+#[cfg(feature = "lyric-finder")]
+pub fn load_lyric_from_file_cache(
+    query: &str,
+    cache_folder: &Path,
+) -> Option<lyric_finder::LyricResult> {
+    let path = lyric_file_cache_path(query, cache_folder);
+    let f = BufReader::new(std::fs::File::open(&path).ok()?);
+    match serde_json::from_reader(f) {
+        Ok(lyric) => Some(lyric),
+        Err(err) => {
+            tracing::error!(
+                "Failed to load the cached lyric {}: {err:#}",
+                path.display()
+            );
+            None
+        }
+    }
 }
 
 pub fn store_data_into_file_cache<T: Serialize>(
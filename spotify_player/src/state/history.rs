@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// name of the file storing the play history, relative to the application's cache folder
+const PLAY_HISTORY_FILE: &str = "play_history.jsonl";
+
+/// maximum number of items in each top list shown in the history page
+pub const HISTORY_PAGE_STATS_LIMIT: usize = 100;
+
+/// how long computed statistics stay valid, so that records aging out of a period's window are dropped
+const STATS_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A record of a track being played
+pub struct PlayRecord {
+    /// the played track's URI
+    pub track_uri: String,
+    pub track: String,
+    pub artists: Vec<String>,
+    pub album: String,
+    /// the URI of the context (playlist, album, artist) the track was played from
+    pub context_uri: Option<String>,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    /// the amount of time the track was actually played
+    pub played_ms: u64,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+/// A period of time to compute the listening statistics over
+pub enum HistoryPeriod {
+    Week,
+    Month,
+    Year,
+}
+
+#[derive(Debug, Default, Serialize)]
+/// Listening statistics computed from the play history
+pub struct HistoryStats {
+    pub n_plays: usize,
+    pub played_ms: u64,
+    pub top_tracks: Vec<HistoryStatsItem>,
+    pub top_artists: Vec<HistoryStatsItem>,
+    pub top_albums: Vec<HistoryStatsItem>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+/// An item (track, artist, album) in the listening statistics
+pub struct HistoryStatsItem {
+    pub name: String,
+    pub n_plays: usize,
+    pub played_ms: u64,
+}
+
+/// listening statistics together with the time they were computed
+type CachedStats = (std::time::Instant, Arc<HistoryStats>);
+
+#[derive(Debug)]
+/// The user's play history, persisted as a JSON-lines file inside the application's cache folder
+pub struct PlayHistory {
+    path: PathBuf,
+    pub records: Vec<PlayRecord>,
+    /// the play record of the currently playing track, which is added to the history once the play finishes
+    pending: Option<PlayRecord>,
+    /// computed statistics (with their computation time) for each period and limit,
+    /// invalidated upon adding a new record or after `STATS_CACHE_TTL`
+    stats_cache: parking_lot::Mutex<HashMap<(HistoryPeriod, usize), CachedStats>>,
+}
+
+impl PlayHistory {
+    /// Load the play history stored inside the `cache_folder`
+    pub fn new(cache_folder: &Path) -> Self {
+        let path = cache_folder.join(PLAY_HISTORY_FILE);
+        let records = match std::fs::File::open(&path) {
+            Ok(f) => BufReader::new(f)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| match serde_json::from_str(&line) {
+                    Ok(record) => Some(record),
+                    Err(err) => {
+                        tracing::warn!("Failed to parse a play history record {line}: {err:#}");
+                        None
+                    }
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+
+        Self {
+            path,
+            records,
+            pending: None,
+            stats_cache: Default::default(),
+        }
+    }
+
+    /// Add a new record to the play history
+    pub fn add(&mut self, record: PlayRecord) -> std::io::Result<()> {
+        let mut f = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(f, "{}", serde_json::to_string(&record)?)?;
+        self.records.push(record);
+        self.stats_cache.lock().clear();
+        Ok(())
+    }
+
+    /// Update the play record of the currently playing track
+    pub fn set_pending(&mut self, record: Option<PlayRecord>) {
+        self.pending = record;
+    }
+
+    /// Add the play record of the currently playing track (if played) to the play history,
+    /// e.g. when the application is shut down
+    pub fn flush_pending(&mut self) -> std::io::Result<()> {
+        match self.pending.take() {
+            Some(record) if record.played_ms > 0 => self.add(record),
+            _ => Ok(()),
+        }
+    }
+
+    /// Get the listening statistics of records played within a given `period`,
+    /// each top list is truncated to have at most `limit` items.
+    ///
+    /// The statistics are cached until a new record is added or the cache entry expires.
+    pub fn stats(&self, period: HistoryPeriod, limit: usize) -> Arc<HistoryStats> {
+        let mut cache = self.stats_cache.lock();
+        match cache.get(&(period, limit)) {
+            Some((computed_at, stats)) if computed_at.elapsed() < STATS_CACHE_TTL => stats.clone(),
+            _ => {
+                let stats = Arc::new(self.compute_stats(period, limit));
+                cache.insert((period, limit), (std::time::Instant::now(), stats.clone()));
+                stats
+            }
+        }
+    }
+
+    fn compute_stats(&self, period: HistoryPeriod, limit: usize) -> HistoryStats {
+        let since = chrono::Utc::now() - period.duration();
+
+        let mut stats = HistoryStats::default();
+        let mut tracks: HashMap<&str, HistoryStatsItem> = HashMap::new();
+        let mut artists: HashMap<&str, HistoryStatsItem> = HashMap::new();
+        let mut albums: HashMap<&str, HistoryStatsItem> = HashMap::new();
+
+        for record in self.records.iter().filter(|r| r.started_at >= since) {
+            stats.n_plays += 1;
+            stats.played_ms += record.played_ms;
+
+            let update = |item: &mut HistoryStatsItem| {
+                item.n_plays += 1;
+                item.played_ms += record.played_ms;
+            };
+
+            update(
+                tracks
+                    .entry(record.track_uri.as_str())
+                    .or_insert_with(|| HistoryStatsItem {
+                        name: format!("{} • {}", record.track, record.artists.join(", ")),
+                        ..Default::default()
+                    }),
+            );
+            for artist in &record.artists {
+                update(
+                    artists
+                        .entry(artist.as_str())
+                        .or_insert_with(|| HistoryStatsItem {
+                            name: artist.clone(),
+                            ..Default::default()
+                        }),
+                );
+            }
+            update(
+                albums
+                    .entry(record.album.as_str())
+                    .or_insert_with(|| HistoryStatsItem {
+                        name: record.album.clone(),
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        let top = |items: HashMap<&str, HistoryStatsItem>| {
+            let mut items = items.into_values().collect::<Vec<_>>();
+            items.sort_by(|x, y| {
+                y.n_plays
+                    .cmp(&x.n_plays)
+                    .then(y.played_ms.cmp(&x.played_ms))
+            });
+            items.truncate(limit);
+            items
+        };
+
+        stats.top_tracks = top(tracks);
+        stats.top_artists = top(artists);
+        stats.top_albums = top(albums);
+        stats
+    }
+}
+
+impl HistoryPeriod {
+    pub fn duration(&self) -> chrono::Duration {
+        match self {
+            Self::Week => chrono::Duration::days(7),
+            Self::Month => chrono::Duration::days(30),
+            Self::Year => chrono::Duration::days(365),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            Self::Week => Self::Month,
+            Self::Month => Self::Year,
+            Self::Year => Self::Week,
+        }
+    }
+}
+
+impl std::fmt::Display for HistoryPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Week => write!(f, "week"),
+            Self::Month => write!(f, "month"),
+            Self::Year => write!(f, "year"),
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::data::UserData;
+use super::model::*;
+
+/// name of the file storing the edit journal, relative to the application's cache folder
+const JOURNAL_FILE: &str = "journal.json";
+
+/// maximum number of entries in each of the journal's undo and redo stacks
+const MAX_JOURNAL_ENTRIES: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A track at a specific position of a playlist
+pub struct PlaylistItem {
+    pub track_id: TrackId<'static>,
+    pub position: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A reversible modification of the user's playlists or library
+pub enum Edit {
+    /// append tracks to a playlist, removing their existing occurrences beforehand
+    AddPlaylistTracks {
+        playlist_id: PlaylistId<'static>,
+        track_ids: Vec<TrackId<'static>>,
+    },
+    /// remove every occurrence of tracks from a playlist
+    DeletePlaylistTracks {
+        playlist_id: PlaylistId<'static>,
+        track_ids: Vec<TrackId<'static>>,
+    },
+    /// insert tracks into a playlist, items are sorted by their positions
+    InsertPlaylistItems {
+        playlist_id: PlaylistId<'static>,
+        items: Vec<PlaylistItem>,
+    },
+    /// remove tracks at specific positions of a playlist's snapshot
+    RemovePlaylistItems {
+        playlist_id: PlaylistId<'static>,
+        items: Vec<PlaylistItem>,
+        snapshot_id: Option<String>,
+    },
+    /// move a playlist item from `range_start` to `insert_index`
+    ReorderPlaylistItem {
+        playlist_id: PlaylistId<'static>,
+        insert_index: usize,
+        range_start: usize,
+        snapshot_id: Option<String>,
+    },
+    AddToLibrary(Vec<Item>),
+    DeleteFromLibrary(Vec<ItemId>),
+}
+
+impl Edit {
+    /// Check if the edit doesn't change anything
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::AddPlaylistTracks { track_ids, .. }
+            | Self::DeletePlaylistTracks { track_ids, .. } => track_ids.is_empty(),
+            Self::InsertPlaylistItems { items, .. } | Self::RemovePlaylistItems { items, .. } => {
+                items.is_empty()
+            }
+            Self::ReorderPlaylistItem {
+                insert_index,
+                range_start,
+                ..
+            } => insert_index == range_start,
+            Self::AddToLibrary(items) => items.is_empty(),
+            Self::DeleteFromLibrary(ids) => ids.is_empty(),
+        }
+    }
+
+    /// Get a description of the edit, using names of the user's playlists and library items
+    pub fn desc(&self, user_data: &UserData) -> String {
+        let playlist_name = |id: &PlaylistId| match user_data.playlists.iter().find(|p| p.id == *id)
+        {
+            Some(p) => format!("playlist \"{}\"", p.name),
+            None => "a playlist".to_string(),
+        };
+
+        match self {
+            Self::AddPlaylistTracks {
+                playlist_id,
+                track_ids,
+            } => format!(
+                "add {} to {}",
+                n_items(track_ids.len(), "track"),
+                playlist_name(playlist_id)
+            ),
+            Self::DeletePlaylistTracks {
+                playlist_id,
+                track_ids,
+            } => format!(
+                "delete {} from {}",
+                n_items(track_ids.len(), "track"),
+                playlist_name(playlist_id)
+            ),
+            Self::InsertPlaylistItems { playlist_id, items } => format!(
+                "insert {} into {}",
+                n_items(items.len(), "track"),
+                playlist_name(playlist_id)
+            ),
+            Self::RemovePlaylistItems {
+                playlist_id, items, ..
+            } => format!(
+                "remove {} from {}",
+                n_items(items.len(), "track"),
+                playlist_name(playlist_id)
+            ),
+            Self::ReorderPlaylistItem {
+                playlist_id,
+                insert_index,
+                range_start,
+                ..
+            } => format!(
+                "move item {} to position {} in {}",
+                range_start + 1,
+                insert_index + 1,
+                playlist_name(playlist_id)
+            ),
+            Self::AddToLibrary(items) => match items.as_slice() {
+                [item] => format!("add \"{}\" to library", item.name()),
+                _ => format!("add {} to library", n_items(items.len(), "item")),
+            },
+            Self::DeleteFromLibrary(ids) => match ids.as_slice() {
+                [id] => match user_data.library_item(id) {
+                    Some(item) => format!("delete \"{}\" from library", item.name()),
+                    None => "delete an item from library".to_string(),
+                },
+                _ => format!("delete {} from library", n_items(ids.len(), "item")),
+            },
+        }
+    }
+}
+
+fn n_items(n: usize, noun: &str) -> String {
+    match n {
+        1 => format!("1 {noun}"),
+        _ => format!("{n} {noun}s"),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A journal entry storing the edits to apply in order to revert a change
+pub struct JournalEntry {
+    /// description of the reverted change
+    pub desc: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub edits: Vec<Edit>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JournalStacks {
+    undo: Vec<JournalEntry>,
+    redo: Vec<JournalEntry>,
+}
+
+#[derive(Debug)]
+/// A journal of changes made to the user's playlists and library, which can be undone and redone.
+///
+/// The journal is persisted as a JSON file inside the application's cache folder,
+/// so changes made in a previous session can still be undone.
+pub struct Journal {
+    path: PathBuf,
+    stacks: JournalStacks,
+}
+
+impl Journal {
+    /// Load the journal stored inside the `cache_folder`
+    pub fn new(cache_folder: &Path) -> Self {
+        let path = cache_folder.join(JOURNAL_FILE);
+        let stacks = match std::fs::File::open(&path) {
+            Ok(f) => match serde_json::from_reader(BufReader::new(f)) {
+                Ok(stacks) => stacks,
+                Err(err) => {
+                    tracing::error!("Failed to load the edit journal: {err:#}");
+                    JournalStacks::default()
+                }
+            },
+            Err(_) => JournalStacks::default(),
+        };
+        Self { path, stacks }
+    }
+
+    /// Store the journal into the cache folder
+    pub fn store(&self) -> std::io::Result<()> {
+        let f = BufWriter::new(std::fs::File::create(&self.path)?);
+        serde_json::to_writer(f, &self.stacks)?;
+        Ok(())
+    }
+
+    /// Record a new change, which clears the redo stack
+    pub fn record(&mut self, entry: JournalEntry) {
+        self.stacks.redo.clear();
+        push_entry(&mut self.stacks.undo, entry);
+    }
+
+    /// Pop the latest entry from the undo stack (`redo` is false) or the redo stack (`redo` is true)
+    pub fn pop(&mut self, redo: bool) -> Option<JournalEntry> {
+        match redo {
+            false => self.stacks.undo.pop(),
+            true => self.stacks.redo.pop(),
+        }
+    }
+
+    /// Push an entry to the undo stack (`redo` is false) or the redo stack (`redo` is true)
+    pub fn push(&mut self, entry: JournalEntry, redo: bool) {
+        match redo {
+            false => push_entry(&mut self.stacks.undo, entry),
+            true => push_entry(&mut self.stacks.redo, entry),
+        }
+    }
+}
+
+fn push_entry(stack: &mut Vec<JournalEntry>, entry: JournalEntry) {
+    stack.push(entry);
+    if stack.len() > MAX_JOURNAL_ENTRIES {
+        stack.remove(0);
+    }
+}
@@ -0,0 +1,311 @@
+use std::collections::{HashMap, HashSet};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use super::model::*;
+
+/// name of the file storing the library index, relative to the application's cache folder
+const LIBRARY_INDEX_FILE: &str = "library_index.json";
+
+/// prefix of a search page's query to search the local library index instead of Spotify
+pub const LOCAL_SEARCH_PREFIX: &str = "local:";
+
+/// maximum number of items of each type returned by a local search
+pub const LOCAL_SEARCH_LIMIT: usize = 50;
+
+#[derive(Debug)]
+/// A local index of tracks in the user's library (playlists, saved albums and liked tracks),
+/// persisted as a JSON file inside the application's cache folder.
+///
+/// The index is built incrementally: each time a library source is retrieved,
+/// its tracks replace the previously indexed tracks of that source.
+pub struct LibraryIndex {
+    path: PathBuf,
+    /// indexed tracks of each library source, keyed by the source's URI
+    sources: HashMap<String, Vec<Track>>,
+}
+
+#[derive(Debug, Default)]
+/// A parsed local search query
+struct LocalSearchQuery {
+    /// free-text terms, which must match the track's name, artists or album
+    terms: Vec<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    track: Option<String>,
+    /// an inclusive range of release years
+    year: Option<(u32, u32)>,
+}
+
+impl LibraryIndex {
+    /// Load the library index stored inside the `cache_folder`
+    pub fn new(cache_folder: &Path) -> Self {
+        let path = cache_folder.join(LIBRARY_INDEX_FILE);
+        let sources = match std::fs::File::open(&path) {
+            Ok(f) => match serde_json::from_reader(BufReader::new(f)) {
+                Ok(sources) => sources,
+                Err(err) => {
+                    tracing::error!("Failed to load the library index: {err:#}");
+                    HashMap::new()
+                }
+            },
+            Err(_) => HashMap::new(),
+        };
+        Self { path, sources }
+    }
+
+    /// Store the library index into the cache folder
+    pub fn store(&self) -> std::io::Result<()> {
+        let f = BufWriter::new(std::fs::File::create(&self.path)?);
+        serde_json::to_writer(f, &self.sources)?;
+        Ok(())
+    }
+
+    /// Check if a library source has been indexed
+    pub fn contains_source(&self, uri: &str) -> bool {
+        self.sources.contains_key(uri)
+    }
+
+    /// Replace the indexed tracks of a library source.
+    /// Return `true` if the index is changed.
+    pub fn update_source(&mut self, uri: &str, tracks: &[Track]) -> bool {
+        if let Some(indexed) = self.sources.get(uri) {
+            if indexed.len() == tracks.len()
+                && indexed.iter().zip(tracks).all(|(x, y)| x.id == y.id)
+            {
+                return false;
+            }
+        }
+        self.sources.insert(uri.to_string(), tracks.to_vec());
+        true
+    }
+
+    /// Remove library sources not satisfying the predicate `f`, e.g. unfollowed playlists.
+    /// Return `true` if the index is changed.
+    pub fn retain_sources(&mut self, f: impl Fn(&str) -> bool) -> bool {
+        let n_sources = self.sources.len();
+        self.sources.retain(|uri, _| f(uri));
+        self.sources.len() != n_sources
+    }
+
+    /// Get the indexed library sources, each of which is a pair of the source's URI and its tracks
+    pub fn sources(&self) -> impl Iterator<Item = (&String, &Vec<Track>)> {
+        self.sources.iter()
+    }
+
+    /// Get the number of unique indexed tracks
+    pub fn n_tracks(&self) -> usize {
+        self.tracks().count()
+    }
+
+    /// Get unique indexed tracks
+    fn tracks(&self) -> impl Iterator<Item = &Track> {
+        let mut visited = HashSet::new();
+        self.sources
+            .values()
+            .flatten()
+            .filter(move |t| visited.insert(t.id.uri()))
+    }
+
+    /// Search the library index.
+    ///
+    /// The query consists of free-text terms and field filters (`artist:`, `album:`, `track:`, `year:`),
+    /// whose values can be quoted to include spaces, e.g. `album:"ok computer" year:1990-1999 paranoid`.
+    /// Tracks are ranked by how well the free-text terms match their names, artists and albums.
+    /// Returned artists and albums are the ones of the matched tracks, and returned playlists are
+    /// the user's `playlists` whose names match the free-text terms.
+    pub fn search(&self, query: &str, playlists: &[Playlist], limit: usize) -> SearchResults {
+        let query = LocalSearchQuery::parse(query);
+
+        let mut tracks = self
+            .tracks()
+            .filter_map(|t| query.score(t).map(|score| (score, t)))
+            .collect::<Vec<_>>();
+        tracks.sort_by(|(x_score, x), (y_score, y)| {
+            y_score.cmp(x_score).then_with(|| x.name.cmp(&y.name))
+        });
+
+        let mut results = SearchResults::default();
+        let mut artist_ids = HashSet::new();
+        let mut album_ids = HashSet::new();
+        for (_, track) in &tracks {
+            for artist in &track.artists {
+                if results.artists.len() < limit && artist_ids.insert(artist.id.clone()) {
+                    results.artists.push(artist.clone());
+                }
+            }
+            if let Some(album) = &track.album {
+                if results.albums.len() < limit && album_ids.insert(album.id.clone()) {
+                    results.albums.push(album.clone());
+                }
+            }
+        }
+        results.tracks = tracks
+            .into_iter()
+            .take(limit)
+            .map(|(_, t)| t.clone())
+            .collect();
+
+        if !query.terms.is_empty() {
+            results.playlists = playlists
+                .iter()
+                .filter(|p| {
+                    let name = p.name.to_lowercase();
+                    query.terms.iter().all(|term| name.contains(term))
+                })
+                .take(limit)
+                .cloned()
+                .collect();
+        }
+
+        results
+    }
+}
+
+impl LocalSearchQuery {
+    fn parse(query: &str) -> Self {
+        let mut parsed = Self::default();
+
+        for token in tokenize(&query.to_lowercase()) {
+            let is_filter = match token.split_once(':') {
+                Some(("artist", value)) => {
+                    parsed.artist = Some(value.trim_matches('"').to_string());
+                    true
+                }
+                Some(("album", value)) => {
+                    parsed.album = Some(value.trim_matches('"').to_string());
+                    true
+                }
+                Some(("track", value)) => {
+                    parsed.track = Some(value.trim_matches('"').to_string());
+                    true
+                }
+                Some(("year", value)) => match parse_year_range(value) {
+                    Some(range) => {
+                        parsed.year = Some(range);
+                        true
+                    }
+                    None => false,
+                },
+                _ => false,
+            };
+            if !is_filter {
+                let term = token.trim_matches('"');
+                if !term.is_empty() {
+                    parsed.terms.push(term.to_string());
+                }
+            }
+        }
+
+        parsed
+    }
+
+    /// Compute the ranking score of a track, return `None` if the track doesn't match the query
+    fn score(&self, track: &Track) -> Option<usize> {
+        let name = track.name.to_lowercase();
+        let artists = track
+            .artists
+            .iter()
+            .map(|a| a.name.to_lowercase())
+            .collect::<Vec<_>>();
+        let album = track
+            .album
+            .as_ref()
+            .map(|a| a.name.to_lowercase())
+            .unwrap_or_default();
+
+        if let Some(ref artist) = self.artist {
+            if !artists.iter().any(|a| a.contains(artist.as_str())) {
+                return None;
+            }
+        }
+        if let Some(ref filter) = self.album {
+            if !album.contains(filter.as_str()) {
+                return None;
+            }
+        }
+        if let Some(ref filter) = self.track {
+            if !name.contains(filter.as_str()) {
+                return None;
+            }
+        }
+        if let Some((from, to)) = self.year {
+            let year = track
+                .album
+                .as_ref()
+                .and_then(|a| a.release_date.get(..4))
+                .and_then(|y| y.parse::<u32>().ok())?;
+            if year < from || year > to {
+                return None;
+            }
+        }
+
+        let mut score = 0;
+        for term in &self.terms {
+            // matches in a track's name are ranked higher than matches in its artists or album
+            let term_score = std::iter::once(3 * match_score(&name, term))
+                .chain(artists.iter().map(|a| 2 * match_score(a, term)))
+                .chain(std::iter::once(match_score(&album, term)))
+                .max()
+                .unwrap_or_default();
+            if term_score == 0 {
+                return None;
+            }
+            score += term_score;
+        }
+        Some(score)
+    }
+}
+
+/// Compute how well a text matches a term: an exact match is ranked higher than a word's prefix match,
+/// which is ranked higher than a substring match
+fn match_score(text: &str, term: &str) -> usize {
+    if text == term {
+        4
+    } else if text
+        .split(|c: char| !c.is_alphanumeric())
+        .any(|word| word.starts_with(term))
+    {
+        2
+    } else if text.contains(term) {
+        1
+    } else {
+        0
+    }
+}
+
+/// Split a query into whitespace-separated tokens, in which quoted values can include spaces
+fn tokenize(query: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut in_quotes = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                token.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// Parse a year (e.g. `1997`) or a range of years (e.g. `1990-1999`)
+fn parse_year_range(value: &str) -> Option<(u32, u32)> {
+    match value.split_once('-') {
+        Some((from, to)) => Some((from.parse().ok()?, to.parse().ok()?)),
+        None => {
+            let year = value.parse().ok()?;
+            Some((year, year))
+        }
+    }
+}
@@ -1,13 +1,23 @@
 mod constant;
 mod data;
+mod history;
+mod journal;
+mod library;
 mod model;
 mod player;
+mod render;
+mod sleep_timer;
 mod ui;
 
 pub use constant::*;
 pub use data::*;
+pub use history::*;
+pub use journal::*;
+pub use library::*;
 pub use model::*;
 pub use player::*;
+pub use render::*;
+pub use sleep_timer::*;
 pub use ui::*;
 
 use crate::config;
@@ -23,6 +33,13 @@ pub struct State {
     pub player: RwLock<PlayerState>,
     pub data: RwLock<AppData>,
 
+    /// wakes the UI renderer up upon state changes
+    pub render: RenderNotifier,
+
+    /// the integrated player's audio, used to render the audio visualizer
+    #[cfg(feature = "streaming")]
+    pub audio_tap: std::sync::Arc<crate::visualizer::AudioTap>,
+
     pub is_daemon: bool,
 }
 
@@ -35,13 +52,21 @@ impl State {
             // update the UI's theme based on the `theme` config option
             ui.theme = theme;
         }
+        #[cfg(feature = "streaming")]
+        {
+            ui.eq_preset = configs.app_config.dsp.preset.clone();
+            ui.is_visualizer_shown = configs.app_config.show_visualizer;
+        }
 
-        let app_data = AppData::new(&configs.cache_folder);
+        let app_data = AppData::new(&configs.profile_cache_folder());
 
         Self {
             ui: Mutex::new(ui),
             player: RwLock::new(PlayerState::default()),
             data: RwLock::new(app_data),
+            render: RenderNotifier::default(),
+            #[cfg(feature = "streaming")]
+            audio_tap: Default::default(),
             is_daemon,
         }
     }
@@ -53,4 +78,11 @@ impl State {
             || (configs.app_config.enable_streaming == config::StreamingType::DaemonOnly
                 && self.is_daemon)
     }
+
+    /// Add the currently playing track's play record to the play history before the application is shut down
+    pub fn flush_play_history(&self) {
+        if let Err(err) = self.data.write().play_history.flush_pending() {
+            tracing::error!("Failed to flush the pending play record: {err:#}");
+        }
+    }
 }
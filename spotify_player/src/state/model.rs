@@ -1,6 +1,8 @@
 pub use rspotify::model as rspotify_model;
 use rspotify::model::CurrentPlaybackContext;
-pub use rspotify::model::{AlbumId, AlbumType, ArtistId, Id, PlaylistId, TrackId, UserId};
+pub use rspotify::model::{
+    AlbumId, AlbumType, ArtistId, EpisodeId, Id, PlaylistId, ShowId, TrackId, UserId,
+};
 
 use crate::utils::map_join;
 use html_escape::decode_html_entities;
@@ -9,7 +11,7 @@ use std::borrow::Cow;
 
 #[derive(Serialize, Clone, Debug)]
 #[serde(untagged)]
-/// A Spotify context (playlist, album, artist)
+/// A Spotify context (playlist, album, artist, show)
 pub enum Context {
     Playlist {
         playlist: Playlist,
@@ -29,6 +31,10 @@ pub enum Context {
         tracks: Vec<Track>,
         desc: String,
     },
+    Show {
+        show: Show,
+        episodes: Vec<Episode>,
+    },
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -44,6 +50,7 @@ pub enum ContextId {
     Album(AlbumId<'static>),
     Artist(ArtistId<'static>),
     Tracks(TracksId),
+    Show(ShowId<'static>),
 }
 
 #[derive(Clone, Debug)]
@@ -65,6 +72,10 @@ pub struct SearchResults {
     pub artists: Vec<Artist>,
     pub albums: Vec<Album>,
     pub playlists: Vec<Playlist>,
+    #[serde(default)]
+    pub shows: Vec<Show>,
+    #[serde(default)]
+    pub episodes: Vec<Episode>,
 }
 
 #[derive(Debug)]
@@ -77,21 +88,45 @@ pub enum TrackOrder {
     Duration,
 }
 
-#[derive(Debug, Clone)]
-/// A Spotify item (track, album, artist, playlist)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A Spotify item (track, album, artist, playlist, show)
 pub enum Item {
     Track(Track),
     Album(Album),
     Artist(Artist),
     Playlist(Playlist),
+    Show(Show),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ItemId {
     Track(TrackId<'static>),
     Album(AlbumId<'static>),
     Artist(ArtistId<'static>),
     Playlist(PlaylistId<'static>),
+    Show(ShowId<'static>),
+}
+
+impl Item {
+    pub fn id(&self) -> ItemId {
+        match self {
+            Self::Track(track) => ItemId::Track(track.id.clone()),
+            Self::Album(album) => ItemId::Album(album.id.clone()),
+            Self::Artist(artist) => ItemId::Artist(artist.id.clone()),
+            Self::Playlist(playlist) => ItemId::Playlist(playlist.id.clone()),
+            Self::Show(show) => ItemId::Show(show.id.clone()),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Track(track) => &track.name,
+            Self::Album(album) => &album.name,
+            Self::Artist(artist) => &artist.name,
+            Self::Playlist(playlist) => &playlist.name,
+            Self::Show(show) => &show.name,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -157,6 +192,29 @@ pub struct Playlist {
     pub desc: String,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+/// A Spotify show (podcast)
+pub struct Show {
+    pub id: ShowId<'static>,
+    pub name: String,
+    pub publisher: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+/// A Spotify episode of a show
+pub struct Episode {
+    pub id: EpisodeId<'static>,
+    pub name: String,
+    pub show: Show,
+    pub duration: std::time::Duration,
+    pub release_date: String,
+    pub explicit: bool,
+    /// the position to resume the episode from, `None` if the episode is not started
+    pub resume_position: Option<std::time::Duration>,
+    /// whether the episode is fully played, based on the user's Spotify listening progress
+    pub fully_played: bool,
+}
+
 #[derive(Clone, Debug)]
 /// A Spotify category
 pub struct Category {
@@ -165,6 +223,17 @@ pub struct Category {
 }
 
 impl Context {
+    /// gets the context's name
+    pub fn name(&self) -> &str {
+        match self {
+            Context::Album { album, .. } => &album.name,
+            Context::Playlist { playlist, .. } => &playlist.name,
+            Context::Artist { artist, .. } => &artist.name,
+            Context::Tracks { desc, .. } => desc,
+            Context::Show { show, .. } => &show.name,
+        }
+    }
+
     /// gets the context's description
     pub fn description(&self) -> String {
         match self {
@@ -192,6 +261,14 @@ impl Context {
             }
             Context::Artist { ref artist, .. } => artist.name.to_string(),
             Context::Tracks { desc, tracks } => format!("{} | {} songs", desc, tracks.len()),
+            Context::Show { show, episodes } => {
+                format!(
+                    "{} | {} | {} episodes",
+                    show.name,
+                    show.publisher,
+                    episodes.len()
+                )
+            }
         }
     }
 }
@@ -203,6 +280,7 @@ impl ContextId {
             Self::Artist(id) => id.uri(),
             Self::Playlist(id) => id.uri(),
             Self::Tracks(id) => id.uri.to_owned(),
+            Self::Show(id) => id.uri(),
         }
     }
 }
@@ -446,6 +524,103 @@ impl std::fmt::Display for Playlist {
     }
 }
 
+impl From<rspotify_model::SimplifiedShow> for Show {
+    fn from(show: rspotify_model::SimplifiedShow) -> Self {
+        Self {
+            id: show.id,
+            name: show.name,
+            publisher: show.publisher,
+        }
+    }
+}
+
+impl From<rspotify_model::FullShow> for Show {
+    fn from(show: rspotify_model::FullShow) -> Self {
+        Self {
+            id: show.id,
+            name: show.name,
+            publisher: show.publisher,
+        }
+    }
+}
+
+impl std::fmt::Display for Show {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} • {}", self.name, self.publisher)
+    }
+}
+
+impl Episode {
+    /// gets the episode's name, including an explicit label
+    pub fn display_name(&self) -> Cow<'_, str> {
+        if self.explicit {
+            Cow::Owned(format!("{} (E)", self.name))
+        } else {
+            Cow::Borrowed(self.name.as_str())
+        }
+    }
+
+    /// gets the episode's listening progress, e.g. `played` or `12:34 left`
+    pub fn progress_info(&self, is_played: bool) -> String {
+        if is_played {
+            return "played".to_string();
+        }
+        match self.resume_position {
+            Some(position) if !position.is_zero() => {
+                let remaining = self.duration.saturating_sub(position).as_secs();
+                format!("{}:{:02} left", remaining / 60, remaining % 60)
+            }
+            _ => String::new(),
+        }
+    }
+
+    /// converts from a `rspotify_model::SimplifiedEpisode` and the show containing it into `Episode`
+    pub fn from_simplified_episode(episode: rspotify_model::SimplifiedEpisode, show: Show) -> Self {
+        let (resume_position, fully_played) = resume_point_info(episode.resume_point);
+        Self {
+            id: episode.id,
+            name: episode.name,
+            show,
+            duration: episode.duration.to_std().unwrap_or_default(),
+            release_date: episode.release_date,
+            explicit: episode.explicit,
+            resume_position,
+            fully_played,
+        }
+    }
+}
+
+impl From<rspotify_model::FullEpisode> for Episode {
+    fn from(episode: rspotify_model::FullEpisode) -> Self {
+        let (resume_position, fully_played) = resume_point_info(episode.resume_point);
+        Self {
+            id: episode.id,
+            name: episode.name,
+            show: episode.show.into(),
+            duration: episode.duration.to_std().unwrap_or_default(),
+            release_date: episode.release_date,
+            explicit: episode.explicit,
+            resume_position,
+            fully_played,
+        }
+    }
+}
+
+impl std::fmt::Display for Episode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} • {}", self.display_name(), self.show.name)
+    }
+}
+
+fn resume_point_info(
+    resume_point: Option<rspotify_model::ResumePoint>,
+) -> (Option<std::time::Duration>, bool) {
+    match resume_point {
+        Some(p) => (p.resume_position.to_std().ok(), p.fully_played),
+        None => (None, false),
+    }
+}
+
 impl From<rspotify_model::category::Category> for Category {
     fn from(c: rspotify_model::category::Category) -> Self {
         Self {
@@ -1,4 +1,5 @@
 use super::model::*;
+use super::sleep_timer::SleepTimer;
 
 /// Player state
 #[derive(Default, Debug)]
@@ -12,6 +13,13 @@ pub struct PlayerState {
     pub buffered_playback: Option<PlaybackMetadata>,
 
     pub queue: Option<rspotify_model::CurrentUserQueue>,
+    /// Upcoming tracks managed by the application.
+    ///
+    /// The Web API can only append items to Spotify's queue, so the application keeps its own
+    /// (editable) queue, whose tracks are added to Spotify's queue one by one when the current track is about to end.
+    pub local_queue: Vec<Track>,
+
+    pub sleep_timer: Option<SleepTimer>,
 }
 
 impl PlayerState {
@@ -56,6 +64,16 @@ impl PlayerState {
         }
     }
 
+    pub fn current_playing_episode(&self) -> Option<&rspotify_model::FullEpisode> {
+        match self.playback {
+            None => None,
+            Some(ref playback) => match playback.item {
+                Some(rspotify::model::PlayableItem::Episode(ref episode)) => Some(episode),
+                _ => None,
+            },
+        }
+    }
+
     pub fn playback_progress(&self) -> Option<chrono::Duration> {
         match self.playback {
             None => None,
@@ -89,6 +107,9 @@ impl PlayerState {
                         rspotify_model::Type::Artist => Some(ContextId::Artist(
                             ArtistId::from_uri(&uri).ok()?.into_static(),
                         )),
+                        rspotify_model::Type::Show => {
+                            Some(ContextId::Show(ShowId::from_uri(&uri).ok()?.into_static()))
+                        }
                         _ => None,
                     }
                 }
@@ -0,0 +1,28 @@
+use parking_lot::{Condvar, Mutex};
+
+/// A notifier to wake the UI renderer up when a new frame needs to be drawn,
+/// e.g. after the application's state changes
+#[derive(Default)]
+pub struct RenderNotifier {
+    is_dirty: Mutex<bool>,
+    cond: Condvar,
+}
+
+impl RenderNotifier {
+    /// Request the renderer to draw a new frame
+    pub fn notify(&self) {
+        *self.is_dirty.lock() = true;
+        self.cond.notify_one();
+    }
+
+    /// Block until a new frame is requested or the timeout elapses.
+    ///
+    /// Return whether a new frame is requested.
+    pub fn wait(&self, timeout: std::time::Duration) -> bool {
+        let mut is_dirty = self.is_dirty.lock();
+        if !*is_dirty {
+            self.cond.wait_for(&mut is_dirty, timeout);
+        }
+        std::mem::take(&mut *is_dirty)
+    }
+}
@@ -0,0 +1,256 @@
+use anyhow::{Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use super::{model::*, State};
+
+/// the durations (in minutes) cycled through by the `CycleSleepTimer` command,
+/// which are followed by the end of the current track and the end of the current context
+pub const SLEEP_TIMER_PRESETS_IN_MINS: [u64; 4] = [15, 30, 45, 60];
+
+/// the maximum duration (in seconds) of a sleep timer
+pub const MAX_SLEEP_TIMER_DURATION_IN_SECS: u64 = 24 * 3600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// A user-specified sleep timer
+pub enum SleepTimerSpec {
+    /// pause the playback after a number of seconds
+    Duration(u64),
+    /// pause the playback at the end of the current track
+    EndOfTrack,
+    /// pause the playback at the end of the current context (album, playlist, etc)
+    EndOfContext,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// When a sleep timer pauses the playback
+pub enum SleepTimerMode {
+    At(chrono::DateTime<chrono::Local>),
+    /// at the end of the given track
+    EndOfTrack(TrackId<'static>),
+    /// at the end of the given context, based on the context's track order
+    EndOfContext(ContextId),
+}
+
+#[derive(Debug, Clone)]
+/// A timer pausing the playback, whose playback volume is faded out over the last minute
+pub struct SleepTimer {
+    pub mode: SleepTimerMode,
+    /// the playback's volume before being faded out, which is restored after pausing the playback
+    pub volume_before_fade: Option<u8>,
+}
+
+impl std::str::FromStr for SleepTimerSpec {
+    type Err = anyhow::Error;
+
+    /// Parse a sleep timer, which is either a duration (e.g. `30` (minutes), `1h30m`, `90s`),
+    /// `track` (end of the current track) or `context` (end of the current album/playlist).
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_lowercase();
+        match s.as_str() {
+            "track" | "end-of-track" => return Ok(Self::EndOfTrack),
+            "context" | "album" | "playlist" | "end-of-context" => return Ok(Self::EndOfContext),
+            _ => {}
+        }
+
+        if let Ok(mins) = s.parse::<u64>() {
+            let secs = mins
+                .checked_mul(60)
+                .with_context(|| format!("sleep timer {s:?} is too long"))?;
+            return Ok(Self::Duration(check_duration(secs)?));
+        }
+
+        let mut secs: u64 = 0;
+        let mut num = String::new();
+        for c in s.chars() {
+            if c.is_ascii_digit() {
+                num.push(c);
+                continue;
+            }
+            let unit = match c {
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                _ => anyhow::bail!("invalid sleep timer {s:?}"),
+            };
+            let n = num
+                .parse::<u64>()
+                .with_context(|| format!("invalid sleep timer {s:?}"))?;
+            secs = n
+                .checked_mul(unit)
+                .and_then(|n| secs.checked_add(n))
+                .with_context(|| format!("sleep timer {s:?} is too long"))?;
+            num.clear();
+        }
+        if !num.is_empty() || secs == 0 {
+            anyhow::bail!("invalid sleep timer {s:?}");
+        }
+        Ok(Self::Duration(check_duration(secs)?))
+    }
+}
+
+/// Check that a sleep timer's duration (in seconds) doesn't exceed the maximum duration
+fn check_duration(secs: u64) -> Result<u64> {
+    if secs > MAX_SLEEP_TIMER_DURATION_IN_SECS {
+        anyhow::bail!(
+            "sleep timer duration must be at most {} hours",
+            MAX_SLEEP_TIMER_DURATION_IN_SECS / 3600
+        );
+    }
+    Ok(secs)
+}
+
+impl std::fmt::Display for SleepTimerMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::At(time) => write!(f, "at {}", time.format("%H:%M:%S")),
+            Self::EndOfTrack(_) => write!(f, "end of track"),
+            Self::EndOfContext(_) => write!(f, "end of context"),
+        }
+    }
+}
+
+impl State {
+    /// Set the sleep timer based on the current playback, `None` to cancel the timer.
+    /// Return the volume to be restored if the previous timer was fading out the playback's volume.
+    pub fn set_sleep_timer(&self, spec: Option<SleepTimerSpec>) -> Result<Option<u8>> {
+        let mut player = self.player.write();
+        let mode = match spec {
+            None => {
+                tracing::info!("Cancel the sleep timer");
+                return Ok(player.sleep_timer.take().and_then(|t| t.volume_before_fade));
+            }
+            Some(SleepTimerSpec::Duration(secs)) => {
+                let secs = check_duration(secs)? as i64;
+                let time = chrono::TimeDelta::try_seconds(secs)
+                    .and_then(|duration| chrono::Local::now().checked_add_signed(duration))
+                    .with_context(|| format!("invalid sleep timer duration: {secs} seconds"))?;
+                SleepTimerMode::At(time)
+            }
+            Some(SleepTimerSpec::EndOfTrack) => SleepTimerMode::EndOfTrack(
+                player
+                    .current_playing_track()
+                    .and_then(|t| t.id.clone())
+                    .context("no playing track found")?,
+            ),
+            Some(SleepTimerSpec::EndOfContext) => SleepTimerMode::EndOfContext(
+                player
+                    .playing_context_id()
+                    .context("no playing context found")?,
+            ),
+        };
+        tracing::info!("Set the sleep timer: {mode}");
+
+        let volume_before_fade = player.sleep_timer.take().and_then(|t| t.volume_before_fade);
+        player.sleep_timer = Some(SleepTimer {
+            mode,
+            volume_before_fade: None,
+        });
+        Ok(volume_before_fade)
+    }
+
+    /// Get the sleep timer following the current one in the presets cycled through by the `CycleSleepTimer` command,
+    /// `None` if the timer should be cancelled
+    pub fn next_sleep_timer_preset(&self) -> Option<SleepTimerSpec> {
+        let mode = self
+            .player
+            .read()
+            .sleep_timer
+            .as_ref()
+            .map(|t| t.mode.clone());
+        match mode {
+            None => Some(SleepTimerSpec::Duration(
+                SLEEP_TIMER_PRESETS_IN_MINS[0] * 60,
+            )),
+            Some(SleepTimerMode::At(time)) => {
+                let remaining_secs = (time - chrono::Local::now()).num_seconds().max(0) as u64;
+                SLEEP_TIMER_PRESETS_IN_MINS
+                    .iter()
+                    .find(|mins| **mins * 60 > remaining_secs)
+                    .map(|mins| SleepTimerSpec::Duration(mins * 60))
+                    .or(Some(SleepTimerSpec::EndOfTrack))
+            }
+            Some(SleepTimerMode::EndOfTrack(_)) => Some(SleepTimerSpec::EndOfContext),
+            Some(SleepTimerMode::EndOfContext(_)) => None,
+        }
+    }
+
+    /// Get the remaining time of the sleep timer.
+    /// Return `None` if there is no sleep timer or the remaining time is unknown
+    /// (e.g. tracks of the timer's context are not retrieved yet).
+    pub fn sleep_timer_remaining(&self) -> Option<chrono::Duration> {
+        let (mode, track, progress, context_id) = {
+            let player = self.player.read();
+            let timer = player.sleep_timer.as_ref()?;
+            let track = player
+                .current_playing_track()
+                .and_then(|t| Some((t.id.clone()?, t.duration)));
+            (
+                timer.mode.clone(),
+                track,
+                player.playback_progress(),
+                player.playing_context_id(),
+            )
+        };
+
+        // the remaining time of the current track if it's the given track, zero otherwise
+        let track_remaining = |id: &TrackId| match (&track, progress) {
+            (Some((track_id, duration)), Some(progress)) if track_id == id => {
+                (*duration - progress).max(chrono::Duration::zero())
+            }
+            _ => chrono::Duration::zero(),
+        };
+
+        match mode {
+            SleepTimerMode::At(time) => Some(time - chrono::Local::now()),
+            SleepTimerMode::EndOfTrack(id) => Some(track_remaining(&id)),
+            SleepTimerMode::EndOfContext(id) => {
+                let Some((track_id, _)) = track.as_ref().filter(|_| context_id == Some(id.clone()))
+                else {
+                    // the context has ended or been switched
+                    return Some(chrono::Duration::zero());
+                };
+
+                let data = self.data.read();
+                let tracks = data.context_tracks(&id)?;
+                let pos = tracks.iter().position(|t| t.id == *track_id)?;
+                let next_tracks_duration = tracks[pos + 1..]
+                    .iter()
+                    .map(|t| t.duration)
+                    .sum::<std::time::Duration>();
+                Some(
+                    track_remaining(track_id)
+                        + chrono::Duration::from_std(next_tracks_duration).ok()?,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_sleep_timer() {
+        assert_eq!(
+            "1h30m".parse::<SleepTimerSpec>().unwrap(),
+            SleepTimerSpec::Duration(5400)
+        );
+        assert_eq!(
+            "30".parse::<SleepTimerSpec>().unwrap(),
+            SleepTimerSpec::Duration(1800)
+        );
+        assert_eq!(
+            "track".parse::<SleepTimerSpec>().unwrap(),
+            SleepTimerSpec::EndOfTrack
+        );
+        for s in [
+            "25h",
+            "999999999999",
+            "99999999999999999999s",
+            "18446744073709551615h1s",
+        ] {
+            assert!(s.parse::<SleepTimerSpec>().is_err(), "{s}");
+        }
+    }
+}
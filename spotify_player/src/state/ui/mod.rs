@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::{config, key};
 
 pub type UIStateGuard<'a> = parking_lot::MutexGuard<'a, UIState>;
@@ -19,19 +21,118 @@ pub struct ImageRenderInfo {
     pub rendered: bool,
 }
 
+/// A multi-selection of tracks in a track table, on which bulk actions can be run
+#[derive(Default, Debug)]
+pub struct TrackSelection {
+    pub track_ids: HashSet<TrackId<'static>>,
+    /// the index of the last toggled row, from which a range selection starts
+    pub anchor: Option<usize>,
+}
+
+impl TrackSelection {
+    pub fn is_empty(&self) -> bool {
+        self.track_ids.is_empty()
+    }
+
+    pub fn contains(&self, id: &TrackId) -> bool {
+        self.track_ids.contains(id)
+    }
+
+    /// Toggle the selection of a track at a given row
+    pub fn toggle(&mut self, id: TrackId<'static>, row: usize) {
+        if !self.track_ids.remove(&id) {
+            self.track_ids.insert(id);
+        }
+        self.anchor = Some(row);
+    }
+
+    /// Get the selected tracks, ordered by their positions in `tracks`
+    pub fn selected_tracks<'a>(&self, tracks: impl IntoIterator<Item = &'a Track>) -> Vec<Track> {
+        tracks
+            .into_iter()
+            .filter(|t| self.contains(&t.id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The progress of a bulk action running on multiple tracks
+#[derive(Debug)]
+pub struct BulkProgress {
+    pub desc: &'static str,
+    pub n_done: usize,
+    pub total: usize,
+}
+
+/// the duration for which a status message is shown
+const STATUS_MESSAGE_DURATION: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// A message about the application's status shown at the bottom of the main layout
+#[derive(Debug)]
+pub struct StatusMessage {
+    pub text: String,
+    pub is_error: bool,
+    pub created_at: std::time::Instant,
+}
+
+impl StatusMessage {
+    pub fn new(text: String, is_error: bool) -> Self {
+        Self {
+            text,
+            is_error,
+            created_at: std::time::Instant::now(),
+        }
+    }
+
+    /// The remaining duration for which the message is shown
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        STATUS_MESSAGE_DURATION
+            .checked_sub(self.created_at.elapsed())
+            .filter(|d| !d.is_zero())
+    }
+}
+
+/// A rendered window's area, which is used to handle mouse events inside the window
+#[derive(Debug, Clone, Copy)]
+pub struct WindowArea {
+    /// the area to render the window's items, excluding the table header (if any)
+    pub rect: tui::layout::Rect,
+    /// the window's focus state, `None` if the window is the only focusable window of the page
+    pub focus: Option<WindowFocus>,
+    pub n_items: usize,
+}
+
 /// Application's UI state
 #[derive(Debug)]
 pub struct UIState {
     pub is_running: bool,
     pub theme: config::Theme,
+    /// name of the integrated player's equalizer preset
+    #[cfg(feature = "streaming")]
+    pub eq_preset: String,
+    #[cfg(feature = "streaming")]
+    pub is_visualizer_shown: bool,
     pub input_key_sequence: key::KeySequence,
 
     pub history: Vec<PageState>,
     pub popup: Option<PopupState>,
 
+    /// tracks selected in the current page's track table
+    pub track_selection: TrackSelection,
+    /// the progress of the running bulk action (if any)
+    pub bulk_progress: Option<BulkProgress>,
+    pub status_message: Option<StatusMessage>,
+
     /// The rectangle representing the playback progress bar,
     /// which is mainly used to handle mouse click events (for seeking command)
     pub playback_progress_bar_rect: tui::layout::Rect,
+    /// The column of the progress bar being dragged by the mouse (if any)
+    pub progress_bar_drag_column: Option<u16>,
+
+    /// The windows rendered in the current page, re-collected on every render
+    pub window_areas: Vec<WindowArea>,
+    /// The area of the rendered list popup (if any)
+    pub popup_list_rect: Option<tui::layout::Rect>,
 
     #[cfg(feature = "image")]
     pub last_cover_image_render_info: ImageRenderInfo,
@@ -46,6 +147,26 @@ impl UIState {
         self.history.last_mut().expect("non-empty history")
     }
 
+    /// Get the playback position corresponding to a column of the progress bar
+    pub fn progress_bar_position(
+        &self,
+        column: u16,
+        duration: chrono::Duration,
+    ) -> chrono::Duration {
+        let rect = self.playback_progress_bar_rect;
+        let width = std::cmp::max(rect.width, 1) as i64;
+        let column = std::cmp::min(column.saturating_sub(rect.x) as i64, width);
+        chrono::Duration::try_milliseconds(duration.num_milliseconds() * column / width).unwrap()
+    }
+
+    /// Get the rendered window (if any) containing a given position
+    pub fn window_area_at(&self, position: tui::layout::Position) -> Option<WindowArea> {
+        self.window_areas
+            .iter()
+            .find(|w| w.rect.contains(position))
+            .copied()
+    }
+
     pub fn new_search_popup(&mut self) {
         self.current_page_mut().select(0);
         self.popup = Some(PopupState::Search {
@@ -56,6 +177,15 @@ impl UIState {
     pub fn new_page(&mut self, page: PageState) {
         self.history.push(page);
         self.popup = None;
+        self.track_selection = TrackSelection::default();
+    }
+
+    pub fn previous_page(&mut self) {
+        if self.history.len() > 1 {
+            self.history.pop();
+            self.popup = None;
+            self.track_selection = TrackSelection::default();
+        }
     }
 
     pub fn new_radio_page(&mut self, uri: &str) {
@@ -133,6 +263,10 @@ impl Default for UIState {
         Self {
             is_running: true,
             theme: Default::default(),
+            #[cfg(feature = "streaming")]
+            eq_preset: String::new(),
+            #[cfg(feature = "streaming")]
+            is_visualizer_shown: false,
             input_key_sequence: key::KeySequence { keys: vec![] },
 
             history: vec![PageState::Library {
@@ -140,7 +274,15 @@ impl Default for UIState {
             }],
             popup: None,
 
+            track_selection: TrackSelection::default(),
+            bulk_progress: None,
+            status_message: None,
+
             playback_progress_bar_rect: Default::default(),
+            progress_bar_drag_column: None,
+
+            window_areas: vec![],
+            popup_list_rect: None,
 
             #[cfg(feature = "image")]
             last_cover_image_render_info: Default::default(),
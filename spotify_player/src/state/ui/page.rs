@@ -1,4 +1,8 @@
-use crate::{state::model::*, ui::single_line_input::LineInput, utils};
+use crate::{
+    state::{model::*, HistoryPeriod},
+    ui::single_line_input::LineInput,
+    utils,
+};
 use tui::widgets::{ListState, TableState};
 
 #[derive(Clone, Debug)]
@@ -21,12 +25,18 @@ pub enum PageState {
         track: String,
         artists: String,
         scroll_offset: usize,
+        /// the offset (in milliseconds) added to the playback progress when syncing the lyric
+        offset_ms: i64,
     },
     Browse {
         state: BrowsePageUIState,
     },
     Queue {
-        scroll_offset: usize,
+        state: QueuePageUIState,
+    },
+    History {
+        period: HistoryPeriod,
+        state: HistoryPageUIState,
     },
     CommandHelp {
         scroll_offset: usize,
@@ -42,6 +52,7 @@ pub enum PageType {
     #[cfg(feature = "lyric-finder")]
     Lyric,
     Queue,
+    History,
     CommandHelp,
 }
 
@@ -50,6 +61,7 @@ pub struct LibraryPageUIState {
     pub playlist_list: ListState,
     pub saved_album_list: ListState,
     pub followed_artist_list: ListState,
+    pub saved_show_list: ListState,
     pub focus: LibraryFocusState,
 }
 
@@ -59,9 +71,26 @@ pub struct SearchPageUIState {
     pub album_list: ListState,
     pub artist_list: ListState,
     pub playlist_list: ListState,
+    pub show_list: ListState,
+    pub episode_list: ListState,
     pub focus: SearchFocusState,
 }
 
+#[derive(Clone, Debug)]
+pub struct HistoryPageUIState {
+    pub top_track_list: ListState,
+    pub top_artist_list: ListState,
+    pub top_album_list: ListState,
+    pub focus: HistoryFocusState,
+}
+
+#[derive(Clone, Debug)]
+pub struct QueuePageUIState {
+    pub local_queue_table: TableState,
+    pub spotify_queue_scroll_offset: usize,
+    pub focus: QueueFocusState,
+}
+
 #[derive(Clone, Debug)]
 pub enum ContextPageType {
     CurrentPlaying,
@@ -85,6 +114,9 @@ pub enum ContextPageUIState {
     Tracks {
         track_table: TableState,
     },
+    Show {
+        episode_table: TableState,
+    },
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -92,6 +124,20 @@ pub enum LibraryFocusState {
     Playlists,
     SavedAlbums,
     FollowedArtists,
+    SavedShows,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HistoryFocusState {
+    Tracks,
+    Artists,
+    Albums,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QueueFocusState {
+    LocalQueue,
+    SpotifyQueue,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -108,6 +154,8 @@ pub enum SearchFocusState {
     Albums,
     Artists,
     Playlists,
+    Shows,
+    Episodes,
 }
 
 #[derive(Clone, Debug)]
@@ -121,6 +169,16 @@ pub enum BrowsePageUIState {
     },
 }
 
+/// A focusable window of a page with multiple windows
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WindowFocus {
+    Library(LibraryFocusState),
+    Search(SearchFocusState),
+    Artist(ArtistFocusState),
+    History(HistoryFocusState),
+    Queue(QueueFocusState),
+}
+
 pub enum MutableWindowState<'a> {
     Table(&'a mut TableState),
     List(&'a mut ListState),
@@ -138,6 +196,7 @@ impl PageState {
             #[cfg(feature = "lyric-finder")]
             PageState::Lyric { .. } => PageType::Lyric,
             PageState::Queue { .. } => PageType::Queue,
+            PageState::History { .. } => PageType::History,
             PageState::CommandHelp { .. } => PageType::CommandHelp,
         }
     }
@@ -155,6 +214,26 @@ impl PageState {
             .map(|state| state.selected())?
     }
 
+    /// Focus a window of the page.
+    ///
+    /// Unlike switching the focus with `Focusable`, this keeps the window's selection.
+    pub fn focus_window(&mut self, window: WindowFocus) {
+        match (self, window) {
+            (Self::Library { state }, WindowFocus::Library(focus)) => state.focus = focus,
+            (Self::Search { state, .. }, WindowFocus::Search(focus)) => state.focus = focus,
+            (
+                Self::Context {
+                    state: Some(ContextPageUIState::Artist { focus: f, .. }),
+                    ..
+                },
+                WindowFocus::Artist(focus),
+            ) => *f = focus,
+            (Self::History { state, .. }, WindowFocus::History(focus)) => state.focus = focus,
+            (Self::Queue { state }, WindowFocus::Queue(focus)) => state.focus = focus,
+            _ => {}
+        }
+    }
+
     /// The currently focused window state of the page.
     pub fn focus_window_state_mut(&mut self) -> Option<MutableWindowState> {
         match self {
@@ -164,6 +243,7 @@ impl PageState {
                         playlist_list,
                         saved_album_list,
                         followed_artist_list,
+                        saved_show_list,
                         focus,
                     },
             } => Some(match focus {
@@ -172,6 +252,7 @@ impl PageState {
                 LibraryFocusState::FollowedArtists => {
                     MutableWindowState::List(followed_artist_list)
                 }
+                LibraryFocusState::SavedShows => MutableWindowState::List(saved_show_list),
             }),
             Self::Search {
                 state:
@@ -180,6 +261,8 @@ impl PageState {
                         album_list,
                         artist_list,
                         playlist_list,
+                        show_list,
+                        episode_list,
                         focus,
                     },
                 ..
@@ -189,6 +272,8 @@ impl PageState {
                 SearchFocusState::Albums => Some(MutableWindowState::List(album_list)),
                 SearchFocusState::Artists => Some(MutableWindowState::List(artist_list)),
                 SearchFocusState::Playlists => Some(MutableWindowState::List(playlist_list)),
+                SearchFocusState::Shows => Some(MutableWindowState::List(show_list)),
+                SearchFocusState::Episodes => Some(MutableWindowState::List(episode_list)),
             },
             Self::Context { state, .. } => state.as_mut().map(|state| match state {
                 ContextPageUIState::Tracks { track_table } => {
@@ -198,6 +283,9 @@ impl PageState {
                     MutableWindowState::Table(track_table)
                 }
                 ContextPageUIState::Album { track_table } => MutableWindowState::Table(track_table),
+                ContextPageUIState::Show { episode_table } => {
+                    MutableWindowState::Table(episode_table)
+                }
                 ContextPageUIState::Artist {
                     top_track_table,
                     album_table,
@@ -217,11 +305,36 @@ impl PageState {
                     Some(MutableWindowState::List(state))
                 }
             },
+            Self::History {
+                state:
+                    HistoryPageUIState {
+                        top_track_list,
+                        top_artist_list,
+                        top_album_list,
+                        focus,
+                    },
+                ..
+            } => Some(match focus {
+                HistoryFocusState::Tracks => MutableWindowState::List(top_track_list),
+                HistoryFocusState::Artists => MutableWindowState::List(top_artist_list),
+                HistoryFocusState::Albums => MutableWindowState::List(top_album_list),
+            }),
             #[cfg(feature = "lyric-finder")]
             Self::Lyric { scroll_offset, .. } => Some(MutableWindowState::Scroll(scroll_offset)),
-            Self::CommandHelp { scroll_offset } | Self::Queue { scroll_offset } => {
-                Some(MutableWindowState::Scroll(scroll_offset))
-            }
+            Self::Queue {
+                state:
+                    QueuePageUIState {
+                        local_queue_table,
+                        spotify_queue_scroll_offset,
+                        focus,
+                    },
+            } => Some(match focus {
+                QueueFocusState::LocalQueue => MutableWindowState::Table(local_queue_table),
+                QueueFocusState::SpotifyQueue => {
+                    MutableWindowState::Scroll(spotify_queue_scroll_offset)
+                }
+            }),
+            Self::CommandHelp { scroll_offset } => Some(MutableWindowState::Scroll(scroll_offset)),
         }
     }
 }
@@ -232,6 +345,7 @@ impl LibraryPageUIState {
             playlist_list: utils::new_list_state(),
             saved_album_list: utils::new_list_state(),
             followed_artist_list: utils::new_list_state(),
+            saved_show_list: utils::new_list_state(),
             focus: LibraryFocusState::Playlists,
         }
     }
@@ -244,11 +358,34 @@ impl SearchPageUIState {
             album_list: utils::new_list_state(),
             artist_list: utils::new_list_state(),
             playlist_list: utils::new_list_state(),
+            show_list: utils::new_list_state(),
+            episode_list: utils::new_list_state(),
             focus: SearchFocusState::Input,
         }
     }
 }
 
+impl QueuePageUIState {
+    pub fn new() -> Self {
+        Self {
+            local_queue_table: utils::new_table_state(),
+            spotify_queue_scroll_offset: 0,
+            focus: QueueFocusState::LocalQueue,
+        }
+    }
+}
+
+impl HistoryPageUIState {
+    pub fn new() -> Self {
+        Self {
+            top_track_list: utils::new_list_state(),
+            top_artist_list: utils::new_list_state(),
+            top_album_list: utils::new_list_state(),
+            focus: HistoryFocusState::Tracks,
+        }
+    }
+}
+
 impl ContextPageType {
     pub fn title(&self) -> String {
         match self {
@@ -258,6 +395,7 @@ impl ContextPageType {
                 ContextId::Album(_) => String::from("Album"),
                 ContextId::Artist(_) => String::from("Artist"),
                 ContextId::Tracks(id) => id.kind.to_owned(),
+                ContextId::Show(_) => String::from("Show"),
             },
         }
     }
@@ -290,6 +428,12 @@ impl ContextPageUIState {
             track_table: utils::new_table_state(),
         }
     }
+
+    pub fn new_show() -> Self {
+        Self::Show {
+            episode_table: utils::new_table_state(),
+        }
+    }
 }
 
 impl<'a> MutableWindowState<'a> {
@@ -310,6 +454,15 @@ impl<'a> MutableWindowState<'a> {
             Self::Scroll(scroll_offset) => Some(**scroll_offset),
         }
     }
+
+    /// The position of the first item rendered in the window
+    pub fn offset(&self) -> usize {
+        match self {
+            Self::List(state) => state.offset(),
+            Self::Table(state) => state.offset(),
+            Self::Scroll(scroll_offset) => **scroll_offset,
+        }
+    }
 }
 
 pub trait Focusable {
@@ -332,6 +485,13 @@ impl Focusable for PageState {
                 state: Some(ContextPageUIState::Artist { focus, .. }),
                 ..
             } => focus.next(),
+            Self::History {
+                state: HistoryPageUIState { focus, .. },
+                ..
+            } => focus.next(),
+            Self::Queue {
+                state: QueuePageUIState { focus, .. },
+            } => focus.next(),
             _ => {}
         }
 
@@ -355,6 +515,13 @@ impl Focusable for PageState {
                 state: Some(ContextPageUIState::Artist { focus, .. }),
                 ..
             } => focus.previous(),
+            Self::History {
+                state: HistoryPageUIState { focus, .. },
+                ..
+            } => focus.previous(),
+            Self::Queue {
+                state: QueuePageUIState { focus, .. },
+            } => focus.previous(),
             _ => {}
         }
 
@@ -391,7 +558,8 @@ impl_focusable!(
     LibraryFocusState,
     [Playlists, SavedAlbums],
     [SavedAlbums, FollowedArtists],
-    [FollowedArtists, Playlists]
+    [FollowedArtists, SavedShows],
+    [SavedShows, Playlists]
 );
 
 impl_focusable!(
@@ -401,11 +569,26 @@ impl_focusable!(
     [RelatedArtists, TopTracks]
 );
 
+impl_focusable!(
+    QueueFocusState,
+    [LocalQueue, SpotifyQueue],
+    [SpotifyQueue, LocalQueue]
+);
+
+impl_focusable!(
+    HistoryFocusState,
+    [Tracks, Artists],
+    [Artists, Albums],
+    [Albums, Tracks]
+);
+
 impl_focusable!(
     SearchFocusState,
     [Input, Tracks],
     [Tracks, Albums],
     [Albums, Artists],
     [Artists, Playlists],
-    [Playlists, Input]
+    [Playlists, Shows],
+    [Shows, Episodes],
+    [Episodes, Input]
 );
@@ -16,29 +16,55 @@ pub enum PopupState {
     UserFollowedArtistList(ListState),
     UserSavedAlbumList(ListState),
     DeviceList(ListState),
+    ProfileList(ListState),
     ArtistList(ArtistPopupAction, Vec<Artist>, ListState),
     ThemeList(Vec<crate::config::Theme>, ListState),
+    /// names of the integrated player's equalizer presets
+    #[cfg(feature = "streaming")]
+    EqPresetList(Vec<String>, ListState),
     ActionList(Box<ActionListItem>, ListState),
     PlaylistCreate {
         name: LineInput,
         desc: LineInput,
         current_field: PlaylistCreateCurrentField,
+        /// tracks added to the playlist after it's created
+        track_ids: Vec<TrackId<'static>>,
+    },
+    CommandLine {
+        line_input: LineInput,
+        /// an error from parsing or running the last entered command
+        error: Option<String>,
+        completion: Option<CommandLineCompletion>,
     },
 }
 
+/// Completions of the command line's last argument
+#[derive(Debug)]
+pub struct CommandLineCompletion {
+    /// the command line's text before the completed argument
+    pub head: String,
+    pub candidates: Vec<String>,
+    /// the index of the selected candidate
+    pub id: usize,
+}
+
 #[derive(Debug, Clone)]
 pub enum ActionListItem {
     Track(Track, Vec<command::Action>),
     Artist(Artist, Vec<command::Action>),
     Album(Album, Vec<command::Action>),
     Playlist(Playlist, Vec<command::Action>),
+    Show(Show, Vec<command::Action>),
+    Episode(Episode, Vec<command::Action>),
+    /// multiple tracks selected in a track table
+    Tracks(Vec<Track>, Vec<command::Action>),
 }
 
 /// An action on an item in a playlist popup list
 #[derive(Debug)]
 pub enum PlaylistPopupAction {
     Browse,
-    AddTrack(TrackId<'static>),
+    AddTracks(Vec<TrackId<'static>>),
 }
 
 /// An action on an item in an artist popup list
@@ -53,13 +79,16 @@ impl PopupState {
     pub fn list_state(&self) -> Option<&ListState> {
         match self {
             Self::DeviceList(list_state) => Some(list_state),
+            Self::ProfileList(list_state) => Some(list_state),
             Self::UserPlaylistList(.., list_state) => Some(list_state),
             Self::UserFollowedArtistList(list_state) => Some(list_state),
             Self::UserSavedAlbumList(list_state) => Some(list_state),
             Self::ArtistList(.., list_state) => Some(list_state),
             Self::ThemeList(.., list_state) => Some(list_state),
+            #[cfg(feature = "streaming")]
+            Self::EqPresetList(.., list_state) => Some(list_state),
             Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            Self::Search { .. } | Self::PlaylistCreate { .. } | Self::CommandLine { .. } => None,
         }
     }
 
@@ -67,13 +96,16 @@ impl PopupState {
     pub fn list_state_mut(&mut self) -> Option<&mut ListState> {
         match self {
             Self::DeviceList(list_state) => Some(list_state),
+            Self::ProfileList(list_state) => Some(list_state),
             Self::UserPlaylistList(.., list_state) => Some(list_state),
             Self::UserFollowedArtistList(list_state) => Some(list_state),
             Self::UserSavedAlbumList(list_state) => Some(list_state),
             Self::ArtistList(.., list_state) => Some(list_state),
             Self::ThemeList(.., list_state) => Some(list_state),
+            #[cfg(feature = "streaming")]
+            Self::EqPresetList(.., list_state) => Some(list_state),
             Self::ActionList(.., list_state) => Some(list_state),
-            Self::Search { .. } | Self::PlaylistCreate { .. } => None,
+            Self::Search { .. } | Self::PlaylistCreate { .. } | Self::CommandLine { .. } => None,
         }
     }
 
@@ -101,15 +133,21 @@ impl ActionListItem {
             ActionListItem::Artist(.., actions) => actions.len(),
             ActionListItem::Album(.., actions) => actions.len(),
             ActionListItem::Playlist(.., actions) => actions.len(),
+            ActionListItem::Show(.., actions) => actions.len(),
+            ActionListItem::Episode(.., actions) => actions.len(),
+            ActionListItem::Tracks(.., actions) => actions.len(),
         }
     }
 
-    pub fn name(&self) -> &str {
+    pub fn name(&self) -> String {
         match self {
-            ActionListItem::Track(track, ..) => &track.name,
-            ActionListItem::Artist(artist, ..) => &artist.name,
-            ActionListItem::Album(album, ..) => &album.name,
-            ActionListItem::Playlist(playlist, ..) => &playlist.name,
+            ActionListItem::Track(track, ..) => track.name.clone(),
+            ActionListItem::Artist(artist, ..) => artist.name.clone(),
+            ActionListItem::Album(album, ..) => album.name.clone(),
+            ActionListItem::Playlist(playlist, ..) => playlist.name.clone(),
+            ActionListItem::Show(show, ..) => show.name.clone(),
+            ActionListItem::Episode(episode, ..) => episode.name.clone(),
+            ActionListItem::Tracks(tracks, ..) => format!("{} selected tracks", tracks.len()),
         }
     }
 
@@ -127,6 +165,15 @@ impl ActionListItem {
             ActionListItem::Playlist(.., actions) => {
                 actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
             }
+            ActionListItem::Show(.., actions) => {
+                actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
+            }
+            ActionListItem::Episode(.., actions) => {
+                actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
+            }
+            ActionListItem::Tracks(.., actions) => {
+                actions.iter().map(|a| format!("{a:?}")).collect::<Vec<_>>()
+            }
         }
     }
 }
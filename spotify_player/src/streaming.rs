@@ -1,13 +1,23 @@
-use crate::{client::Client, config, state::SharedState};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    client::Client,
+    config::{self, NormalizationType},
+    dsp::Dsp,
+    state::{Mutex, SharedState},
+};
 use librespot_connect::spirc::Spirc;
 use librespot_core::{
     config::{ConnectConfig, DeviceType},
-    spotify_id,
+    spotify_id::{self, SpotifyId},
 };
-use librespot_playback::mixer::MixerConfig;
+use librespot_playback::mixer::{MixerConfig, VolumeGetter};
 use librespot_playback::{
     audio_backend,
-    config::{AudioFormat, Bitrate, PlayerConfig},
+    config::{AudioFormat, Bitrate, NormalisationType, PlayerConfig},
     mixer::{self, Mixer},
     player,
 };
@@ -139,6 +149,209 @@ impl PlayerEvent {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+/// The integrated player's track progress reported by its latest event
+struct TrackProgress {
+    track_id: SpotifyId,
+    position_ms: u32,
+    duration_ms: u32,
+    is_playing: bool,
+    reported_at: Instant,
+}
+
+impl TrackProgress {
+    /// Get the track's current position, estimated from the reported position
+    fn position(&self) -> Duration {
+        let position = Duration::from_millis(self.position_ms.into());
+        if self.is_playing {
+            (position + self.reported_at.elapsed())
+                .min(Duration::from_millis(self.duration_ms.into()))
+        } else {
+            position
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FaderState {
+    progress: Option<TrackProgress>,
+    /// whether the previous track has ended, i.e. the playback advances automatically to the next track
+    has_track_ended: bool,
+    /// whether the current track's start is faded in, which is only the case after an automatic advance
+    is_track_faded_in: bool,
+    /// whether the current track's end is faded out, which isn't the case if the playback
+    /// starts (e.g. by seeking) within the track's last `crossfade_duration`
+    is_track_faded_out: bool,
+    /// when the playback started being faded in after resuming
+    fade_in_at: Option<Instant>,
+    /// when the playback started being faded out before pausing
+    fade_out_at: Option<Instant>,
+}
+
+/// A volume fader of the integrated player, which is applied on top of the player's volume.
+///
+/// `librespot` plays one track at a time, so a crossfade is done by fading out
+/// the end of a track and fading in the start of the next track, when the playback
+/// advances automatically from a track to the next one.
+#[derive(Debug)]
+pub struct Fader {
+    crossfade_duration: Duration,
+    pause_fade_duration: Duration,
+    state: Mutex<FaderState>,
+}
+
+impl Fader {
+    pub fn new(device: &config::DeviceConfig) -> Self {
+        Self {
+            crossfade_duration: Duration::from_millis(device.crossfade_duration_ms.into()),
+            pause_fade_duration: Duration::from_millis(device.pause_fade_duration_ms.into()),
+            state: Mutex::new(FaderState::default()),
+        }
+    }
+
+    /// Get the volume factor (from `0.0` to `1.0`) applied on top of the player's volume
+    fn factor(&self) -> f64 {
+        // the ratio of `elapsed` over `duration`, clamped to `[0, 1]`
+        fn ramp(elapsed: Duration, duration: Duration) -> f64 {
+            (elapsed.as_secs_f64() / duration.as_secs_f64()).clamp(0.0, 1.0)
+        }
+
+        let state = self.state.lock();
+        let mut factor = 1.0;
+        if let Some(progress) = state.progress {
+            if !self.crossfade_duration.is_zero() && progress.duration_ms > 0 {
+                let position = progress.position();
+                let remaining = Duration::from_millis(progress.duration_ms.into()) - position;
+                if state.is_track_faded_in {
+                    factor *= ramp(position, self.crossfade_duration);
+                }
+                if state.is_track_faded_out {
+                    factor *= ramp(remaining, self.crossfade_duration);
+                }
+            }
+        }
+        if let Some(t) = state.fade_in_at {
+            factor *= ramp(t.elapsed(), self.pause_fade_duration);
+        }
+        if let Some(t) = state.fade_out_at {
+            factor *= 1.0 - ramp(t.elapsed(), self.pause_fade_duration);
+        }
+        factor
+    }
+
+    /// Update the fader's state based on an integrated player's event
+    fn handle_player_event(&self, event: &player::PlayerEvent) {
+        let mut state = self.state.lock();
+        match *event {
+            player::PlayerEvent::Playing {
+                track_id,
+                position_ms,
+                duration_ms,
+                ..
+            } => {
+                let is_resumed = state
+                    .progress
+                    .is_some_and(|p| p.track_id == track_id && !p.is_playing);
+                if !is_resumed {
+                    self.update_crossfade(&mut state, track_id, position_ms, duration_ms);
+                }
+                state.progress = Some(TrackProgress {
+                    track_id,
+                    position_ms,
+                    duration_ms,
+                    is_playing: true,
+                    reported_at: Instant::now(),
+                });
+                state.fade_out_at = None;
+                if is_resumed && !self.pause_fade_duration.is_zero() {
+                    state.fade_in_at = Some(Instant::now());
+                }
+            }
+            player::PlayerEvent::Paused {
+                track_id,
+                position_ms,
+                duration_ms,
+                ..
+            } => {
+                let is_paused = state
+                    .progress
+                    .is_some_and(|p| p.track_id == track_id && p.is_playing);
+                if !is_paused {
+                    // a new track is loaded or the paused playback is seeked
+                    self.update_crossfade(&mut state, track_id, position_ms, duration_ms);
+                }
+                state.progress = Some(TrackProgress {
+                    track_id,
+                    position_ms,
+                    duration_ms,
+                    is_playing: false,
+                    reported_at: Instant::now(),
+                });
+                state.fade_in_at = None;
+                state.fade_out_at = None;
+            }
+            player::PlayerEvent::EndOfTrack { .. } => {
+                state.has_track_ended = true;
+            }
+            player::PlayerEvent::Stopped { .. } => {
+                *state = FaderState::default();
+            }
+            _ => {}
+        }
+    }
+
+    /// Update whether the current track is crossfaded, based on the playback's track and position
+    /// reported by an event that doesn't resume a paused playback (e.g. a new track or a seek)
+    fn update_crossfade(
+        &self,
+        state: &mut FaderState,
+        track_id: SpotifyId,
+        position_ms: u32,
+        duration_ms: u32,
+    ) {
+        let crossfade_ms = self.crossfade_duration.as_millis();
+        let is_new_track = !state.progress.is_some_and(|p| p.track_id == track_id);
+        // a track is only faded in if the playback advances to it from the end of the previous track,
+        // and not if it's played by the user (e.g. the first track or a skipped-to track) or seeked
+        state.is_track_faded_in =
+            is_new_track && state.has_track_ended && u128::from(position_ms) < crossfade_ms;
+        state.has_track_ended = false;
+        state.is_track_faded_out =
+            u128::from(duration_ms.saturating_sub(position_ms)) > crossfade_ms;
+    }
+
+    /// Fade out the playback before pausing it.
+    /// The playback stays silent until it's paused or the fade-out is cancelled.
+    pub async fn fade_out(&self) {
+        if self.pause_fade_duration.is_zero() {
+            return;
+        }
+        {
+            let mut state = self.state.lock();
+            state.fade_in_at = None;
+            state.fade_out_at = Some(Instant::now());
+        }
+        tokio::time::sleep(self.pause_fade_duration).await;
+    }
+
+    /// Cancel the fade-out started by [`Fader::fade_out`], e.g. if the playback failed to pause
+    pub fn cancel_fade_out(&self) {
+        self.state.lock().fade_out_at = None;
+    }
+}
+
+/// The integrated player's volume, faded by a [`Fader`]
+struct FadedVolume {
+    volume: Box<dyn VolumeGetter + Send>,
+    fader: Arc<Fader>,
+}
+
+impl VolumeGetter for FadedVolume {
+    fn attenuation_factor(&self) -> f64 {
+        self.volume.attenuation_factor() * self.fader.factor()
+    }
+}
+
 fn execute_player_event_hook_command(
     cmd: &config::Command,
     event: PlayerEvent,
@@ -160,7 +373,12 @@ fn execute_player_event_hook_command(
 }
 
 /// Create a new streaming connection
-pub async fn new_connection(client: Client, state: SharedState) -> Spirc {
+pub async fn new_connection(
+    client: Client,
+    state: SharedState,
+    fader: Arc<Fader>,
+    dsp: Arc<Dsp>,
+) -> Spirc {
     let session = client.session().await;
     let configs = config::get_config();
     let device = &configs.app_config.device;
@@ -195,6 +413,13 @@ pub async fn new_connection(client: Client, state: SharedState) -> Spirc {
             .parse::<Bitrate>()
             .unwrap_or_default(),
         normalisation: device.normalization,
+        normalisation_type: match device.normalization_type {
+            NormalizationType::Track => NormalisationType::Track,
+            NormalizationType::Album => NormalisationType::Album,
+            NormalizationType::Auto => NormalisationType::Auto,
+        },
+        normalisation_pregain_db: device.normalization_pregain,
+        gapless: device.gapless,
         ..Default::default()
     };
 
@@ -203,16 +428,20 @@ pub async fn new_connection(client: Client, state: SharedState) -> Spirc {
         session.device_id()
     );
 
-    let (player, mut channel) = player::Player::new(
-        player_config,
-        session.clone(),
-        mixer.get_soft_volume(),
-        move || backend(None, AudioFormat::default()),
-    );
+    let audio_tap = state.audio_tap.clone();
+    let volume_getter = Box::new(FadedVolume {
+        volume: mixer.get_soft_volume(),
+        fader: fader.clone(),
+    });
+    let (player, mut channel) =
+        player::Player::new(player_config, session.clone(), volume_getter, move || {
+            audio_tap.wrap_sink(dsp.wrap_sink(backend(None, AudioFormat::default())))
+        });
 
     let player_event_task = tokio::task::spawn({
         async move {
             while let Some(event) = channel.recv().await {
+                fader.handle_player_event(&event);
                 match PlayerEvent::from_librespot_player_event(event) {
                     Err(err) => {
                         tracing::warn!("Failed to convert a `librespot` player event into `spotify_player` player event: {err:#}");
@@ -234,6 +463,7 @@ pub async fn new_connection(client: Client, state: SharedState) -> Spirc {
                             }
                             _ => {}
                         }
+                        state.render.notify();
                         client.update_playback(&state);
 
                         // execute a player event hook command
@@ -263,3 +493,47 @@ pub async fn new_connection(client: Client, state: SharedState) -> Spirc {
 
     spirc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn playing(track: &str, position_ms: u32) -> player::PlayerEvent {
+        player::PlayerEvent::Playing {
+            play_request_id: 0,
+            track_id: SpotifyId::from_uri(&format!("spotify:track:{track}")).unwrap(),
+            position_ms,
+            duration_ms: 200_000,
+        }
+    }
+
+    #[test]
+    fn crossfade_only_on_automatic_advance() {
+        let fader = Fader::new(&config::DeviceConfig {
+            crossfade_duration_ms: 5000,
+            ..Default::default()
+        });
+        let first = "4uLU6hMCjMI75M1A2tKUQC";
+        let second = "6rqhFgbbKwnb9MLmUQDhG6";
+
+        // the first played track isn't faded in
+        fader.handle_player_event(&playing(first, 0));
+        assert!(fader.factor() > 0.99);
+
+        // seeking into the end of a track doesn't fade it out
+        fader.handle_player_event(&playing(first, 199_000));
+        assert!(fader.factor() > 0.99);
+
+        // a track is faded in after the previous track ends
+        fader.handle_player_event(&player::PlayerEvent::EndOfTrack {
+            play_request_id: 0,
+            track_id: SpotifyId::from_uri(&format!("spotify:track:{first}")).unwrap(),
+        });
+        fader.handle_player_event(&playing(second, 0));
+        assert!(fader.factor() < 0.1);
+
+        // a track skipped to by the user isn't faded in
+        fader.handle_player_event(&playing(first, 0));
+        assert!(fader.factor() > 0.99);
+    }
+}
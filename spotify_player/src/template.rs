@@ -0,0 +1,617 @@
+//! A template language to describe the current playback.
+//!
+//! Templates are shared by the playback window (`playback_format`), desktop notifications
+//! (`notify_format`) and the `get key playback --format` CLI command. A template consists of:
+//! - literal text, with `{{` and `}}` to escape braces and newlines to start new lines
+//! - fields: `{name}`, `{name:spec}`, `{name@style}` or `{name:spec@style}`, in which `spec` is
+//!   `[align][width][.max_width]` with `align` being one of `<`, `>` or `^`
+//! - conditionals: `{?name:template}` (or `{!name:template}`) renders the inner template
+//!   only if the field is (or is not) set
+//!
+//! See `docs/config.md` for the list of supported fields.
+
+use anyhow::{Context as _, Result};
+use rspotify::model::{self as rspotify_model, Id};
+use tui::text::{Line, Span, Text};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    config::{self, Theme},
+    state::{PlaybackMetadata, State},
+};
+
+/// The playback's data used to render a template
+pub struct PlaybackInfo<'a> {
+    pub item: &'a rspotify_model::PlayableItem,
+    pub is_playing: bool,
+    pub progress: Option<chrono::Duration>,
+    pub device_name: &'a str,
+    pub volume: Option<u32>,
+    pub is_muted: bool,
+    pub repeat_state: &'static str,
+    pub shuffle_state: bool,
+    pub extras: PlaybackExtras,
+}
+
+/// The playback's data that is not part of Spotify's playback context
+#[derive(Default)]
+pub struct PlaybackExtras {
+    pub context_name: Option<String>,
+    pub is_liked: Option<bool>,
+    pub sleep_timer_remaining: Option<chrono::Duration>,
+}
+
+impl<'a> PlaybackInfo<'a> {
+    /// Construct the playback's data from the application's buffered playback metadata
+    pub fn new(
+        item: &'a rspotify_model::PlayableItem,
+        metadata: &'a PlaybackMetadata,
+        progress: Option<chrono::Duration>,
+        extras: PlaybackExtras,
+    ) -> Self {
+        Self {
+            item,
+            is_playing: metadata.is_playing,
+            progress,
+            device_name: &metadata.device_name,
+            volume: metadata.mute_state.or(metadata.volume),
+            is_muted: metadata.mute_state.is_some(),
+            repeat_state: if metadata.fake_track_repeat_state {
+                "track (fake)"
+            } else {
+                metadata.repeat_state.into()
+            },
+            shuffle_state: metadata.shuffle_state,
+            extras,
+        }
+    }
+
+    /// Construct the playback's data from a Spotify's playback context.
+    /// Return `None` if the playback has no playing item.
+    pub fn from_playback(
+        playback: &'a rspotify_model::CurrentPlaybackContext,
+        extras: PlaybackExtras,
+    ) -> Option<Self> {
+        Some(Self {
+            item: playback.item.as_ref()?,
+            is_playing: playback.is_playing,
+            progress: playback.progress,
+            device_name: &playback.device.name,
+            volume: playback.device.volume_percent,
+            is_muted: false,
+            repeat_state: playback.repeat_state.into(),
+            shuffle_state: playback.shuffle_state,
+            extras,
+        })
+    }
+}
+
+impl PlaybackExtras {
+    /// Construct the playback's extra data from the application's state
+    pub fn new(state: &State) -> Self {
+        // computed before locking the player state, which is also locked to compute the timer's remaining time
+        let sleep_timer_remaining = state.sleep_timer_remaining();
+        let (track_id, context_id) = {
+            let player = state.player.read();
+            (
+                player.current_playing_track().and_then(|t| t.id.clone()),
+                player.playing_context_id(),
+            )
+        };
+
+        let data = state.data.read();
+        Self {
+            context_name: context_id
+                .and_then(|id| data.caches.context.get(&id.uri()))
+                .map(|c| c.name().to_owned()),
+            is_liked: track_id.map(|id| data.user_data.saved_tracks.contains_key(&id.uri())),
+            sleep_timer_remaining,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Status,
+    Track,
+    Artists,
+    Album,
+    Metadata,
+    Progress,
+    Duration,
+    Volume,
+    Device,
+    Repeat,
+    Shuffle,
+    Context,
+    Liked,
+    Year,
+    TrackNumber,
+    Popularity,
+    Sleep,
+}
+
+const FIELDS: [(&str, Field); 17] = [
+    ("status", Field::Status),
+    ("track", Field::Track),
+    ("artists", Field::Artists),
+    ("album", Field::Album),
+    ("metadata", Field::Metadata),
+    ("progress", Field::Progress),
+    ("duration", Field::Duration),
+    ("volume", Field::Volume),
+    ("device", Field::Device),
+    ("repeat", Field::Repeat),
+    ("shuffle", Field::Shuffle),
+    ("context", Field::Context),
+    ("liked", Field::Liked),
+    ("year", Field::Year),
+    ("track_number", Field::TrackNumber),
+    ("popularity", Field::Popularity),
+    ("sleep", Field::Sleep),
+];
+
+#[derive(Debug, Clone, Copy, Default)]
+enum Align {
+    #[default]
+    Left,
+    Right,
+    Center,
+}
+
+/// A field's format specification: `[align][width][.max_width]`
+#[derive(Debug, Clone, Copy, Default)]
+struct FormatSpec {
+    align: Align,
+    width: Option<usize>,
+    max_width: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Newline,
+    Field {
+        field: Field,
+        spec: FormatSpec,
+        style: Option<String>,
+    },
+    Conditional {
+        field: Field,
+        negated: bool,
+        body: Vec<Node>,
+    },
+}
+
+/// A parsed template
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// Rendered lines, each of which consists of texts and their optional style names
+type Lines<'t> = Vec<Vec<(String, Option<&'t str>)>>;
+
+impl Template {
+    pub fn parse(s: &str) -> Result<Self> {
+        let mut chars = s.chars().peekable();
+        let nodes = parse_nodes(&mut chars, false)?;
+        Ok(Self { nodes })
+    }
+
+    /// Render the template into a styled text, whose styles are taken from `theme`
+    pub fn render_text(&self, info: &PlaybackInfo, theme: &Theme) -> Text<'static> {
+        self.render(info)
+            .into_iter()
+            .map(|spans| {
+                Line::from(
+                    spans
+                        .into_iter()
+                        .map(|(text, style)| match style {
+                            Some(name) => {
+                                Span::styled(text, theme.style_by_name(name).unwrap_or_default())
+                            }
+                            None => Span::raw(text),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Render the template into a plain string
+    pub fn render_string(&self, info: &PlaybackInfo) -> String {
+        self.render(info)
+            .into_iter()
+            .map(|spans| spans.into_iter().map(|(text, _)| text).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn render(&self, info: &PlaybackInfo) -> Lines<'_> {
+        let mut lines = vec![vec![]];
+        render_nodes(&self.nodes, info, &mut lines);
+        // a trailing newline doesn't start a new line
+        if lines.len() > 1 && lines.last().is_some_and(|l| l.is_empty()) {
+            lines.pop();
+        }
+        lines
+    }
+}
+
+/// Parse template nodes until the end of the input or, if `nested` is true,
+/// until the closing brace of a conditional
+fn parse_nodes(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    nested: bool,
+) -> Result<Vec<Node>> {
+    let mut nodes = vec![];
+    let mut text = String::new();
+
+    let mut is_closed = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '}' if nested => {
+                is_closed = true;
+                break;
+            }
+            '}' => anyhow::bail!("unmatched `}}`, use `}}}}` for a literal brace"),
+            '\n' | '{' => {
+                if !text.is_empty() {
+                    nodes.push(Node::Text(std::mem::take(&mut text)));
+                }
+                nodes.push(if c == '\n' {
+                    Node::Newline
+                } else {
+                    parse_placeholder(chars)?
+                });
+            }
+            c => text.push(c),
+        }
+    }
+    if nested && !is_closed {
+        anyhow::bail!("unclosed `{{`");
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+
+    Ok(nodes)
+}
+
+/// Parse a field or a conditional placeholder, whose opening brace is already consumed
+fn parse_placeholder(chars: &mut std::iter::Peekable<std::str::Chars>) -> Result<Node> {
+    let negated = match chars.peek() {
+        Some('?') => Some(false),
+        Some('!') => Some(true),
+        _ => None,
+    };
+    if let Some(negated) = negated {
+        chars.next();
+        let name = take_while(chars, |c| c != ':' && c != '}');
+        let field = parse_field(&name)?;
+        if chars.next() != Some(':') {
+            anyhow::bail!("expected `:` after the conditional field `{name}`");
+        }
+        let body = parse_nodes(chars, true).with_context(|| format!("conditional `{name}`"))?;
+        return Ok(Node::Conditional {
+            field,
+            negated,
+            body,
+        });
+    }
+
+    let name = take_while(chars, |c| c != ':' && c != '@' && c != '}');
+    let field = parse_field(&name)?;
+    let spec = if chars.peek() == Some(&':') {
+        chars.next();
+        parse_format_spec(&take_while(chars, |c| c != '@' && c != '}'))
+            .with_context(|| format!("field `{name}`"))?
+    } else {
+        FormatSpec::default()
+    };
+    let style = if chars.peek() == Some(&'@') {
+        chars.next();
+        let style = take_while(chars, |c| c != '}');
+        // style names are validated against the default theme as they don't depend on the theme
+        if Theme::default().style_by_name(&style).is_none() {
+            anyhow::bail!("unknown style `{style}` for field `{name}`");
+        }
+        Some(style)
+    } else {
+        None
+    };
+    if chars.next() != Some('}') {
+        anyhow::bail!("unclosed `{{` for field `{name}`");
+    }
+
+    Ok(Node::Field { field, spec, style })
+}
+
+fn take_while(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    pred: impl Fn(char) -> bool,
+) -> String {
+    let mut s = String::new();
+    while let Some(&c) = chars.peek() {
+        if !pred(c) {
+            break;
+        }
+        s.push(c);
+        chars.next();
+    }
+    s
+}
+
+fn parse_field(name: &str) -> Result<Field> {
+    match FIELDS.iter().find(|(n, _)| *n == name) {
+        Some((_, field)) => Ok(*field),
+        None => anyhow::bail!(
+            "unknown field `{name}`, expected one of: {}",
+            FIELDS.map(|(n, _)| n).join(", ")
+        ),
+    }
+}
+
+fn parse_format_spec(s: &str) -> Result<FormatSpec> {
+    let (align, s) = match s.chars().next() {
+        Some('<') => (Align::Left, &s[1..]),
+        Some('>') => (Align::Right, &s[1..]),
+        Some('^') => (Align::Center, &s[1..]),
+        _ => (Align::Left, s),
+    };
+    let (width, max_width) = match s.split_once('.') {
+        Some((width, max_width)) => (width, Some(max_width)),
+        None => (s, None),
+    };
+    let parse_number = |s: &str| {
+        s.parse::<usize>()
+            .with_context(|| format!("invalid format specification `{s}`"))
+    };
+
+    Ok(FormatSpec {
+        align,
+        width: if width.is_empty() {
+            None
+        } else {
+            Some(parse_number(width)?)
+        },
+        max_width: max_width.map(parse_number).transpose()?,
+    })
+}
+
+fn render_nodes<'t>(nodes: &'t [Node], info: &PlaybackInfo, lines: &mut Lines<'t>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => push_span(lines, text.clone(), None),
+            Node::Newline => lines.push(vec![]),
+            Node::Field { field, spec, style } => {
+                let (value, _) = field_value(*field, info);
+                let style = style.as_deref().unwrap_or(default_style_name(*field));
+                push_span(lines, apply_format_spec(value, spec), Some(style));
+            }
+            Node::Conditional {
+                field,
+                negated,
+                body,
+            } => {
+                let (_, is_set) = field_value(*field, info);
+                if is_set != *negated {
+                    render_nodes(body, info, lines);
+                }
+            }
+        }
+    }
+}
+
+fn push_span<'t>(lines: &mut Lines<'t>, text: String, style: Option<&'t str>) {
+    if !text.is_empty() {
+        lines
+            .last_mut()
+            .expect("non-empty lines")
+            .push((text, style));
+    }
+}
+
+fn apply_format_spec(value: String, spec: &FormatSpec) -> String {
+    let mut value = value;
+    if let Some(max_width) = spec.max_width {
+        if value.width() > max_width {
+            // keep the value's leading characters fitting in the maximum width with the ellipsis
+            let mut width = 0;
+            value = value
+                .chars()
+                .take_while(|c| {
+                    width += c.width().unwrap_or_default();
+                    width < max_width
+                })
+                .collect();
+            if max_width > 0 {
+                value.push('…');
+            }
+        }
+    }
+
+    let len = value.width();
+    match spec.width {
+        Some(width) if width > len => {
+            let padding = width - len;
+            let (left, right) = match spec.align {
+                Align::Left => (0, padding),
+                Align::Right => (padding, 0),
+                Align::Center => (padding / 2, padding - padding / 2),
+            };
+            format!("{}{value}{}", " ".repeat(left), " ".repeat(right))
+        }
+        _ => value,
+    }
+}
+
+/// The theme's component style used to render a field without a specified style
+fn default_style_name(field: Field) -> &'static str {
+    match field {
+        Field::Status => "playback_status",
+        Field::Track => "playback_track",
+        Field::Artists => "playback_artists",
+        Field::Album | Field::Context => "playback_album",
+        _ => "playback_metadata",
+    }
+}
+
+/// Get a field's value and whether the field is set
+fn field_value(field: Field, info: &PlaybackInfo) -> (String, bool) {
+    let set = |value: String| {
+        let is_set = !value.is_empty();
+        (value, is_set)
+    };
+    let unset = || (String::new(), false);
+
+    // an episode is rendered with its show's publisher as `{artists}` and its show as `{album}`
+    match field {
+        Field::Status => (
+            if info.is_playing {
+                config::get_config().app_config.play_icon.clone()
+            } else {
+                config::get_config().app_config.pause_icon.clone()
+            },
+            info.is_playing,
+        ),
+        Field::Track => set(match info.item {
+            rspotify_model::PlayableItem::Track(track) if track.explicit => {
+                format!("{} (E)", track.name)
+            }
+            rspotify_model::PlayableItem::Track(track) => track.name.clone(),
+            rspotify_model::PlayableItem::Episode(episode) if episode.explicit => {
+                format!("{} (E)", episode.name)
+            }
+            rspotify_model::PlayableItem::Episode(episode) => episode.name.clone(),
+        }),
+        Field::Artists => set(match info.item {
+            rspotify_model::PlayableItem::Track(track) => {
+                crate::utils::map_join(&track.artists, |a| &a.name, ", ")
+            }
+            rspotify_model::PlayableItem::Episode(episode) => episode.show.publisher.clone(),
+        }),
+        Field::Album => set(match info.item {
+            rspotify_model::PlayableItem::Track(track) => track.album.name.clone(),
+            rspotify_model::PlayableItem::Episode(episode) => episode.show.name.clone(),
+        }),
+        Field::Metadata => (
+            format!(
+                "repeat: {} | shuffle: {} | volume: {} | device: {}{}",
+                info.repeat_state,
+                info.shuffle_state,
+                volume_text(info),
+                info.device_name,
+                match info.extras.sleep_timer_remaining {
+                    Some(remaining) => format!(" | sleep: {}", sleep_text(remaining)),
+                    None => String::new(),
+                },
+            ),
+            true,
+        ),
+        Field::Progress => match info.progress {
+            Some(progress) => set(crate::utils::format_duration(&progress)),
+            None => unset(),
+        },
+        Field::Duration => set(crate::utils::format_duration(&match info.item {
+            rspotify_model::PlayableItem::Track(track) => track.duration,
+            rspotify_model::PlayableItem::Episode(episode) => episode.duration,
+        })),
+        Field::Volume => match info.volume {
+            Some(_) => set(volume_text(info)),
+            None => unset(),
+        },
+        Field::Device => set(info.device_name.to_owned()),
+        Field::Repeat => (info.repeat_state.to_owned(), info.repeat_state != "off"),
+        Field::Shuffle => (
+            if info.shuffle_state { "on" } else { "off" }.to_owned(),
+            info.shuffle_state,
+        ),
+        Field::Context => set(info.extras.context_name.clone().unwrap_or_default()),
+        Field::Liked => match info.extras.is_liked {
+            Some(true) => (config::get_config().app_config.liked_icon.clone(), true),
+            _ => unset(),
+        },
+        Field::Year => {
+            let release_date = match info.item {
+                rspotify_model::PlayableItem::Track(track) => track.album.release_date.as_deref(),
+                rspotify_model::PlayableItem::Episode(episode) => Some(&*episode.release_date),
+            };
+            set(release_date
+                .and_then(|d| d.split('-').next())
+                .unwrap_or_default()
+                .to_owned())
+        }
+        Field::TrackNumber => match info.item {
+            rspotify_model::PlayableItem::Track(track) => set(track.track_number.to_string()),
+            rspotify_model::PlayableItem::Episode(_) => unset(),
+        },
+        Field::Popularity => match info.item {
+            rspotify_model::PlayableItem::Track(track) => set(track.popularity.to_string()),
+            rspotify_model::PlayableItem::Episode(_) => unset(),
+        },
+        Field::Sleep => match info.extras.sleep_timer_remaining {
+            Some(remaining) => set(sleep_text(remaining)),
+            None => unset(),
+        },
+    }
+}
+
+fn volume_text(info: &PlaybackInfo) -> String {
+    let volume = info.volume.unwrap_or_default();
+    if info.is_muted {
+        format!("{volume}% (muted)")
+    } else {
+        format!("{volume}%")
+    }
+}
+
+fn sleep_text(remaining: chrono::Duration) -> String {
+    crate::utils::format_duration(&remaining.max(chrono::Duration::zero()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_escaped_braces() {
+        let template = Template::parse("{?liked:{{{liked}}}}").unwrap();
+        let [Node::Conditional { body, .. }] = &template.nodes[..] else {
+            panic!("expected a conditional: {template:?}");
+        };
+        assert!(matches!(
+            &body[..],
+            [Node::Text(l), Node::Field { field: Field::Liked, .. }, Node::Text(r)] if l == "{" && r == "}"
+        ));
+
+        assert!(Template::parse("{?liked:{liked}").is_err());
+        assert!(Template::parse("{track}}").is_err());
+    }
+
+    #[test]
+    fn format_spec_uses_display_width() {
+        let spec = |s| parse_format_spec(s).unwrap();
+        assert_eq!(
+            apply_format_spec("日本語".to_string(), &spec("8")),
+            "日本語  "
+        );
+        assert_eq!(
+            apply_format_spec("日本語".to_string(), &spec(">7")),
+            " 日本語"
+        );
+        assert_eq!(apply_format_spec("日本語".to_string(), &spec(".4")), "日…");
+        assert_eq!(apply_format_spec("abcdef".to_string(), &spec(".4")), "abc…");
+        assert_eq!(apply_format_spec("abc".to_string(), &spec(".3")), "abc");
+    }
+}
@@ -10,19 +10,28 @@ mod popup;
 pub mod single_line_input;
 mod utils;
 
-/// Run the application UI
+/// the interval to report the number of frames drawn by the UI
+const FRAME_STATS_REPORT_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Run the application UI.
+///
+/// A new frame is only drawn when it's requested (e.g. upon a state change or a terminal event)
+/// or when a part of the UI needs to be updated over time (e.g. the playback's progress).
 pub fn run(state: SharedState) -> Result<()> {
     let mut terminal = init_ui().context("failed to initialize the application's UI")?;
 
-    let ui_refresh_duration = std::time::Duration::from_millis(
+    // the minimum duration between two consecutive frames
+    let frame_duration = std::time::Duration::from_millis(
         config::get_config().app_config.app_refresh_duration_in_ms,
     );
     let mut last_terminal_size = None;
+    let mut frame_stats = FrameStats::new();
 
     loop {
         {
             let mut ui = state.ui.lock();
             if !ui.is_running {
+                state.flush_play_history();
                 clean_up(terminal).context("clean up UI resources")?;
                 std::process::exit(0);
             }
@@ -48,8 +57,128 @@ pub fn run(state: SharedState) -> Result<()> {
                 tracing::error!("Failed to render the application: {err:#}");
             }
         }
+        frame_stats.n_frames += 1;
+
+        // limit the frame rate, so that bursts of state changes are drawn in a single frame
+        std::thread::sleep(frame_duration);
+
+        // wait until a new frame is requested or the next tick
+        let next_tick =
+            next_tick_duration(&state, frame_duration).map(|d| std::time::Instant::now() + d);
+        loop {
+            let now = std::time::Instant::now();
+            if next_tick.is_some_and(|t| t <= now) {
+                break;
+            }
+            let timeout = next_tick
+                .map_or(FRAME_STATS_REPORT_INTERVAL, |t| t - now)
+                .min(frame_stats.time_until_report());
+            let is_requested = state.render.wait(timeout);
+            frame_stats.report_if_due();
+            if is_requested {
+                break;
+            }
+        }
+    }
+}
+
+/// Get the duration after which a new frame needs to be drawn even if nothing changes,
+/// or `None` if the UI doesn't change over time.
+fn next_tick_duration(
+    state: &SharedState,
+    frame_duration: std::time::Duration,
+) -> Option<std::time::Duration> {
+    let (has_animated_window, status_message_remaining) = {
+        let ui = state.ui.lock();
+        (
+            has_animated_window(state, &ui),
+            ui.status_message.as_ref().and_then(|m| m.remaining()),
+        )
+    };
+    let player = state.player.read();
+    let is_playing = player.playback.as_ref().is_some_and(|p| p.is_playing);
+
+    if is_playing && has_animated_window {
+        return Some(frame_duration);
+    }
+
+    // the playback's progress and the sleep timer's countdown are updated every second
+    let progress_tick = (is_playing || player.sleep_timer.is_some()).then(|| {
+        let progress_ms = player
+            .playback_progress()
+            .map(|p| p.num_milliseconds())
+            .unwrap_or_default();
+        std::time::Duration::from_millis(1000 - progress_ms.rem_euclid(1000) as u64)
+    });
+
+    // the status message is hidden when it expires
+    match (progress_tick, status_message_remaining) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
 
-        std::thread::sleep(ui_refresh_duration);
+/// Check whether the UI has an animated window, which is updated every frame during a playback
+fn has_animated_window(state: &SharedState, ui: &UIState) -> bool {
+    #[cfg(feature = "streaming")]
+    if ui.is_visualizer_shown {
+        return true;
+    }
+    #[cfg(feature = "lyric-finder")]
+    if let PageState::Lyric { track, artists, .. } = ui.current_page() {
+        // only a synced lyric is scrolled along with the playback
+        if let Some(lyric_finder::LyricResult::Some {
+            synced_lyric: Some(_),
+            ..
+        }) = state
+            .data
+            .read()
+            .caches
+            .lyrics
+            .get(&format!("{track} {artists}"))
+        {
+            return true;
+        }
+    }
+    #[cfg(not(feature = "lyric-finder"))]
+    let _ = state;
+    #[cfg(not(any(feature = "streaming", feature = "lyric-finder")))]
+    let _ = ui;
+
+    false
+}
+
+/// Statistics of the frames drawn by the UI, which are logged periodically to measure
+/// the rendering's cost (see `scripts/bench_idle_frames`)
+struct FrameStats {
+    n_frames: usize,
+    started_at: std::time::Instant,
+}
+
+impl FrameStats {
+    fn new() -> Self {
+        Self {
+            n_frames: 0,
+            started_at: std::time::Instant::now(),
+        }
+    }
+
+    fn time_until_report(&self) -> std::time::Duration {
+        FRAME_STATS_REPORT_INTERVAL.saturating_sub(self.started_at.elapsed())
+    }
+
+    fn report_if_due(&mut self) {
+        let elapsed = self.started_at.elapsed();
+        if elapsed < FRAME_STATS_REPORT_INTERVAL {
+            return;
+        }
+        tracing::info!(
+            "Drew {} frames in the last {}s ({:.1} frames per minute)",
+            self.n_frames,
+            elapsed.as_secs(),
+            self.n_frames as f64 * 60.0 / elapsed.as_secs_f64()
+        );
+        *self = Self::new();
     }
 }
 
@@ -90,11 +219,62 @@ fn render_application(frame: &mut Frame, state: &SharedState, ui: &mut UIStateGu
     // of the playback window
     let rect = playback::render_playback_window(frame, state, ui, rect);
 
+    let rect = render_bulk_progress(frame, ui, rect);
+
+    let rect = render_status_message(frame, ui, rect);
+
     let (rect, is_active) = popup::render_popup(frame, state, ui, rect);
 
     render_main_layout(is_active, frame, state, ui, rect);
 }
 
+/// Render the progress of the running bulk action (if any) at the bottom of the given rectangle
+fn render_bulk_progress(frame: &mut Frame, ui: &UIStateGuard, rect: Rect) -> Rect {
+    let Some(ref progress) = ui.bulk_progress else {
+        return rect;
+    };
+
+    let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
+    let ratio = if progress.total == 0 {
+        1.0
+    } else {
+        (progress.n_done as f64 / progress.total as f64).min(1.0)
+    };
+    frame.render_widget(
+        LineGauge::default()
+            .filled_style(ui.theme.playback_progress_bar())
+            .ratio(ratio)
+            .label(format!(
+                "{}: {}/{}",
+                progress.desc, progress.n_done, progress.total
+            )),
+        chunks[1],
+    );
+    chunks[0]
+}
+
+/// Render the status message (if not expired) at the bottom of the given rectangle
+fn render_status_message(frame: &mut Frame, ui: &UIStateGuard, rect: Rect) -> Rect {
+    let Some(ref message) = ui.status_message else {
+        return rect;
+    };
+    if message.remaining().is_none() {
+        return rect;
+    }
+
+    let chunks = Layout::vertical([Constraint::Fill(0), Constraint::Length(1)]).split(rect);
+    let style = if message.is_error {
+        Style::default().fg(Color::Red)
+    } else {
+        ui.theme.page_desc()
+    };
+    frame.render_widget(
+        Paragraph::new(message.text.as_str()).style(style),
+        chunks[1],
+    );
+    chunks[0]
+}
+
 /// Render the application's main layout
 fn render_main_layout(
     is_active: bool,
@@ -103,6 +283,9 @@ fn render_main_layout(
     ui: &mut UIStateGuard,
     rect: Rect,
 ) {
+    // the rendered windows are re-collected on every render
+    ui.window_areas.clear();
+
     let page_type = ui.current_page().page_type();
     match page_type {
         PageType::Library => page::render_library_page(is_active, frame, state, ui, rect),
@@ -111,7 +294,8 @@ fn render_main_layout(
         PageType::Browse => page::render_browse_page(is_active, frame, state, ui, rect),
         #[cfg(feature = "lyric-finder")]
         PageType::Lyric => page::render_lyric_page(is_active, frame, state, ui, rect),
-        PageType::Queue => page::render_queue_page(frame, state, ui, rect),
+        PageType::Queue => page::render_queue_page(is_active, frame, state, ui, rect),
+        PageType::History => page::render_history_page(is_active, frame, state, ui, rect),
         PageType::CommandHelp => page::render_commands_help_page(frame, ui, rect),
     }
 }
@@ -46,8 +46,8 @@ pub fn render_search_page(
     let search_input_rect = chunks[0];
     let rect = chunks[1];
 
-    // track/album/artist/playlist search results layout (2x2 table)
-    let chunks = Layout::vertical([Constraint::Ratio(1, 2); 2])
+    // track/album/artist/playlist/show/episode search results layout (3x2 table)
+    let chunks = Layout::vertical([Constraint::Ratio(1, 3); 3])
         .split(rect)
         .iter()
         .flat_map(|rect| {
@@ -75,6 +75,15 @@ pub fn render_search_page(
     );
     let playlist_rect =
         construct_and_render_block("Playlists", &ui.theme, Borders::TOP, frame, chunks[3]);
+    let show_rect = construct_and_render_block(
+        "Shows",
+        &ui.theme,
+        Borders::TOP | Borders::RIGHT,
+        frame,
+        chunks[4],
+    );
+    let episode_rect =
+        construct_and_render_block("Episodes", &ui.theme, Borders::TOP, frame, chunks[5]);
 
     // 3. Construct the page's widgets
     let (track_list, n_tracks) = {
@@ -142,6 +151,36 @@ pub fn render_search_page(
         utils::construct_list_widget(&ui.theme, playlist_items, is_active)
     };
 
+    let (show_list, n_shows) = {
+        let show_items = search_results
+            .map(|s| {
+                s.shows
+                    .iter()
+                    .map(|a| (a.to_string(), false))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let is_active = is_active && focus_state == SearchFocusState::Shows;
+
+        utils::construct_list_widget(&ui.theme, show_items, is_active)
+    };
+
+    let (episode_list, n_episodes) = {
+        let episode_items = search_results
+            .map(|s| {
+                s.episodes
+                    .iter()
+                    .map(|a| (a.to_string(), false))
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        let is_active = is_active && focus_state == SearchFocusState::Episodes;
+
+        utils::construct_list_widget(&ui.theme, episode_items, is_active)
+    };
+
     // 4. Render the page's widgets
     // Render the query input box
     frame.render_widget(
@@ -149,6 +188,19 @@ pub fn render_search_page(
         search_input_rect,
     );
 
+    // Record the page's windows to handle mouse events inside them
+    for (rect, focus, n_items) in [
+        (search_input_rect, SearchFocusState::Input, 0),
+        (track_rect, SearchFocusState::Tracks, n_tracks),
+        (album_rect, SearchFocusState::Albums, n_albums),
+        (artist_rect, SearchFocusState::Artists, n_artists),
+        (playlist_rect, SearchFocusState::Playlists, n_playlists),
+        (show_rect, SearchFocusState::Shows, n_shows),
+        (episode_rect, SearchFocusState::Episodes, n_episodes),
+    ] {
+        utils::record_window_area(ui, rect, 0, Some(WindowFocus::Search(focus)), n_items);
+    }
+
     // Render the search result windows.
     // Need mutable access to the list/table states stored inside the page state for rendering.
     let page_state = match ui.current_page_mut() {
@@ -183,6 +235,20 @@ pub fn render_search_page(
         n_playlists,
         &mut page_state.playlist_list,
     );
+    utils::render_list_window(
+        frame,
+        show_list,
+        show_rect,
+        n_shows,
+        &mut page_state.show_list,
+    );
+    utils::render_list_window(
+        frame,
+        episode_list,
+        episode_rect,
+        n_episodes,
+        &mut page_state.episode_list,
+    );
 }
 
 pub fn render_context_page(
@@ -297,6 +363,17 @@ pub fn render_context_page(
                         &data,
                     );
                 }
+                Context::Show { episodes, .. } => {
+                    render_episode_table(
+                        frame,
+                        rect,
+                        is_active,
+                        state,
+                        ui.search_filtered_items(episodes),
+                        ui,
+                        &data,
+                    );
+                }
             }
         }
         None => {
@@ -322,13 +399,15 @@ pub fn render_library_page(
     };
 
     // 2. Construct the page's layout
-    // Horizontally split the library page into 3 windows:
+    // Horizontally split the library page into 4 windows:
     // - a playlists window
     // - a saved albums window
     // - a followed artists window
+    // - a saved shows window
     let chunks = Layout::horizontal([
-        Constraint::Percentage(40),
-        Constraint::Percentage(40),
+        Constraint::Percentage(30),
+        Constraint::Percentage(30),
+        Constraint::Percentage(20),
         Constraint::Percentage(20),
     ])
     .split(rect);
@@ -346,8 +425,14 @@ pub fn render_library_page(
         frame,
         chunks[1],
     );
-    let artist_rect =
-        construct_and_render_block("Artists", &ui.theme, Borders::ALL, frame, chunks[2]);
+    let artist_rect = construct_and_render_block(
+        "Artists",
+        &ui.theme,
+        Borders::TOP | Borders::LEFT | Borders::BOTTOM,
+        frame,
+        chunks[2],
+    );
+    let show_rect = construct_and_render_block("Shows", &ui.theme, Borders::ALL, frame, chunks[3]);
 
     // 3. Construct the page's widgets
     // Construct the playlist window
@@ -377,8 +462,26 @@ pub fn render_library_page(
             .collect(),
         is_active && focus_state == LibraryFocusState::FollowedArtists,
     );
+    // Construct the saved show window
+    let (show_list, n_shows) = utils::construct_list_widget(
+        &ui.theme,
+        ui.search_filtered_items(&data.user_data.saved_shows)
+            .into_iter()
+            .map(|s| (s.to_string(), curr_context_uri == Some(s.id.uri())))
+            .collect(),
+        is_active && focus_state == LibraryFocusState::SavedShows,
+    );
 
     // 4. Render the page's widgets
+    for (rect, focus, n_items) in [
+        (playlist_rect, LibraryFocusState::Playlists, n_playlists),
+        (album_rect, LibraryFocusState::SavedAlbums, n_albums),
+        (artist_rect, LibraryFocusState::FollowedArtists, n_artists),
+        (show_rect, LibraryFocusState::SavedShows, n_shows),
+    ] {
+        utils::record_window_area(ui, rect, 0, Some(WindowFocus::Library(focus)), n_items);
+    }
+
     // Render the library page's windows.
     // Will need mutable access to the list/table states stored inside the page state for rendering.
     let page_state = match ui.current_page_mut() {
@@ -407,6 +510,13 @@ pub fn render_library_page(
         n_artists,
         &mut page_state.followed_artist_list,
     );
+    utils::render_list_window(
+        frame,
+        show_list,
+        show_rect,
+        n_shows,
+        &mut page_state.saved_show_list,
+    );
 }
 
 pub fn render_browse_page(
@@ -461,6 +571,7 @@ pub fn render_browse_page(
     };
 
     // 4. Render the page's widget
+    utils::record_window_area(ui, rect, 0, None, len);
     let list_state = match ui.current_page_mut().focus_window_state_mut() {
         Some(MutableWindowState::List(list_state)) => list_state,
         _ => return,
@@ -477,6 +588,11 @@ pub fn render_lyric_page(
     rect: Rect,
 ) {
     // 1. Get data
+    let progress_ms = state
+        .player
+        .read()
+        .playback_progress()
+        .map(|p| p.num_milliseconds());
     let data = state.data.read();
 
     // 2. Construct the page's layout
@@ -484,16 +600,18 @@ pub fn render_lyric_page(
     let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
 
     // 3. Construct the page's widgets
-    let (track, artists, scroll_offset) = match ui.current_page_mut() {
+    let current_line_style = ui.theme.current_playing();
+    let (track, artists, scroll_offset, offset_ms) = match ui.current_page_mut() {
         PageState::Lyric {
             track,
             artists,
             scroll_offset,
-        } => (track, artists, scroll_offset),
+            offset_ms,
+        } => (track, artists, scroll_offset, *offset_ms),
         _ => return,
     };
 
-    let (desc, lyric) = match data.caches.lyrics.get(&format!("{track} {artists}")) {
+    let (desc, lyric, synced_lyric) = match data.caches.lyrics.get(&format!("{track} {artists}")) {
         None => {
             frame.render_widget(Paragraph::new("Loading..."), rect);
             return;
@@ -506,11 +624,44 @@ pub fn render_lyric_page(
             track,
             artists,
             lyric,
-        }) => (format!("{track} by {artists}"), format!("\n{lyric}")),
+            synced_lyric,
+        }) => (format!("{track} by {artists}"), lyric, synced_lyric),
+    };
+
+    let (desc, lines) = match synced_lyric {
+        // a synced lyric is scrolled automatically to keep the current line in the middle of the page
+        Some(synced_lyric) => {
+            let current_line = progress_ms.and_then(|p| synced_lyric.line_index_at(p + offset_ms));
+            if let Some(id) = current_line {
+                *scroll_offset = id.saturating_sub(chunks[1].height as usize / 2);
+            }
+
+            let lines = synced_lyric
+                .lines
+                .iter()
+                .enumerate()
+                .map(|(id, l)| {
+                    let line = Line::from(l.text.as_str());
+                    if Some(id) == current_line {
+                        line.style(current_line_style)
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let desc = if offset_ms == 0 {
+                desc
+            } else {
+                format!("{desc} (offset: {:+.2}s)", offset_ms as f64 / 1000.0)
+            };
+            (desc, lines)
+        }
+        None => (desc, lyric.lines().map(Line::from).collect::<Vec<_>>()),
     };
 
     // update the scroll offset so that it doesn't exceed the lyric's length
-    let n_rows = lyric.matches('\n').count() + 1;
+    let n_rows = lines.len() + 1;
     if *scroll_offset >= n_rows {
         *scroll_offset = n_rows - 1;
     }
@@ -522,7 +673,10 @@ pub fn render_lyric_page(
 
     // render lyric text
     frame.render_widget(
-        Paragraph::new(lyric).scroll((scroll_offset as u16, 0)),
+        Paragraph::new(Text::from_iter(
+            std::iter::once(Line::default()).chain(lines),
+        ))
+        .scroll((scroll_offset as u16, 0)),
         chunks[1],
     );
 }
@@ -598,6 +752,7 @@ pub fn render_commands_help_page(frame: &mut Frame, ui: &mut UIStateGuard, rect:
 }
 
 pub fn render_queue_page(
+    is_active: bool,
     frame: &mut Frame,
     state: &SharedState,
     ui: &mut UIStateGuard,
@@ -630,28 +785,75 @@ pub fn render_queue_page(
 
     // 1. Get data
     let player = state.player.read();
-    let queue = match player.queue {
-        Some(ref q) => &q.queue,
-        None => return,
+    let local_queue = &player.local_queue;
+    let spotify_queue = match player.queue {
+        Some(ref q) => q.queue.as_slice(),
+        None => &[],
     };
-    let scroll_offset = match ui.current_page_mut() {
-        PageState::Queue {
-            ref mut scroll_offset,
-        } => {
-            if !queue.is_empty() && *scroll_offset >= queue.len() {
-                *scroll_offset = queue.len() - 1
+    let (scroll_offset, focus_state) = match ui.current_page_mut() {
+        PageState::Queue { state } => {
+            if !spotify_queue.is_empty() && state.spotify_queue_scroll_offset >= spotify_queue.len()
+            {
+                state.spotify_queue_scroll_offset = spotify_queue.len() - 1
             }
-            *scroll_offset
+            (state.spotify_queue_scroll_offset, state.focus)
         }
         _ => return,
     };
 
     // 2. Construct the page's layout
-    let rect = construct_and_render_block("Queue", &ui.theme, Borders::ALL, frame, rect);
+    // Vertically split the page into 2 windows:
+    // - a window for the application's queue
+    // - a window for Spotify's queue
+    let chunks =
+        Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)]).split(rect);
+    let local_queue_rect =
+        construct_and_render_block("Up Next", &ui.theme, Borders::ALL, frame, chunks[0]);
+    let spotify_queue_rect =
+        construct_and_render_block("Spotify Queue", &ui.theme, Borders::ALL, frame, chunks[1]);
 
-    // 3. Construct the page's widget
-    let queue_table = Table::new(
-        queue
+    // 3. Construct the page's widgets
+    let header = Row::new(vec![
+        Cell::from("#"),
+        Cell::from("Title"),
+        Cell::from("Artists"),
+        Cell::from("Duration"),
+    ])
+    .style(ui.theme.table_header());
+    let widths = [
+        Constraint::Percentage(5),
+        Constraint::Percentage(40),
+        Constraint::Percentage(35),
+        Constraint::Percentage(20),
+    ];
+
+    let local_queue_table = Table::new(
+        local_queue
+            .iter()
+            .enumerate()
+            .map(|(i, t)| {
+                Row::new(vec![
+                    Cell::from(format!("{}", i + 1)),
+                    Cell::from(t.display_name()),
+                    Cell::from(t.artists_info()),
+                    Cell::from(format!(
+                        "{}:{:02}",
+                        t.duration.as_secs() / 60,
+                        t.duration.as_secs() % 60,
+                    )),
+                ])
+            })
+            .collect::<Vec<_>>(),
+        widths,
+    )
+    .header(header.clone())
+    .highlight_style(
+        ui.theme
+            .selection(is_active && focus_state == QueueFocusState::LocalQueue),
+    );
+
+    let spotify_queue_table = Table::new(
+        spotify_queue
             .iter()
             .enumerate()
             .skip(scroll_offset)
@@ -664,25 +866,154 @@ pub fn render_queue_page(
                 ])
             })
             .collect::<Vec<_>>(),
-        [
-            Constraint::Percentage(5),
-            Constraint::Percentage(40),
-            Constraint::Percentage(35),
-            Constraint::Percentage(20),
-        ],
+        widths,
     )
-    .header(
-        Row::new(vec![
-            Cell::from("#"),
-            Cell::from("Title"),
-            Cell::from("Artists"),
-            Cell::from("Duration"),
-        ])
-        .style(ui.theme.table_header()),
+    .header(header);
+
+    // 4. Render page's widgets
+    let n_local_queue_items = local_queue.len();
+    for (rect, focus, n_items) in [
+        (
+            local_queue_rect,
+            QueueFocusState::LocalQueue,
+            n_local_queue_items,
+        ),
+        (
+            spotify_queue_rect,
+            QueueFocusState::SpotifyQueue,
+            spotify_queue.len(),
+        ),
+    ] {
+        utils::record_window_area(ui, rect, 1, Some(WindowFocus::Queue(focus)), n_items);
+    }
+    if let PageState::Queue { state } = ui.current_page_mut() {
+        utils::render_table_window(
+            frame,
+            local_queue_table,
+            local_queue_rect,
+            n_local_queue_items,
+            &mut state.local_queue_table,
+        );
+    }
+    frame.render_widget(spotify_queue_table, spotify_queue_rect);
+}
+
+pub fn render_history_page(
+    is_active: bool,
+    frame: &mut Frame,
+    state: &SharedState,
+    ui: &mut UIStateGuard,
+    rect: Rect,
+) {
+    // 1. Get data
+    let (period, focus_state) = match ui.current_page() {
+        PageState::History { period, state } => (*period, state.focus),
+        _ => return,
+    };
+    let stats = state
+        .data
+        .read()
+        .play_history
+        .stats(period, HISTORY_PAGE_STATS_LIMIT);
+
+    // 2. Construct the page's layout
+    let rect = construct_and_render_block("Play History", &ui.theme, Borders::ALL, frame, rect);
+    let chunks = Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
+    let desc_rect = chunks[0];
+
+    // Horizontally split the remaining area into 3 windows:
+    // - a top tracks window
+    // - a top artists window
+    // - a top albums window
+    let chunks = Layout::horizontal([
+        Constraint::Percentage(40),
+        Constraint::Percentage(30),
+        Constraint::Percentage(30),
+    ])
+    .split(chunks[1]);
+    let track_rect = construct_and_render_block(
+        "Top Tracks",
+        &ui.theme,
+        Borders::TOP | Borders::RIGHT,
+        frame,
+        chunks[0],
+    );
+    let artist_rect = construct_and_render_block(
+        "Top Artists",
+        &ui.theme,
+        Borders::TOP | Borders::RIGHT,
+        frame,
+        chunks[1],
     );
+    let album_rect =
+        construct_and_render_block("Top Albums", &ui.theme, Borders::TOP, frame, chunks[2]);
 
-    // 4. Render page's widget
-    frame.render_widget(queue_table, rect);
+    // 3. Construct the page's widgets
+    let desc = Paragraph::new(format!(
+        "{} plays, {} listened in the last {period}",
+        stats.n_plays,
+        format_duration(&chrono::Duration::milliseconds(stats.played_ms as i64)),
+    ))
+    .style(ui.theme.page_desc());
+
+    let construct_items = |items: &[HistoryStatsItem]| {
+        items
+            .iter()
+            .map(|item| (format!("{} ({} plays)", item.name, item.n_plays), false))
+            .collect::<Vec<_>>()
+    };
+    let (track_list, n_tracks) = utils::construct_list_widget(
+        &ui.theme,
+        construct_items(&stats.top_tracks),
+        is_active && focus_state == HistoryFocusState::Tracks,
+    );
+    let (artist_list, n_artists) = utils::construct_list_widget(
+        &ui.theme,
+        construct_items(&stats.top_artists),
+        is_active && focus_state == HistoryFocusState::Artists,
+    );
+    let (album_list, n_albums) = utils::construct_list_widget(
+        &ui.theme,
+        construct_items(&stats.top_albums),
+        is_active && focus_state == HistoryFocusState::Albums,
+    );
+
+    // 4. Render the page's widgets
+    frame.render_widget(desc, desc_rect);
+
+    for (rect, focus, n_items) in [
+        (track_rect, HistoryFocusState::Tracks, n_tracks),
+        (artist_rect, HistoryFocusState::Artists, n_artists),
+        (album_rect, HistoryFocusState::Albums, n_albums),
+    ] {
+        utils::record_window_area(ui, rect, 0, Some(WindowFocus::History(focus)), n_items);
+    }
+
+    let page_state = match ui.current_page_mut() {
+        PageState::History { state, .. } => state,
+        _ => return,
+    };
+    utils::render_list_window(
+        frame,
+        track_list,
+        track_rect,
+        n_tracks,
+        &mut page_state.top_track_list,
+    );
+    utils::render_list_window(
+        frame,
+        artist_list,
+        artist_rect,
+        n_artists,
+        &mut page_state.top_artist_list,
+    );
+    utils::render_list_window(
+        frame,
+        album_list,
+        album_rect,
+        n_albums,
+        &mut page_state.top_album_list,
+    );
 }
 
 /// Render windows for an artist context page, which includes
@@ -790,6 +1121,21 @@ fn render_artist_context_page_windows(
         data,
     );
 
+    utils::record_window_area(
+        ui,
+        albums_rect,
+        1,
+        Some(WindowFocus::Artist(ArtistFocusState::Albums)),
+        n_albums,
+    );
+    utils::record_window_area(
+        ui,
+        related_artists_rect,
+        0,
+        Some(WindowFocus::Artist(ArtistFocusState::RelatedArtists)),
+        n_artists,
+    );
+
     let (album_table_state, artist_list_state) = match ui.current_page_mut() {
         PageState::Context {
             state:
@@ -849,11 +1195,14 @@ fn render_track_table(
         .into_iter()
         .enumerate()
         .map(|(id, t)| {
-            let (id, style) = if playing_track_uri == t.id.uri() {
+            let (id, mut style) = if playing_track_uri == t.id.uri() {
                 (playing_id.to_string(), ui.theme.current_playing())
             } else {
                 ((id + 1).to_string(), Style::default())
             };
+            if ui.track_selection.contains(&t.id) {
+                style = style.patch(ui.theme.multi_selection());
+            }
             Row::new(vec![
                 Cell::from(if data.user_data.is_liked_track(t) {
                     &configs.app_config.liked_icon
@@ -889,7 +1238,11 @@ fn render_track_table(
         Row::new(vec![
             Cell::from(""),
             Cell::from("#"),
-            Cell::from("Title"),
+            Cell::from(if ui.track_selection.is_empty() {
+                "Title".to_string()
+            } else {
+                format!("Title ({} selected)", ui.track_selection.track_ids.len())
+            }),
             Cell::from("Artists"),
             Cell::from("Album"),
             Cell::from("Duration"),
@@ -899,6 +1252,16 @@ fn render_track_table(
     .column_spacing(2)
     .highlight_style(ui.theme.selection(is_active));
 
+    // the top track table is one of the artist context page's focusable windows
+    let focus = match ui.current_page() {
+        PageState::Context {
+            state: Some(ContextPageUIState::Artist { .. }),
+            ..
+        } => Some(WindowFocus::Artist(ArtistFocusState::TopTracks)),
+        _ => None,
+    };
+    utils::record_window_area(ui, rect, 1, focus, n_tracks);
+
     if let PageState::Context {
         state: Some(state), ..
     } = ui.current_page_mut()
@@ -910,7 +1273,95 @@ fn render_track_table(
             ContextPageUIState::Playlist { track_table } => track_table,
             ContextPageUIState::Album { track_table } => track_table,
             ContextPageUIState::Tracks { track_table } => track_table,
+            ContextPageUIState::Show { .. } => return,
         };
         utils::render_table_window(frame, track_table, rect, n_tracks, track_table_state);
     }
 }
+
+fn render_episode_table(
+    frame: &mut Frame,
+    rect: Rect,
+    is_active: bool,
+    state: &SharedState,
+    episodes: Vec<&Episode>,
+    ui: &mut UIStateGuard,
+    data: &DataReadGuard,
+) {
+    let configs = config::get_config();
+    // get the current playing episode's URI to decorate such episode (if exists) in the episode table
+    let mut playing_episode_uri = "".to_string();
+    let mut playing_id = "";
+    if let Some(ref playback) = state.player.read().playback {
+        if let Some(rspotify_model::PlayableItem::Episode(ref episode)) = playback.item {
+            playing_episode_uri = episode.id.uri();
+
+            playing_id = if playback.is_playing {
+                &configs.app_config.play_icon
+            } else {
+                &configs.app_config.pause_icon
+            };
+        }
+    }
+
+    let n_episodes = episodes.len();
+    let rows = episodes
+        .into_iter()
+        .enumerate()
+        .map(|(id, e)| {
+            let (id, style) = if playing_episode_uri == e.id.uri() {
+                (playing_id.to_string(), ui.theme.current_playing())
+            } else {
+                ((id + 1).to_string(), Style::default())
+            };
+            Row::new(vec![
+                Cell::from(id),
+                Cell::from(e.display_name()),
+                Cell::from(e.release_date.clone()),
+                Cell::from(format!(
+                    "{}:{:02}",
+                    e.duration.as_secs() / 60,
+                    e.duration.as_secs() % 60,
+                )),
+                Cell::from(e.progress_info(data.user_data.is_played_episode(e))),
+            ])
+            .style(style)
+        })
+        .collect::<Vec<_>>();
+
+    let episode_table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Fill(6),
+            Constraint::Length(10),
+            Constraint::Length(8),
+            Constraint::Length(12),
+        ],
+    )
+    .header(
+        Row::new(vec![
+            Cell::from("#"),
+            Cell::from("Title"),
+            Cell::from("Released"),
+            Cell::from("Duration"),
+            Cell::from("Progress"),
+        ])
+        .style(ui.theme.table_header()),
+    )
+    .column_spacing(2)
+    .highlight_style(ui.theme.selection(is_active));
+
+    utils::record_window_area(ui, rect, 1, None, n_episodes);
+
+    if let PageState::Context {
+        state:
+            Some(ContextPageUIState::Show {
+                episode_table: episode_table_state,
+            }),
+        ..
+    } = ui.current_page_mut()
+    {
+        utils::render_table_window(frame, episode_table, rect, n_episodes, episode_table_state);
+    }
+}
@@ -1,9 +1,10 @@
 use super::{utils::construct_and_render_block, *};
 
 /// Render a playback window showing information about the current playback, which includes
-/// - track title, artists, album
+/// - track title, artists, album (or episode title, show's publisher, show)
 /// - playback metadata (playing state, repeat state, shuffle state, volume, device, etc)
 /// - cover image (if `image` feature is enabled)
+/// - audio visualizer (if `streaming` feature is enabled)
 /// - playback progress bar
 pub fn render_playback_window(
     frame: &mut Frame,
@@ -14,9 +15,25 @@ pub fn render_playback_window(
     let (rect, other_rect) = split_rect_for_playback_window(rect);
     let rect = construct_and_render_block("Playback", &ui.theme, Borders::ALL, frame, rect);
 
+    // the visualizer is based on the integrated client's audio
+    #[cfg(feature = "streaming")]
+    let (is_visualizer_shown, visualizer_replaces_cover_image) = {
+        let is_shown = ui.is_visualizer_shown && state.is_streaming_enabled();
+        #[cfg(feature = "image")]
+        let replaces_cover_image = is_shown
+            && config::get_config()
+                .app_config
+                .visualizer_replaces_cover_image;
+        #[cfg(not(feature = "image"))]
+        let replaces_cover_image = false;
+        (is_shown, replaces_cover_image)
+    };
+
+    // computed before locking the player state, which is also locked to compute the playback's extra data
+    let playback_extras = crate::template::PlaybackExtras::new(state);
     let player = state.player.read();
     if let Some(ref playback) = player.playback {
-        if let Some(rspotify::model::PlayableItem::Track(ref track)) = playback.item {
+        if let Some(ref item) = playback.item {
             let (metadata_rect, progress_bar_rect) = {
                 // allocate the progress bar rect
                 let (rect, progress_bar_rect) = {
@@ -48,7 +65,24 @@ pub fn render_playback_window(
                             (hor_chunks[1], ver_chunks[0])
                         };
 
-                        let url = crate::utils::get_track_album_image_url(track).map(String::from);
+                        let url = match item {
+                            rspotify_model::PlayableItem::Track(track) => {
+                                crate::utils::get_track_album_image_url(track)
+                            }
+                            rspotify_model::PlayableItem::Episode(episode) => {
+                                crate::utils::get_episode_image_url(episode)
+                            }
+                        }
+                        .map(String::from);
+                        #[cfg(feature = "streaming")]
+                        let url = if visualizer_replaces_cover_image {
+                            render_visualizer(frame, state, ui, cover_img_rect);
+                            // reset the render info, so the cover image is re-rendered once the visualizer is hidden
+                            ui.last_cover_image_render_info = Default::default();
+                            None
+                        } else {
+                            url
+                        };
                         if let Some(url) = url {
                             let needs_clear = if ui.last_cover_image_render_info.url != url
                                 || ui.last_cover_image_render_info.render_area != cover_img_rect
@@ -97,20 +131,43 @@ pub fn render_playback_window(
                     }
                 };
 
+                // Render the audio visualizer next to the playback's metadata
+                #[cfg(feature = "streaming")]
+                let metadata_rect = if is_visualizer_shown && !visualizer_replaces_cover_image {
+                    let chunks =
+                        Layout::horizontal([Constraint::Fill(0), Constraint::Percentage(40)])
+                            .spacing(1)
+                            .split(metadata_rect);
+                    render_visualizer(frame, state, ui, chunks[1]);
+                    chunks[0]
+                } else {
+                    metadata_rect
+                };
+
                 (metadata_rect, progress_bar_rect)
             };
 
+            let duration = match item {
+                rspotify_model::PlayableItem::Track(track) => track.duration,
+                rspotify_model::PlayableItem::Episode(episode) => episode.duration,
+            };
+            // preview the seek position while the progress bar is being dragged
+            let progress = match ui.progress_bar_drag_column {
+                Some(column) => ui.progress_bar_position(column, duration),
+                None => std::cmp::min(
+                    player.playback_progress().expect("non-empty playback"),
+                    duration,
+                ),
+            };
+
             if let Some(ref playback) = player.buffered_playback {
-                let playback_text = construct_playback_text(ui, track, playback);
+                let playback_text =
+                    construct_playback_text(ui, item, playback, progress, playback_extras);
                 let playback_desc = Paragraph::new(playback_text).wrap(Wrap { trim: false });
                 frame.render_widget(playback_desc, metadata_rect);
             }
 
-            let progress = std::cmp::min(
-                player.playback_progress().expect("non-empty playback"),
-                track.duration,
-            );
-            render_playback_progress_bar(frame, ui, progress, track, progress_bar_rect);
+            render_playback_progress_bar(frame, ui, progress, duration, progress_bar_rect);
         }
     } else {
         // Previously rendered image can result in a weird rendering text,
@@ -146,102 +203,31 @@ pub fn render_playback_window(
 
 fn construct_playback_text(
     ui: &UIStateGuard,
-    track: &rspotify_model::FullTrack,
+    item: &rspotify_model::PlayableItem,
     playback: &PlaybackMetadata,
+    progress: chrono::Duration,
+    extras: crate::template::PlaybackExtras,
 ) -> Text<'static> {
     // Construct a "styled" text (`playback_text`) from playback's data
-    // based on a user-configurable format string (app_config.playback_format)
-    let configs = config::get_config();
-    let format_str = &configs.app_config.playback_format;
-
-    let mut playback_text = Text::default();
-    let mut spans = vec![];
-
-    // this regex is to handle a format argument or a newline
-    let re = regex::Regex::new(r"\{.*?\}|\n").unwrap();
-
-    let mut ptr = 0;
-    for m in re.find_iter(format_str) {
-        let s = m.start();
-        let e = m.end();
-        if ptr < s {
-            spans.push(Span::raw(format_str[ptr..s].to_string()));
-        }
-        ptr = e;
-
-        let (text, style) = match m.as_str() {
-            // upon encountering a newline, create a new `Spans`
-            "\n" => {
-                let mut tmp = vec![];
-                std::mem::swap(&mut tmp, &mut spans);
-                playback_text.lines.push(Line::from(tmp));
-                continue;
-            }
-            "{status}" => (
-                if !playback.is_playing {
-                    &configs.app_config.pause_icon
-                } else {
-                    &configs.app_config.play_icon
-                }
-                .to_owned(),
-                ui.theme.playback_status(),
-            ),
-            "{track}" => (
-                if track.explicit {
-                    format!("{} (E)", track.name)
-                } else {
-                    track.name.clone()
-                },
-                ui.theme.playback_track(),
-            ),
-            "{artists}" => (
-                crate::utils::map_join(&track.artists, |a| &a.name, ", "),
-                ui.theme.playback_artists(),
-            ),
-            "{album}" => (track.album.name.to_owned(), ui.theme.playback_album()),
-            "{metadata}" => (
-                format!(
-                    "repeat: {} | shuffle: {} | volume: {} | device: {}",
-                    if playback.fake_track_repeat_state {
-                        "track (fake)"
-                    } else {
-                        <&'static str>::from(playback.repeat_state)
-                    },
-                    playback.shuffle_state,
-                    match playback.mute_state {
-                        Some(volume) => format!("{volume}% (muted)"),
-                        None => format!("{}%", playback.volume.unwrap_or_default()),
-                    },
-                    playback.device_name,
-                ),
-                ui.theme.playback_metadata(),
-            ),
-            _ => continue,
-        };
-
-        spans.push(Span::styled(text, style));
-    }
-    if ptr < format_str.len() {
-        spans.push(Span::raw(format_str[ptr..].to_string()));
-    }
-    if !spans.is_empty() {
-        playback_text.lines.push(Line::from(spans));
-    }
-
-    playback_text
+    // based on a user-configurable template (app_config.playback_format),
+    // which is parsed upon loading the configurations
+    let info = crate::template::PlaybackInfo::new(item, playback, Some(progress), extras);
+    config::get_config()
+        .templates
+        .playback
+        .render_text(&info, &ui.theme)
 }
 
 fn render_playback_progress_bar(
     frame: &mut Frame,
     ui: &mut UIStateGuard,
     progress: chrono::Duration,
-    track: &rspotify_model::FullTrack,
+    duration: chrono::Duration,
     rect: Rect,
 ) {
     // Negative numbers can sometimes appear from progress.num_seconds() so this stops
     // them coming through into the ratios
-    let ratio =
-        (progress.num_seconds() as f64 / track.duration.num_seconds() as f64).clamp(0.0, 1.0);
+    let ratio = (progress.num_seconds() as f64 / duration.num_seconds() as f64).clamp(0.0, 1.0);
 
     match config::get_config().app_config.progress_bar_type {
         config::ProgressBarType::Line => frame.render_widget(
@@ -252,7 +238,7 @@ fn render_playback_progress_bar(
                     format!(
                         "{}/{}",
                         crate::utils::format_duration(&progress),
-                        crate::utils::format_duration(&track.duration),
+                        crate::utils::format_duration(&duration),
                     ),
                     Style::default().add_modifier(Modifier::BOLD),
                 )),
@@ -266,7 +252,7 @@ fn render_playback_progress_bar(
                     format!(
                         "{}/{}",
                         crate::utils::format_duration(&progress),
-                        crate::utils::format_duration(&track.duration),
+                        crate::utils::format_duration(&duration),
                     ),
                     Style::default().add_modifier(Modifier::BOLD),
                 )),
@@ -329,6 +315,33 @@ fn render_playback_cover_image(state: &SharedState, ui: &mut UIStateGuard) -> Re
     Ok(())
 }
 
+#[cfg(feature = "streaming")]
+fn render_visualizer(frame: &mut Frame, state: &SharedState, ui: &UIStateGuard, rect: Rect) {
+    let style = ui.theme.playback_visualizer();
+    match config::get_config().app_config.visualizer_type {
+        config::VisualizerType::Spectrum => {
+            let bars = state
+                .audio_tap
+                .spectrum(rect.width as usize)
+                .into_iter()
+                .map(|level| Bar::default().value(level).text_value(String::new()))
+                .collect::<Vec<_>>();
+            let chart = BarChart::default()
+                .data(BarGroup::default().bars(&bars))
+                .bar_width(1)
+                .bar_gap(0)
+                .max(100)
+                .bar_style(style);
+            frame.render_widget(chart, rect);
+        }
+        config::VisualizerType::Waveform => {
+            let data = state.audio_tap.waveform(rect.width as usize);
+            let sparkline = Sparkline::default().data(&data).max(100).style(style);
+            frame.render_widget(sparkline, rect);
+        }
+    }
+}
+
 /// Split the given area into two, the first one for the playback window
 /// and the second one for the main application's layout (popup, page, etc).
 fn split_rect_for_playback_window(rect: Rect) -> (Rect, Rect) {
@@ -15,6 +15,8 @@ pub fn render_popup(
     ui: &mut UIStateGuard,
     rect: Rect,
 ) -> (Rect, bool) {
+    ui.popup_list_rect = None;
+
     match ui.popup {
         None => (rect, true),
         Some(ref popup) => match popup {
@@ -22,6 +24,7 @@ pub fn render_popup(
                 name,
                 desc,
                 current_field,
+                track_ids,
             } => {
                 let chunks =
                     Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).split(rect);
@@ -30,8 +33,13 @@ pub fn render_popup(
                     Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
                         .split(chunks[1]);
 
+                let name_title = if track_ids.is_empty() {
+                    "Enter Name for New Playlist:".to_string()
+                } else {
+                    format!("Enter Name for New Playlist ({} tracks):", track_ids.len())
+                };
                 let name_input = construct_and_render_block(
-                    "Enter Name for New Playlist:",
+                    &name_title,
                     &ui.theme,
                     Borders::ALL,
                     frame,
@@ -56,6 +64,58 @@ pub fn render_popup(
                 );
                 (chunks[0], true)
             }
+            PopupState::CommandLine {
+                line_input,
+                error,
+                completion,
+            } => {
+                // the second line shows either an error or the input's completions
+                let second_line = match (error, completion) {
+                    (Some(err), _) => {
+                        Some(Line::styled(err.as_str(), Style::default().fg(Color::Red)))
+                    }
+                    (None, Some(completion)) if completion.candidates.len() > 1 => {
+                        Some(Line::from(
+                            completion
+                                .candidates
+                                .iter()
+                                .enumerate()
+                                .flat_map(|(i, c)| {
+                                    let style = if i == completion.id {
+                                        ui.theme.selection(true)
+                                    } else {
+                                        Style::default()
+                                    };
+                                    [Span::styled(c.as_str(), style), Span::raw("  ")]
+                                })
+                                .collect::<Vec<_>>(),
+                        ))
+                    }
+                    _ => None,
+                };
+
+                let height = if second_line.is_some() { 4 } else { 3 };
+                let chunks =
+                    Layout::vertical([Constraint::Fill(0), Constraint::Length(height)]).split(rect);
+                let rect = construct_and_render_block(
+                    "Command",
+                    &ui.theme,
+                    Borders::ALL,
+                    frame,
+                    chunks[1],
+                );
+                let lines =
+                    Layout::vertical([Constraint::Length(1), Constraint::Fill(0)]).split(rect);
+                let input_chunks = Layout::horizontal([Constraint::Length(1), Constraint::Fill(0)])
+                    .split(lines[0]);
+
+                frame.render_widget(Paragraph::new(":"), input_chunks[0]);
+                frame.render_widget(line_input.widget(true), input_chunks[1]);
+                if let Some(line) = second_line {
+                    frame.render_widget(Paragraph::new(line), lines[1]);
+                }
+                (chunks[0], false)
+            }
             PopupState::Search { query } => {
                 let chunks =
                     Layout::vertical([Constraint::Fill(0), Constraint::Length(3)]).split(rect);
@@ -97,17 +157,38 @@ pub fn render_popup(
                 let rect = render_list_popup(frame, rect, "Devices", items, 5, ui);
                 (rect, false)
             }
+            PopupState::ProfileList(_) => {
+                let active_profile = config::active_profile_name();
+                let items =
+                    std::iter::once(("default".to_string(), active_profile.is_none()))
+                        .chain(
+                            config::get_config().app_config.profiles.iter().map(|p| {
+                                (p.name.clone(), active_profile.as_ref() == Some(&p.name))
+                            }),
+                        )
+                        .collect();
+
+                let rect = render_list_popup(frame, rect, "Profiles", items, 5, ui);
+                (rect, false)
+            }
             PopupState::ThemeList(themes, ..) => {
                 let items = themes.iter().map(|t| (t.name.clone(), false)).collect();
 
                 let rect = render_list_popup(frame, rect, "Themes", items, 7, ui);
                 (rect, false)
             }
+            #[cfg(feature = "streaming")]
+            PopupState::EqPresetList(presets, ..) => {
+                let items = presets.iter().map(|name| (name.clone(), false)).collect();
+
+                let rect = render_list_popup(frame, rect, "Equalizer Presets", items, 7, ui);
+                (rect, false)
+            }
             PopupState::UserPlaylistList(action, _) => {
                 let data = state.data.read();
                 let playlists = match action {
                     PlaylistPopupAction::Browse => data.user_data.playlists.iter().collect(),
-                    PlaylistPopupAction::AddTrack(_) => data.user_data.modifiable_playlists(),
+                    PlaylistPopupAction::AddTracks(_) => data.user_data.modifiable_playlists(),
                 };
                 let items = playlists
                     .into_iter()
@@ -166,6 +247,7 @@ fn render_list_popup(
 
     let rect = construct_and_render_block(title, &ui.theme, Borders::ALL, frame, chunks[1]);
     let (list, len) = utils::construct_list_widget(&ui.theme, items, true);
+    ui.popup_list_rect = Some(rect);
 
     utils::render_list_window(
         frame,
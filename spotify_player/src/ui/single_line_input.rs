@@ -114,4 +114,10 @@ impl LineInput {
     pub fn get_text(&self) -> String {
         self.line.iter().collect()
     }
+
+    /// Replace the input's text and move the cursor to the end of the text
+    pub fn set_text(&mut self, text: &str) {
+        self.line = text.chars().collect();
+        self.cursor = self.line.len();
+    }
 }
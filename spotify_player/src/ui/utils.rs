@@ -112,3 +112,25 @@ pub fn render_table_window(
     adjust_table_state(state, len);
     frame.render_stateful_widget(widget, rect, state);
 }
+
+/// Record a rendered window's area in the UI state, so that mouse events inside the window can be handled.
+///
+/// `header_height` is the number of rows (e.g. a table's header) rendered above the window's items.
+pub fn record_window_area(
+    ui: &mut UIStateGuard,
+    rect: Rect,
+    header_height: u16,
+    focus: Option<WindowFocus>,
+    n_items: usize,
+) {
+    let header_height = std::cmp::min(header_height, rect.height);
+    ui.window_areas.push(WindowArea {
+        rect: Rect {
+            y: rect.y + header_height,
+            height: rect.height - header_height,
+            ..rect
+        },
+        focus,
+        n_items,
+    });
+}
@@ -42,6 +42,15 @@ pub fn get_track_album_image_url(track: &rspotify::model::FullTrack) -> Option<&
     }
 }
 
+/// Get the image URL of an episode, which falls back to its show's image
+pub fn get_episode_image_url(episode: &rspotify::model::FullEpisode) -> Option<&str> {
+    episode
+        .images
+        .first()
+        .or_else(|| episode.show.images.first())
+        .map(|i| i.url.as_str())
+}
+
 pub fn parse_uri(uri: &str) -> Cow<str> {
     let parts = uri.split(':').collect::<Vec<_>>();
     // The below URI probably has a format of `spotify:user:{user_id}:{type}:{id}`,
@@ -0,0 +1,153 @@
+//! A tap of the integrated player's decoded audio, used to render an audio visualizer
+
+use std::{
+    collections::VecDeque,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use librespot_playback::{
+    audio_backend::{Sink, SinkResult},
+    convert::Converter,
+    decoder::AudioPacket,
+    NUM_CHANNELS, SAMPLE_RATE,
+};
+
+use crate::state::Mutex;
+
+/// number of (mono) samples analyzed by the visualizer
+const WINDOW_SIZE: usize = 1024;
+/// the audio is considered stopped if no sample is written within this duration
+const STALE_DURATION: Duration = Duration::from_millis(250);
+/// frequency range (in Hz) of the spectrum
+const MIN_FREQ: f64 = 40.0;
+const MAX_FREQ: f64 = 16000.0;
+/// the loudness range (in dB) of the spectrum, lower levels are shown as empty bars
+const SPECTRUM_DB_RANGE: f64 = 60.0;
+
+#[derive(Debug, Default)]
+struct TapState {
+    /// the latest (mono) samples
+    samples: VecDeque<f32>,
+    last_write: Option<Instant>,
+}
+
+/// A tap of the integrated player's audio, storing the latest samples written into the audio sink
+#[derive(Debug, Default)]
+pub struct AudioTap {
+    state: Mutex<TapState>,
+}
+
+impl AudioTap {
+    /// Wrap an audio sink, so the audio written into the sink is tapped
+    pub fn wrap_sink(self: &Arc<Self>, sink: Box<dyn Sink>) -> Box<dyn Sink> {
+        Box::new(TapSink {
+            sink,
+            tap: self.clone(),
+        })
+    }
+
+    fn push(&self, samples: &[f64]) {
+        let mut state = self.state.lock();
+        for frame in samples.chunks_exact(NUM_CHANNELS.into()) {
+            let mono = frame.iter().sum::<f64>() / frame.len() as f64;
+            state.samples.push_back(mono as f32);
+        }
+        let n_samples = state.samples.len();
+        if n_samples > WINDOW_SIZE {
+            state.samples.drain(..n_samples - WINDOW_SIZE);
+        }
+        state.last_write = Some(Instant::now());
+    }
+
+    /// Get the latest samples, `None` if the audio is stopped
+    fn latest_samples(&self) -> Option<Vec<f32>> {
+        let state = self.state.lock();
+        if state.last_write?.elapsed() > STALE_DURATION || state.samples.len() < WINDOW_SIZE {
+            return None;
+        }
+        Some(state.samples.iter().copied().collect())
+    }
+
+    /// Get the audio's spectrum as `n_bands` levels (from `0` to `100`)
+    /// of logarithmically spaced frequency bands
+    pub fn spectrum(&self, n_bands: usize) -> Vec<u64> {
+        let Some(samples) = self.latest_samples() else {
+            return vec![0; n_bands];
+        };
+
+        // apply a Hann window to reduce the spectral leakage
+        let n = samples.len();
+        let samples = samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| {
+                let w = 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos();
+                f64::from(*s) * w
+            })
+            .collect::<Vec<_>>();
+        // the magnitude of a full-scale sine wave after being windowed
+        let full_scale = n as f64 / 4.0;
+
+        (0..n_bands)
+            .map(|i| {
+                let freq =
+                    MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf((i as f64 + 0.5) / n_bands.max(1) as f64);
+                let db = 20.0 * (goertzel(&samples, freq) / full_scale).max(1e-9).log10();
+                ((db + SPECTRUM_DB_RANGE) / SPECTRUM_DB_RANGE * 100.0).clamp(0.0, 100.0) as u64
+            })
+            .collect()
+    }
+
+    /// Get the audio's waveform as `n_points` peak levels (from `0` to `100`)
+    pub fn waveform(&self, n_points: usize) -> Vec<u64> {
+        let Some(samples) = self.latest_samples() else {
+            return vec![0; n_points];
+        };
+
+        let chunk_size = (samples.len() / n_points.max(1)).max(1);
+        samples
+            .chunks(chunk_size)
+            .take(n_points)
+            .map(|chunk| {
+                let peak = chunk.iter().fold(0.0_f32, |m, s| m.max(s.abs()));
+                (peak.min(1.0) * 100.0) as u64
+            })
+            .collect()
+    }
+}
+
+/// Compute the magnitude of a frequency in the samples using the Goertzel algorithm
+fn goertzel(samples: &[f64], freq: f64) -> f64 {
+    let coeff = 2.0 * (2.0 * std::f64::consts::PI * freq / f64::from(SAMPLE_RATE)).cos();
+    let (mut s1, mut s2) = (0.0, 0.0);
+    for x in samples {
+        let s = x + coeff * s1 - s2;
+        s2 = s1;
+        s1 = s;
+    }
+    (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0).sqrt()
+}
+
+/// An audio sink tapping the audio written into the underlying sink
+struct TapSink {
+    sink: Box<dyn Sink>,
+    tap: Arc<AudioTap>,
+}
+
+impl Sink for TapSink {
+    fn start(&mut self) -> SinkResult<()> {
+        self.sink.start()
+    }
+
+    fn stop(&mut self) -> SinkResult<()> {
+        self.sink.stop()
+    }
+
+    fn write(&mut self, packet: AudioPacket, converter: &mut Converter) -> SinkResult<()> {
+        if let AudioPacket::Samples(ref samples) = packet {
+            self.tap.push(samples);
+        }
+        self.sink.write(packet, converter)
+    }
+}